@@ -1,41 +1,149 @@
-use std::{num::NonZeroU32, sync::Arc};
+use std::{collections::HashSet, num::NonZeroU32, sync::Arc};
 
 use softbuffer::Surface;
 use tiny_skia::{PixmapMut, PixmapPaint, Transform};
-use tokio::{runtime::Handle, task::AbortHandle};
+use tokio::{runtime::Handle, sync::Mutex, task::AbortHandle};
 use vex_v5_qemu_host::{
-    peripherals::{display::Display, touch::Touchscreen},
-    protocol::{
-        geometry::Point2,
-        touch::{TouchData, TouchEvent},
+    peripherals::{
+        controller::{Controller, ControllerAxis, ControllerButton},
+        display::Display,
+        touch::Touchscreen,
     },
+    protocol::{geometry::Point2, touch::TouchEvent},
 };
 use winit::{
     application::ApplicationHandler,
-    dpi::PhysicalSize,
-    event::{ElementState, MouseButton, TouchPhase, WindowEvent},
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{ElementState, KeyEvent, MouseButton, Touch, TouchPhase, WindowEvent},
     event_loop::ActiveEventLoop,
+    keyboard::{KeyCode, PhysicalKey},
     window::{Window, WindowId},
 };
 
+/// Keys that push a joystick axis to either end of its range, as
+/// `(axis, negative key, positive key)`.
+///
+/// WASD drives the left joystick and IJKL drives the right joystick.
+const KEYBOARD_AXES: [(ControllerAxis, KeyCode, KeyCode); 4] = [
+    (ControllerAxis::Axis1, KeyCode::KeyJ, KeyCode::KeyL),
+    (ControllerAxis::Axis2, KeyCode::KeyK, KeyCode::KeyI),
+    (ControllerAxis::Axis3, KeyCode::KeyS, KeyCode::KeyW),
+    (ControllerAxis::Axis4, KeyCode::KeyA, KeyCode::KeyD),
+];
+
+/// Keys bound to each controller button.
+const KEYBOARD_BUTTONS: [(ControllerButton, KeyCode); 12] = [
+    (ControllerButton::A, KeyCode::Space),
+    (ControllerButton::B, KeyCode::KeyB),
+    (ControllerButton::X, KeyCode::KeyX),
+    (ControllerButton::Y, KeyCode::KeyY),
+    (ControllerButton::Up, KeyCode::ArrowUp),
+    (ControllerButton::Down, KeyCode::ArrowDown),
+    (ControllerButton::Left, KeyCode::ArrowLeft),
+    (ControllerButton::Right, KeyCode::ArrowRight),
+    (ControllerButton::L1, KeyCode::KeyQ),
+    (ControllerButton::L2, KeyCode::KeyZ),
+    (ControllerButton::R1, KeyCode::KeyE),
+    (ControllerButton::R2, KeyCode::KeyC),
+];
+
+/// Converts a position in the window to a point on the brain's screen.
+fn screen_point(position: PhysicalPosition<f64>) -> Point2<i16> {
+    Point2 {
+        x: position.x as _,
+        // TODO: determine if we need to make this work with --fullscreen once
+        // we implement that
+        y: (position.y - 32.0) as _,
+    }
+}
+
 pub struct DisplayWindow {
     window: Option<Arc<Window>>,
     task: Option<AbortHandle>,
     display: Option<Display>,
     touch: Touchscreen,
+    /// The ID of the finger touching the window, if any.
+    active_finger: Option<u64>,
+    controller: Arc<Mutex<Controller>>,
+    pressed_keys: HashSet<KeyCode>,
     should_save_imgs: bool,
 }
 
 impl DisplayWindow {
-    pub const fn new(display: Display, touch: Touchscreen, should_save_imgs: bool) -> Self {
+    pub fn new(
+        display: Display,
+        touch: Touchscreen,
+        controller: Arc<Mutex<Controller>>,
+        should_save_imgs: bool,
+    ) -> Self {
         Self {
             window: None,
             task: None,
             display: Some(display),
             touch,
+            active_finger: None,
+            controller,
+            pressed_keys: HashSet::new(),
             should_save_imgs,
         }
     }
+
+    /// Updates the touchscreen in response to a finger touching the window.
+    ///
+    /// The brain's screen can only sense one touch at a time, so any fingers
+    /// that touch the window while another is down are ignored.
+    async fn handle_touch(&mut self, touch: Touch) {
+        let point = screen_point(touch.location);
+
+        match touch.phase {
+            TouchPhase::Started if self.active_finger.is_none() => {
+                self.active_finger = Some(touch.id);
+                self.touch.press(point).await;
+            }
+            TouchPhase::Moved if self.active_finger == Some(touch.id) => {
+                self.touch.set_point(point).await;
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled if self.active_finger == Some(touch.id) => {
+                self.active_finger = None;
+                self.touch.set_point(point).await;
+                self.touch.release().await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Updates the controller in response to a key being pressed or released.
+    async fn handle_key(&mut self, key: KeyCode, state: ElementState) {
+        match state {
+            ElementState::Pressed => self.pressed_keys.insert(key),
+            ElementState::Released => self.pressed_keys.remove(&key),
+        };
+
+        let mut controller = self.controller.lock().await;
+
+        for (axis, negative, positive) in KEYBOARD_AXES {
+            if key == negative || key == positive {
+                let value = match (
+                    self.pressed_keys.contains(&negative),
+                    self.pressed_keys.contains(&positive),
+                ) {
+                    (true, false) => -127,
+                    (false, true) => 127,
+                    _ => 0,
+                };
+
+                controller.set_axis(axis, value).await;
+            }
+        }
+
+        for (button, button_key) in KEYBOARD_BUTTONS {
+            if key == button_key {
+                controller
+                    .set_button(button, state == ElementState::Pressed)
+                    .await;
+            }
+        }
+    }
 }
 
 impl ApplicationHandler for DisplayWindow {
@@ -121,52 +229,36 @@ impl ApplicationHandler for DisplayWindow {
                 event_loop.exit();
             }
             WindowEvent::Touch(touch) => {
-                Handle::current().block_on(async move {
-                    self.touch
-                        .set_data(TouchData {
-                            point: Point2 {
-                                x: touch.location.x as _,
-                                y: (touch.location.y - 32.0) as _, /* TODO: determine if we need
-                                                                    * to make this work with
-                                                                    * --fullscreen once we
-                                                                    * implement that */
-                            },
-                            event: match touch.phase {
-                                TouchPhase::Started | TouchPhase::Moved => TouchEvent::Press,
-                                TouchPhase::Ended | TouchPhase::Cancelled => TouchEvent::Release,
-                            },
-                        })
-                        .await;
-                });
+                Handle::current().block_on(self.handle_touch(touch));
             }
             WindowEvent::CursorMoved {
                 device_id: _,
                 position,
             } => {
-                Handle::current().block_on(async move {
-                    self.touch
-                        .set_point(Point2 {
-                            x: position.x as _,
-                            y: (position.y - 32.0) as _, /* TODO: determine if we need to make
-                                                          * this work with --fullscreen once we
-                                                          * implement that */
-                        })
-                        .await;
-                });
+                let point = screen_point(position);
+                Handle::current().block_on(self.touch.set_point(point));
             }
             WindowEvent::MouseInput {
                 device_id: _,
                 state,
                 button: MouseButton::Left,
             } => {
-                Handle::current().block_on(async move {
-                    self.touch
-                        .set_event(match state {
-                            ElementState::Pressed => TouchEvent::Press,
-                            ElementState::Released => TouchEvent::Release,
-                        })
-                        .await;
-                });
+                Handle::current().block_on(self.touch.set_event(match state {
+                    ElementState::Pressed => TouchEvent::Press,
+                    ElementState::Released => TouchEvent::Release,
+                }));
+            }
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(key),
+                        state,
+                        repeat: false,
+                        ..
+                    },
+                ..
+            } => {
+                Handle::current().block_on(self.handle_key(key, state));
             }
             _ => (),
         }
@@ -0,0 +1,158 @@
+use std::{fs, path::Path, sync::Arc};
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+use serde::Deserialize;
+use tokio::{runtime::Handle, sync::Mutex};
+use vex_v5_qemu_host::peripherals::controller::{Controller, ControllerAxis, ControllerButton};
+
+/// Maps the inputs of a physical gamepad to the fields of a V5 Controller.
+///
+/// Mappings are loaded from TOML files keyed by controller field, such as:
+///
+/// ```toml
+/// axis_3 = "LeftStickY"
+/// button_a = "South"
+/// ```
+///
+/// Any field left out of the file keeps its default mapping.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GamepadMapping {
+    pub axis_1: Axis,
+    pub axis_2: Axis,
+    pub axis_3: Axis,
+    pub axis_4: Axis,
+    pub button_a: Button,
+    pub button_b: Button,
+    pub button_x: Button,
+    pub button_y: Button,
+    pub button_up: Button,
+    pub button_down: Button,
+    pub button_left: Button,
+    pub button_right: Button,
+    pub button_l1: Button,
+    pub button_l2: Button,
+    pub button_r1: Button,
+    pub button_r2: Button,
+}
+
+impl Default for GamepadMapping {
+    /// Lays out the buttons by position, so the face buttons of an Xbox-style
+    /// gamepad land in the same places as on a V5 Controller.
+    fn default() -> Self {
+        Self {
+            axis_1: Axis::RightStickX,
+            axis_2: Axis::RightStickY,
+            axis_3: Axis::LeftStickY,
+            axis_4: Axis::LeftStickX,
+            button_a: Button::East,
+            button_b: Button::South,
+            button_x: Button::North,
+            button_y: Button::West,
+            button_up: Button::DPadUp,
+            button_down: Button::DPadDown,
+            button_left: Button::DPadLeft,
+            button_right: Button::DPadRight,
+            button_l1: Button::LeftTrigger,
+            button_l2: Button::LeftTrigger2,
+            button_r1: Button::RightTrigger,
+            button_r2: Button::RightTrigger2,
+        }
+    }
+}
+
+impl GamepadMapping {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    fn axis(&self, axis: Axis) -> Option<ControllerAxis> {
+        [
+            (self.axis_1, ControllerAxis::Axis1),
+            (self.axis_2, ControllerAxis::Axis2),
+            (self.axis_3, ControllerAxis::Axis3),
+            (self.axis_4, ControllerAxis::Axis4),
+        ]
+        .into_iter()
+        .find_map(|(mapped, controller_axis)| (mapped == axis).then_some(controller_axis))
+    }
+
+    fn button(&self, button: Button) -> Option<ControllerButton> {
+        [
+            (self.button_a, ControllerButton::A),
+            (self.button_b, ControllerButton::B),
+            (self.button_x, ControllerButton::X),
+            (self.button_y, ControllerButton::Y),
+            (self.button_up, ControllerButton::Up),
+            (self.button_down, ControllerButton::Down),
+            (self.button_left, ControllerButton::Left),
+            (self.button_right, ControllerButton::Right),
+            (self.button_l1, ControllerButton::L1),
+            (self.button_l2, ControllerButton::L2),
+            (self.button_r1, ControllerButton::R1),
+            (self.button_r2, ControllerButton::R2),
+        ]
+        .into_iter()
+        .find_map(|(mapped, controller_button)| (mapped == button).then_some(controller_button))
+    }
+}
+
+/// Forwards input from any connected gamepads to a simulated controller.
+///
+/// Gamepad events are read on a dedicated thread, since gilrs only offers a
+/// blocking API.
+pub fn spawn(controller: Arc<Mutex<Controller>>, mapping: GamepadMapping) {
+    let handle = Handle::current();
+
+    std::thread::spawn(move || {
+        let mut gilrs = match Gilrs::new() {
+            Ok(gilrs) => gilrs,
+            Err(err) => {
+                log::warn!("Gamepad support unavailable: {err}");
+                return;
+            }
+        };
+
+        for (_, gamepad) in gilrs.gamepads() {
+            log::info!("Using gamepad \"{}\".", gamepad.name());
+        }
+
+        loop {
+            let Some(event) = gilrs.next_event_blocking(None) else {
+                continue;
+            };
+
+            handle.block_on(async {
+                let mut controller = controller.lock().await;
+
+                match event.event {
+                    EventType::AxisChanged(axis, value, _) => {
+                        if let Some(axis) = mapping.axis(axis) {
+                            controller.set_axis(axis, (value * 127.0) as i8).await;
+                        }
+                    }
+                    EventType::ButtonPressed(button, _) => {
+                        if let Some(button) = mapping.button(button) {
+                            controller.set_button(button, true).await;
+                        }
+                    }
+                    EventType::ButtonReleased(button, _) => {
+                        if let Some(button) = mapping.button(button) {
+                            controller.set_button(button, false).await;
+                        }
+                    }
+                    EventType::Connected => {
+                        log::info!("Gamepad \"{}\" connected.", gilrs.gamepad(event.id).name());
+                    }
+                    EventType::Disconnected => {
+                        log::info!(
+                            "Gamepad \"{}\" disconnected.",
+                            gilrs.gamepad(event.id).name()
+                        );
+                    }
+                    _ => {}
+                }
+            });
+        }
+    });
+}
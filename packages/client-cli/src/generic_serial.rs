@@ -0,0 +1,94 @@
+use std::io;
+
+use tokio::{
+    io::{copy, split, AsyncRead, AsyncWrite},
+    net::TcpListener,
+};
+use vex_v5_qemu_host::devices::generic_serial::GenericSerial;
+
+/// Where the bytes sent to and from a generic serial port end up on the host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SerialTarget {
+    /// A pseudoterminal that other programs can open like a real serial port.
+    #[cfg(unix)]
+    Pty,
+    /// A TCP socket listening on the given port on localhost.
+    Tcp(u16),
+}
+
+/// Bridges a generic serial port to `target` in the background until either
+/// side disconnects.
+pub fn spawn(port: u8, serial: GenericSerial, target: SerialTarget) {
+    tokio::task::spawn(async move {
+        if let Err(err) = attach(port, serial, target).await {
+            log::error!("Generic serial on port {port} disconnected: {err}");
+        }
+    });
+}
+
+async fn attach(port: u8, serial: GenericSerial, target: SerialTarget) -> io::Result<()> {
+    match target {
+        #[cfg(unix)]
+        SerialTarget::Pty => {
+            let (pty, path) = open_pty()?;
+            log::info!(
+                "Generic serial on port {port} is available at {}.",
+                path.display()
+            );
+
+            // Reads and writes go through separate handles to the same PTY so that
+            // a read waiting for data can't hold up writes.
+            let read = tokio::fs::File::from_std(pty.master.try_clone()?.into());
+            let write = tokio::fs::File::from_std(pty.master.into());
+
+            // The slave side is kept open so that reading from the master
+            // doesn't fail while no other program has the PTY open.
+            let _slave = pty.slave;
+
+            bridge(serial, read, write).await
+        }
+        SerialTarget::Tcp(tcp_port) => {
+            let listener = TcpListener::bind(("127.0.0.1", tcp_port)).await?;
+            log::info!("Generic serial on port {port} is listening on 127.0.0.1:{tcp_port}.");
+
+            let (stream, _) = listener.accept().await?;
+            let (read, write) = stream.into_split();
+
+            bridge(serial, read, write).await
+        }
+    }
+}
+
+/// Opens a pseudoterminal in raw mode, so that bytes pass through it
+/// unmodified, and returns it along with the path of its slave device.
+#[cfg(unix)]
+fn open_pty() -> io::Result<(nix::pty::OpenptyResult, std::path::PathBuf)> {
+    use nix::{
+        pty::openpty,
+        sys::termios::{cfmakeraw, tcgetattr, tcsetattr, SetArg},
+        unistd::ttyname,
+    };
+
+    let pty = openpty(None, None)?;
+
+    let mut termios = tcgetattr(&pty.slave)?;
+    cfmakeraw(&mut termios);
+    tcsetattr(&pty.slave, SetArg::TCSANOW, &termios)?;
+
+    let path = ttyname(&pty.slave)?;
+    Ok((pty, path))
+}
+
+async fn bridge(
+    serial: GenericSerial,
+    mut read: impl AsyncRead + Unpin,
+    mut write: impl AsyncWrite + Unpin,
+) -> io::Result<()> {
+    let (mut serial_read, mut serial_write) = split(serial);
+
+    tokio::try_join!(
+        copy(&mut serial_read, &mut write),
+        copy(&mut read, &mut serial_write),
+    )?;
+    Ok(())
+}
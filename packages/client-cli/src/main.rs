@@ -1,24 +1,38 @@
-use std::{option::Option, path::PathBuf, pin::Pin, time::Duration};
+use std::{
+    option::Option,
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use log::LevelFilter;
 use simplelog::{ColorChoice, ConfigBuilder, TermLogger, TerminalMode};
 use tokio::{
-    io::{stdin, stdout, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    io::{stdin, stdout, AsyncRead, AsyncWrite},
     net::TcpListener,
     process::Command,
     sync::Mutex,
-    time::sleep,
 };
 use vex_v5_qemu_host::{
-    brain::{Binary, Brain},
-    peripherals::usb::{UsbRead, UsbWrite},
-    protocol::battery::BatteryData,
+    brain::{Binary, Brain, BrainStatus},
+    devices::{adi::Adi, generic_serial::GenericSerial, radio::Radio},
+    peripherals::{
+        clock::ClockSource,
+        competition::MatchPeriod,
+        sd_card::SdCardStorage,
+        usb::{UsbRead, UsbWrite},
+    },
+    protocol::competition::{CompetitionConnection, CompetitionMode},
 };
 use winit::event_loop::EventLoop;
 
-use crate::display_window::DisplayWindow;
+use crate::{display_window::DisplayWindow, generic_serial::SerialTarget, radio::RadioTarget};
 
 mod display_window;
+#[cfg(feature = "gamepad")]
+mod gamepad;
+mod generic_serial;
+mod radio;
 
 #[cfg(debug_assertions)]
 const DEFAULT_KERNEL: &str = concat!(
@@ -86,6 +100,74 @@ struct Opt {
     #[clap(long)]
     tcp: Option<u16>,
 
+    /// Load a gamepad mapping from a TOML file.
+    ///
+    /// The file maps V5 Controller fields (such as `axis_3` or `button_a`) to
+    /// gamepad axes and buttons (such as `LeftStickY` or `South`). Fields left
+    /// out of the file keep their default mapping.
+    #[cfg(feature = "gamepad")]
+    #[clap(long)]
+    gamepad_mapping: Option<PathBuf>,
+
+    /// Connect the brain to a competition controller.
+    #[clap(long, value_enum)]
+    competition: Option<CompetitionKind>,
+
+    /// Run a scripted match as soon as the simulator starts.
+    ///
+    /// Takes a comma-separated list of `mode:duration` periods, where `mode` is
+    /// one of `disabled`, `autonomous` or `driver`, for example
+    /// `disabled:3s,autonomous:15s,driver:1m45s`. The robot is disabled once
+    /// the last period ends. Passing `--match` on its own, or `--match
+    /// standard`, runs a standard match with 15 seconds of autonomous and 1
+    /// minute 45 seconds of driver control.
+    ///
+    /// Implies `--competition field-control` unless another connection is
+    /// given.
+    #[clap(
+        long = "match",
+        value_name = "TIMELINE",
+        num_args = 0..=1,
+        default_missing_value = "standard",
+        value_parser = parse_match_timeline,
+    )]
+    match_timeline: Option<MatchTimeline>,
+
+    /// Attach a smartport to a generic serial device on the host.
+    ///
+    /// Takes `PORT=TARGET`, where `PORT` is a smartport number from 1 to 21 and
+    /// `TARGET` is either `pty` to create a pseudoterminal (Unix only) or
+    /// `tcp:<port>` to accept a TCP connection on localhost. This allows a
+    /// program on the host to act as a coprocessor for the robot code. Can be
+    /// given more than once.
+    #[clap(long, value_name = "PORT=TARGET", value_parser = parse_generic_serial)]
+    generic_serial: Vec<(u8, SerialTarget)>,
+
+    /// Plug a radio into a smartport and link it with another simulator.
+    ///
+    /// Takes `PORT=TARGET`, where `PORT` is a smartport number from 1 to 21 and
+    /// `TARGET` is either `listen:<port>` to wait for the other simulator on a
+    /// TCP port on localhost or `connect:<address>` to connect to a simulator
+    /// that is listening. The robot code on both brains can then talk to each
+    /// other over VEXlink. Can be given more than once.
+    #[clap(long, value_name = "PORT=TARGET", value_parser = parse_radio)]
+    radio: Vec<(u8, RadioTarget)>,
+
+    /// Insert an SD card backed by a directory on the host.
+    ///
+    /// Files written by the robot code end up in this directory. Without this
+    /// option, the brain reports that no SD card is inserted.
+    #[clap(long, value_name = "DIR")]
+    sd_card: Option<PathBuf>,
+
+    /// Start the brain's clock at a fixed time instead of the host's time.
+    ///
+    /// Takes a number of seconds since the Unix epoch. The clock is advanced by
+    /// the brain from there, so the dates and times seen by the robot code are
+    /// the same on every run.
+    #[clap(long, value_name = "UNIX_SECONDS", value_parser = parse_unix_time)]
+    fixed_time: Option<SystemTime>,
+
     /// Extra arguments to pass to QEMU.
     qemu_args: Vec<String>,
 }
@@ -96,6 +178,24 @@ enum ProsMode {
     Monolith,
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, PartialEq, Eq)]
+enum CompetitionKind {
+    FieldControl,
+    CompetitionSwitch,
+}
+
+impl From<CompetitionKind> for CompetitionConnection {
+    fn from(kind: CompetitionKind) -> Self {
+        match kind {
+            CompetitionKind::FieldControl => Self::FieldControl,
+            CompetitionKind::CompetitionSwitch => Self::CompetitionSwitch,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct MatchTimeline(Vec<MatchPeriod>);
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let mut opt = <Opt as clap::Parser>::parse();
@@ -131,21 +231,109 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
-    let mut brain = Brain::new(
-        qemu,
-        opt.kernel,
-        Binary {
-            path: opt.program,
-            load_addr: opt.load_addr.unwrap_or(0x03800000),
-        },
-        opt.link.map(|link| Binary {
-            path: link,
-            load_addr: opt.link_addr.unwrap(),
-        }),
-    )
-    .unwrap();
+    let main_binary = Binary {
+        path: opt.program,
+        load_addr: opt.load_addr.unwrap_or(0x03800000),
+    };
+    let linked_binary = opt.link.map(|link| Binary {
+        path: link,
+        load_addr: opt.link_addr.unwrap(),
+    });
+
+    let mut brain = Brain::new();
     let peripherals = brain.peripherals.take().unwrap();
 
+    // The keyboard is always available as a fallback input device, so the master
+    // controller starts out connected.
+    let controller = Arc::new(Mutex::new(peripherals.master_controller));
+    controller.lock().await.connect().await;
+
+    // The brain's onboard three-wire ports are always present, even though nothing
+    // is plugged into them.
+    let _onboard_adi = Adi::new(peripherals.onboard_adi);
+
+    let mut smartports = [
+        peripherals.port_1,
+        peripherals.port_2,
+        peripherals.port_3,
+        peripherals.port_4,
+        peripherals.port_5,
+        peripherals.port_6,
+        peripherals.port_7,
+        peripherals.port_8,
+        peripherals.port_9,
+        peripherals.port_10,
+        peripherals.port_11,
+        peripherals.port_12,
+        peripherals.port_13,
+        peripherals.port_14,
+        peripherals.port_15,
+        peripherals.port_16,
+        peripherals.port_17,
+        peripherals.port_18,
+        peripherals.port_19,
+        peripherals.port_20,
+        peripherals.port_21,
+    ]
+    .map(Some);
+    for (number, target) in opt.generic_serial {
+        let port = smartports[number as usize - 1]
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("port {number} is attached more than once"))?;
+
+        generic_serial::spawn(number, GenericSerial::new(port), target);
+    }
+    for (number, target) in opt.radio {
+        let port = smartports[number as usize - 1]
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("port {number} is attached more than once"))?;
+
+        radio::spawn(number, Radio::new(port), target);
+    }
+
+    // Kept alive for the rest of the program so that the brain can keep using the
+    // card.
+    let mut sd_card = peripherals.sd_card;
+    if let Some(dir) = opt.sd_card {
+        sd_card.insert(SdCardStorage::Directory(dir)).await?;
+    }
+
+    let mut clock = peripherals.clock;
+    if let Some(time) = opt.fixed_time {
+        clock.set_source(ClockSource::Fixed(time));
+    }
+
+    let mut competition = peripherals.competition;
+    let connection = opt.competition.map(CompetitionConnection::from).or(opt
+        .match_timeline
+        .is_some()
+        .then_some(CompetitionConnection::FieldControl));
+    if connection.is_some() {
+        competition.set_connection(connection).await;
+    }
+    if let Some(MatchTimeline(periods)) = opt.match_timeline {
+        // Don't let QEMU and the kernel booting eat into the first period.
+        let mut status = brain.status();
+        tokio::task::spawn(async move {
+            if status
+                .wait_for(|status| *status == BrainStatus::Running)
+                .await
+                .is_ok()
+            {
+                competition.run_match(&periods).await;
+            }
+        });
+    }
+
+    #[cfg(feature = "gamepad")]
+    gamepad::spawn(
+        controller.clone(),
+        match opt.gamepad_mapping {
+            Some(path) => gamepad::GamepadMapping::load(&path)?,
+            None => gamepad::GamepadMapping::default(),
+        },
+    );
+
     let tcp_port = opt.tcp;
     tokio::task::spawn(async move {
         let usb_read = peripherals.usb_read;
@@ -166,14 +354,25 @@ async fn main() -> anyhow::Result<()> {
         }
     });
 
+    // The program is only started once every peripheral is set up, so that it
+    // sees them from the moment it boots.
+    brain
+        .run_program(qemu, opt.kernel, main_binary, linked_binary)
+        .await?;
+
     let _ = tokio::task::block_in_place(move || {
         let event_loop = EventLoop::new().unwrap();
-        let mut app = DisplayWindow::new(peripherals.display, peripherals.touch, opt.save_imgs);
+        let mut app = DisplayWindow::new(
+            peripherals.display,
+            peripherals.touch,
+            controller,
+            opt.save_imgs,
+        );
 
         event_loop.run_app(&mut app)
     });
 
-    brain.terminate().await?;
+    brain.kill_program().await?;
 
     std::process::exit(0);
 }
@@ -182,6 +381,130 @@ fn validate_address_range(s: &str) -> Result<u32, String> {
     clap_num::maybe_hex_range(s, 0x03800000, 0x8000000)
 }
 
+fn parse_match_timeline(s: &str) -> Result<MatchTimeline, String> {
+    if s == "standard" {
+        return Ok(MatchTimeline(MatchPeriod::STANDARD_MATCH.to_vec()));
+    }
+
+    s.split(',')
+        .map(|period| {
+            let (mode, duration) = period
+                .split_once(':')
+                .ok_or_else(|| format!("expected `mode:duration`, found `{period}`"))?;
+
+            let mode = match mode.trim() {
+                "disabled" => CompetitionMode::Disabled,
+                "autonomous" | "auton" => CompetitionMode::Autonomous,
+                "driver" => CompetitionMode::Driver,
+                mode => return Err(format!("unknown competition mode `{mode}`")),
+            };
+
+            Ok(MatchPeriod::new(mode, parse_duration(duration.trim())?))
+        })
+        .collect::<Result<_, _>>()
+        .map(MatchTimeline)
+}
+
+/// Splits a `PORT=TARGET` argument into its smartport number and target.
+fn parse_port_target(s: &str) -> Result<(u8, &str), String> {
+    let (port, target) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected `PORT=TARGET`, found `{s}`"))?;
+
+    let port = port
+        .trim()
+        .parse::<u8>()
+        .ok()
+        .filter(|port| (1..=21).contains(port))
+        .ok_or_else(|| format!("invalid smartport `{port}`, expected a number from 1 to 21"))?;
+
+    Ok((port, target.trim()))
+}
+
+fn parse_generic_serial(s: &str) -> Result<(u8, SerialTarget), String> {
+    let (port, target) = parse_port_target(s)?;
+
+    let target = match target {
+        #[cfg(unix)]
+        "pty" => SerialTarget::Pty,
+        target => match target.strip_prefix("tcp:") {
+            Some(tcp_port) => SerialTarget::Tcp(
+                tcp_port
+                    .parse()
+                    .map_err(|_| format!("invalid TCP port `{tcp_port}`"))?,
+            ),
+            None => return Err(format!("unknown generic serial target `{target}`")),
+        },
+    };
+
+    Ok((port, target))
+}
+
+fn parse_radio(s: &str) -> Result<(u8, RadioTarget), String> {
+    let (port, target) = parse_port_target(s)?;
+
+    let target = if let Some(tcp_port) = target.strip_prefix("listen:") {
+        RadioTarget::Listen(
+            tcp_port
+                .parse()
+                .map_err(|_| format!("invalid TCP port `{tcp_port}`"))?,
+        )
+    } else if let Some(addr) = target.strip_prefix("connect:") {
+        RadioTarget::Connect(addr.to_string())
+    } else {
+        return Err(format!("unknown radio target `{target}`"));
+    };
+
+    Ok((port, target))
+}
+
+/// Parses a number of seconds since the Unix epoch.
+fn parse_unix_time(s: &str) -> Result<SystemTime, String> {
+    let seconds = s
+        .parse()
+        .map_err(|_| format!("invalid number of seconds `{s}`"))?;
+
+    UNIX_EPOCH
+        .checked_add(Duration::from_secs(seconds))
+        .ok_or_else(|| format!("time `{s}` is too far in the future"))
+}
+
+/// Parses a duration made up of one or more `<number><unit>` pairs, such as
+/// `1m45s` or `500ms`.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let mut total = Duration::ZERO;
+    let mut rest = s;
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(|| format!("missing unit in duration `{s}`"))?;
+        let (value, tail) = rest.split_at(digits);
+        let value: f64 = value
+            .parse()
+            .map_err(|_| format!("invalid duration `{s}`"))?;
+
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+
+        let seconds = match unit {
+            "ms" => value / 1000.0,
+            "s" => value,
+            "m" => value * 60.0,
+            _ => return Err(format!("unknown unit `{unit}` in duration `{s}`")),
+        };
+        total = Duration::try_from_secs_f64(seconds)
+            .ok()
+            .and_then(|duration| total.checked_add(duration))
+            .ok_or_else(|| format!("duration `{s}` is out of range"))?;
+        rest = tail;
+    }
+
+    Ok(total)
+}
+
 async fn forward_stdio(
     mut usb_read: UsbRead,
     mut usb_write: UsbWrite,
@@ -0,0 +1,43 @@
+use std::io;
+
+use tokio::net::{TcpListener, TcpStream};
+use vex_v5_qemu_host::devices::radio::Radio;
+
+/// How a radio reaches the radio on another simulated brain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RadioTarget {
+    /// Wait for the other brain to connect on the given TCP port on localhost.
+    Listen(u16),
+    /// Connect to another brain that is listening at the given address.
+    Connect(String),
+}
+
+/// Pairs `radio` with the radio at `target` in the background.
+pub fn spawn(port: u8, mut radio: Radio, target: RadioTarget) {
+    tokio::task::spawn(async move {
+        match open(port, &target).await {
+            Ok(stream) => {
+                log::info!("Radio on port {port} is in range of {target:?}.");
+                radio.pair_with_stream(stream);
+            }
+            Err(err) => log::error!("Radio on port {port} couldn't reach {target:?}: {err}"),
+        }
+
+        // The radio is simulated for as long as it is alive, so it has to be kept
+        // around even if it never finds a peer.
+        std::future::pending::<()>().await;
+    });
+}
+
+async fn open(port: u8, target: &RadioTarget) -> io::Result<TcpStream> {
+    match target {
+        RadioTarget::Listen(tcp_port) => {
+            let listener = TcpListener::bind(("127.0.0.1", *tcp_port)).await?;
+            log::info!("Radio on port {port} is listening on 127.0.0.1:{tcp_port}.");
+
+            let (stream, _) = listener.accept().await?;
+            Ok(stream)
+        }
+        RadioTarget::Connect(addr) => TcpStream::connect(addr).await,
+    }
+}
@@ -1,4 +1,3 @@
-use image::DynamicImage;
 use tauri::{Emitter, Manager};
 use tauri_plugin_log::TimezoneStrategy;
 use tokio::sync::Mutex;
@@ -53,7 +52,7 @@ pub fn run() {
 
             let app_handle = app.handle().to_owned();
             tauri::async_runtime::spawn(async move {
-                let mut usb = peripherals.usb;
+                let mut usb = peripherals.usb_read;
                 let mut display = peripherals.display;
                 loop {
                     tokio::select! {
@@ -61,7 +60,7 @@ pub fn run() {
                             app_handle.emit("brain_usb_recv", data).unwrap();
                         },
                         Some(frame) = display.next_frame() => {
-                            app_handle.emit("brain_display_frame", DynamicImage::ImageRgb8(frame).to_rgba8().to_vec()).unwrap();
+                            app_handle.emit("brain_display_frame", frame.data().to_vec()).unwrap();
                         }
                         else => break,
                     }
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use vex_v5_display_simulator::{ColorTheme, DisplayRenderer, TextOptions};
 use vex_v5_qemu_protocol::{
     display::{Color, Shape},
@@ -6,7 +8,7 @@ use vex_v5_qemu_protocol::{
 
 pub fn main() {
     let mut display = DisplayRenderer::new(ColorTheme::Dark);
-    display.draw_header();
+    display.draw_header("User".to_string(), Duration::ZERO);
 
     display.context.foreground_color = Color(0x8B0000);
 
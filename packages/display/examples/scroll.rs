@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use vex_v5_display_simulator::{ColorTheme, DisplayRenderer, TextOptions};
+use vex_v5_qemu_protocol::{
+    display::{ScrollLocation, TextFont, TextSize},
+    geometry::Point2,
+};
+
+pub fn main() {
+    let mut display = DisplayRenderer::new(ColorTheme::Dark);
+    display.draw_header("User".to_string(), Duration::ZERO);
+
+    // Print more lines than fit on the screen, scrolling the previous ones up
+    // to make room for each new line like a terminal would.
+    for line in 0..16 {
+        if line >= 12 {
+            display.scroll(ScrollLocation::Line(34), 20);
+        }
+
+        display.draw_text(
+            format!("Line {line}"),
+            Point2 {
+                x: 0,
+                y: line.min(11) * 20 + 34,
+            },
+            false,
+            TextOptions {
+                size: TextSize { num: 1, denom: 3 },
+                font: TextFont::Monospace,
+            },
+        );
+    }
+
+    let pix = display.render(false).unwrap();
+    pix.save_png("result.png").unwrap();
+}
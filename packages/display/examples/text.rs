@@ -1,10 +1,12 @@
+use std::time::Duration;
+
 use vex_v5_display_simulator::{ColorTheme, DisplayRenderer, TextOptions};
 use vex_v5_qemu_protocol::geometry::Point2;
 
 pub fn main() {
     let mut display = DisplayRenderer::new(ColorTheme::Dark);
 
-    display.draw_header();
+    display.draw_header("User".to_string(), Duration::ZERO);
 
     display.draw_text(
         "Hello, world!".to_string(),
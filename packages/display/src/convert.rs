@@ -52,6 +52,9 @@ impl ToSkia for Shape {
                 builder.line_to(end.x as f32, end.y as f32);
                 builder.finish().unwrap()
             }
+            Shape::Pixel { position } => PathBuilder::from_rect(
+                Rect::from_xywh(position.x as _, position.y as _, 1.0, 1.0).unwrap(),
+            ),
         }
     }
 }
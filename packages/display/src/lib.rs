@@ -6,12 +6,12 @@ use fontdue::{
 };
 pub use tiny_skia::Pixmap;
 use tiny_skia::{
-    BlendMode, Color, FillRule, Mask, Paint, PathBuilder, PixmapPaint, PixmapRef, Rect, Shader,
-    Stroke, Transform,
+    BlendMode, Color, FillRule, LineCap, LineJoin, Mask, Paint, PathBuilder, PixmapPaint, Rect,
+    Shader, Stroke, Transform,
 };
 use vex_v5_qemu_protocol::{
-    display::{Color as ProtocolColor, Shape, TextFont, TextSize},
-    geometry::Point2,
+    display::{Color as ProtocolColor, ScrollLocation, Shape, TextFont, TextMetrics, TextSize},
+    geometry::{Point2, Rect as ProtocolRect},
 };
 
 use crate::convert::ToSkia;
@@ -76,6 +76,11 @@ pub const HEADER_HEIGHT: u32 = 32;
 
 pub const HEADER_BG: ProtocolColor = ProtocolColor(0x0099CC);
 
+/// Number of clip region masks kept around after they were last used, so that
+/// tasks switching between their own clip regions don't rebuild them on every
+/// draw.
+const CLIP_MASK_CACHE_SIZE: usize = 16;
+
 // struct TextLayout {
 //     text: String,
 //     options: TextOptions,
@@ -90,6 +95,9 @@ pub struct DrawContext {
     pub foreground_color: ProtocolColor,
     /// The display's saved background color.
     pub background_color: ProtocolColor,
+    /// The width of strokes and lines, and the size of pixels.
+    pub pen_size: u32,
+    /// The area that drawing is limited to, or [`None`] to draw anywhere.
     pub clip_region: Option<Arc<Mask>>,
 }
 
@@ -103,6 +111,8 @@ pub struct DisplayRenderer {
     pub prev_canvas: Option<Pixmap>,
     pub header_brain_image: Pixmap,
     text_scratch: Pixmap,
+    /// Recently used clip region masks, with the most recently used first.
+    clip_masks: Vec<(ProtocolRect, Arc<Mask>)>,
     user_mono: Font,
     user_proportional: Font,
     // /// Cache for text layout calculations, to avoid re-calculating the same
@@ -123,7 +133,6 @@ impl DisplayRenderer {
     pub fn new(theme: ColorTheme) -> Self {
         let mut canvas = Pixmap::new(DISPLAY_WIDTH, DISPLAY_HEIGHT).unwrap();
         let text_scratch = canvas.clone();
-        let mut mask = Mask::new(DISPLAY_WIDTH, DISPLAY_HEIGHT).unwrap();
 
         let monospace = Font::from_bytes(
             include_bytes!("../assets/NotoMono-Regular.ttf") as &[u8],
@@ -139,22 +148,12 @@ impl DisplayRenderer {
 
         canvas.fill(theme.default_bg().to_skia());
 
-        let path = PathBuilder::from_rect(
-            Rect::from_ltrb(
-                0.0,
-                HEADER_HEIGHT as f32,
-                DISPLAY_WIDTH as f32,
-                DISPLAY_HEIGHT as f32,
-            )
-            .unwrap(),
-        );
-        mask.fill_path(&path, FillRule::EvenOdd, false, Transform::identity());
-
-        Self {
+        let mut renderer = Self {
             context: DrawContext {
                 foreground_color: theme.default_fg(),
                 background_color: theme.default_bg(),
-                clip_region: Some(Arc::new(mask)),
+                pen_size: 1,
+                clip_region: None,
             },
             context_stack: vec![],
             user_mono: monospace,
@@ -163,8 +162,74 @@ impl DisplayRenderer {
             canvas,
             prev_canvas: None,
             text_scratch,
+            clip_masks: Vec::with_capacity(CLIP_MASK_CACHE_SIZE),
             // text_layout_cache: Cell::default(),
+        };
+        renderer.set_clip_region(ProtocolRect {
+            top_left: Point2 {
+                x: 0,
+                y: HEADER_HEIGHT as _,
+            },
+            bottom_right: Point2 {
+                x: DISPLAY_WIDTH as _,
+                y: DISPLAY_HEIGHT as _,
+            },
+        });
+        renderer
+    }
+
+    /// Limits drawing to the given region of the display.
+    ///
+    /// The bottom and right edges of the region are exclusive. Drawing is
+    /// never allowed in the header, even if the region overlaps it.
+    pub fn set_clip_region(&mut self, region: ProtocolRect) {
+        let mask = match self
+            .clip_masks
+            .iter()
+            .position(|(cached, _)| *cached == region)
+        {
+            Some(index) => {
+                let entry = self.clip_masks.remove(index);
+                let mask = entry.1.clone();
+                self.clip_masks.insert(0, entry);
+                mask
+            }
+            None => {
+                let mask = Arc::new(Self::clip_mask(region));
+                self.clip_masks.truncate(CLIP_MASK_CACHE_SIZE - 1);
+                self.clip_masks.insert(0, (region, mask.clone()));
+                mask
+            }
+        };
+
+        self.context.clip_region = Some(mask);
+    }
+
+    /// Builds a mask that covers the part of `region` below the header.
+    fn clip_mask(region: ProtocolRect) -> Mask {
+        let mut mask = Mask::new(DISPLAY_WIDTH, DISPLAY_HEIGHT).unwrap();
+
+        let ProtocolRect {
+            top_left: a,
+            bottom_right: b,
+        } = region;
+        let left = a.x.min(b.x).max(0);
+        let right = a.x.max(b.x).min(DISPLAY_WIDTH as _);
+        let top = a.y.min(b.y).max(HEADER_HEIGHT as _);
+        let bottom = a.y.max(b.y).min(DISPLAY_HEIGHT as _);
+
+        // An empty region leaves the mask fully transparent, so nothing can be
+        // drawn at all.
+        if let Some(rect) = Rect::from_ltrb(left as _, top as _, right as _, bottom as _) {
+            mask.fill_path(
+                &PathBuilder::from_rect(rect),
+                FillRule::EvenOdd,
+                false,
+                Transform::identity(),
+            );
         }
+
+        mask
     }
 
     pub fn save(&mut self) {
@@ -263,6 +328,10 @@ impl DisplayRenderer {
     }
 
     /// Copies a buffer of pixels to the display.
+    ///
+    /// Each pixel is a native-endian `u32` in the same `0xRRGGBB` format as
+    /// colors, and the highest byte of each one is ignored. Each row of the
+    /// copied area starts `stride` pixels after the previous one.
     pub fn draw_buffer(
         &mut self,
         buf: &[u8],
@@ -270,29 +339,40 @@ impl DisplayRenderer {
         bottom_right: Point2<i32>,
         stride: usize,
     ) {
-        let width = (bottom_right.x - top_left.x) as u32;
-        let height = (bottom_right.y - top_left.y) as u32;
+        let width = (bottom_right.x - top_left.x).max(0) as u32;
+        let height = (bottom_right.y - top_left.y).max(0) as u32;
 
-        if height == 0 || width == 0 {
+        let Some(mut pixmap) = Pixmap::new(width, height) else {
             return;
-        }
+        };
 
-        if stride as u32 != width {
-            unimplemented!("stride != width")
+        let rows = pixmap.pixels_mut().chunks_exact_mut(width as usize);
+        for (y, row) in rows.enumerate() {
+            let Some(src) = buf.get(y * stride * 4..) else {
+                break;
+            };
+
+            for (pixel, color) in row.iter_mut().zip(src.chunks_exact(4)) {
+                let color = u32::from_ne_bytes(color.try_into().unwrap());
+                *pixel = ProtocolColor(color & 0xFFFFFF)
+                    .to_skia()
+                    .premultiply()
+                    .to_color_u8();
+            }
         }
 
-        let pixmap = PixmapRef::from_bytes(buf, width, height).expect("nonzero");
-
         self.canvas.draw_pixmap(
             top_left.x,
             top_left.y,
-            pixmap,
+            pixmap.as_ref(),
             &PixmapPaint {
+                // Parts of the area not covered by the buffer are transparent,
+                // and shouldn't replace what is already on the display.
                 blend_mode: BlendMode::SourceOver,
                 ..Default::default()
             },
             Transform::identity(),
-            None,
+            self.context.clip_region.as_deref(),
         );
     }
 
@@ -324,9 +404,102 @@ impl DisplayRenderer {
         self.prev_canvas = None;
     }
 
-    /// Erases the display by filling it with the default background color.
+    /// Erases the clip region by filling it with the current background color.
     pub fn erase(&mut self) {
-        self.canvas.fill(self.context.background_color.to_skia());
+        let paint = Paint {
+            shader: Shader::SolidColor(self.context.background_color.to_skia()),
+            anti_alias: false,
+            ..Default::default()
+        };
+
+        self.canvas.fill_rect(
+            Rect::from_xywh(0.0, 0.0, DISPLAY_WIDTH as f32, DISPLAY_HEIGHT as f32).unwrap(),
+            &paint,
+            Transform::identity(),
+            self.context.clip_region.as_deref(),
+        );
+    }
+
+    /// Moves the pixels in part of the display up by `lines` pixels, or down if
+    /// `lines` is negative, filling the rows that are left behind with the
+    /// current background color.
+    ///
+    /// [`ScrollLocation::Line`] scrolls every row from the given y-coordinate
+    /// to the bottom of the display, while [`ScrollLocation::Rect`] only
+    /// scrolls the pixels inside of the given rectangle. Pixels that are
+    /// scrolled out of the area are discarded. The header is never scrolled,
+    /// and only pixels inside of the clip region are changed.
+    pub fn scroll(&mut self, location: ScrollLocation, lines: i32) {
+        let (left, top, right, bottom) = match location {
+            ScrollLocation::Line(start) => (0, start, DISPLAY_WIDTH as i32, DISPLAY_HEIGHT as i32),
+            ScrollLocation::Rect(rect) => (
+                rect.top_left.x,
+                rect.top_left.y,
+                rect.bottom_right.x,
+                rect.bottom_right.y,
+            ),
+        };
+
+        let left = left.clamp(0, DISPLAY_WIDTH as i32) as usize;
+        let right = right.clamp(0, DISPLAY_WIDTH as i32) as usize;
+        let top = top.clamp(HEADER_HEIGHT as i32, DISPLAY_HEIGHT as i32) as usize;
+        let bottom = bottom.clamp(HEADER_HEIGHT as i32, DISPLAY_HEIGHT as i32) as usize;
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        let width = DISPLAY_WIDTH as usize;
+        let height = bottom - top;
+        let distance = (lines.unsigned_abs() as usize).min(height);
+        let background = self
+            .context
+            .background_color
+            .to_skia()
+            .premultiply()
+            .to_color_u8();
+
+        // The scroll is done on a copy of the display so that it can be drawn
+        // back through the clip region.
+        let mut scrolled = self.canvas.clone();
+        let pixels = scrolled.pixels_mut();
+
+        // Rows are copied in the opposite direction to the scroll so that each
+        // source row is read before it is overwritten.
+        let copied_rows = 0..height - distance;
+        if lines > 0 {
+            for row in copied_rows {
+                let src = (top + row + distance) * width;
+                let dest = (top + row) * width;
+                pixels.copy_within(src + left..src + right, dest + left);
+            }
+        } else {
+            for row in copied_rows.rev() {
+                let src = (top + row) * width;
+                let dest = (top + row + distance) * width;
+                pixels.copy_within(src + left..src + right, dest + left);
+            }
+        }
+
+        let exposed_rows = if lines > 0 {
+            bottom - distance..bottom
+        } else {
+            top..top + distance
+        };
+        for y in exposed_rows {
+            pixels[y * width + left..y * width + right].fill(background);
+        }
+
+        self.canvas.draw_pixmap(
+            0,
+            0,
+            scrolled.as_ref(),
+            &PixmapPaint {
+                blend_mode: BlendMode::Source,
+                ..Default::default()
+            },
+            Transform::identity(),
+            self.context.clip_region.as_deref(),
+        );
     }
 
     fn fg_paint(&self) -> Paint<'static> {
@@ -339,14 +512,55 @@ impl DisplayRenderer {
 
     /// Draws or strokes a shape on the display, using the current foreground
     /// color.
+    ///
+    /// Strokes and lines are as wide as the current pen size, and pixels are
+    /// drawn as squares of that size centered on their position.
     pub fn draw(&mut self, shape: Shape, stroke: bool) {
+        // A pen size of zero draws the same as a pen size of one, and a pen
+        // wider than the display can't cover any more of it.
+        let pen_size = self
+            .context
+            .pen_size
+            .clamp(1, DISPLAY_WIDTH.max(DISPLAY_HEIGHT)) as i32;
+
+        if let Shape::Pixel { position } = shape {
+            let offset = (pen_size - 1) / 2;
+            let top_left = Point2 {
+                x: position.x.saturating_sub(offset),
+                y: position.y.saturating_sub(offset),
+            };
+
+            self.canvas.fill_path(
+                &Shape::Rectangle {
+                    top_left,
+                    bottom_right: Point2 {
+                        x: top_left.x.saturating_add(pen_size),
+                        y: top_left.y.saturating_add(pen_size),
+                    },
+                }
+                .to_skia(),
+                &self.fg_paint(),
+                FillRule::Winding,
+                Transform::identity(),
+                self.context.clip_region.as_deref(),
+            );
+            return;
+        }
+
         let path = shape.to_skia();
 
         if stroke || matches!(shape, Shape::Line { start: _, end: _ }) {
+            // Square caps make thick lines cover their end points the same way
+            // that a square pen dragged along the line would.
             self.canvas.stroke_path(
                 &path,
                 &self.fg_paint(),
-                &Stroke::default(),
+                &Stroke {
+                    width: pen_size as f32,
+                    line_cap: LineCap::Square,
+                    line_join: LineJoin::Miter,
+                    ..Default::default()
+                },
                 Transform::identity(),
                 self.context.clip_region.as_deref(),
             );
@@ -437,6 +651,55 @@ impl DisplayRenderer {
     //     })
     // }
 
+    /// Returns the font size in pixels that text of the given size is
+    /// rendered at.
+    fn font_px(size: TextSize) -> f32 {
+        size.num as f32 / size.denom as f32 * 48.0
+    }
+
+    /// Returns how much space the given text takes up when drawn with
+    /// [`DisplayRenderer::draw_text`].
+    ///
+    /// The width is the distance from the start of the text to where the next
+    /// character would be drawn, and the height is the height of a line of
+    /// text. Both are zero for empty text.
+    pub fn measure_text(&self, text: &str, options: TextOptions) -> TextMetrics {
+        if text.is_empty() {
+            return TextMetrics::default();
+        }
+
+        let px = Self::font_px(options.size);
+        let font = match options.font {
+            TextFont::Monospace => &self.user_mono,
+            TextFont::Proportional => &self.user_proportional,
+        };
+
+        let mut layout = Layout::new(CoordinateSystem::PositiveYDown);
+        layout.reset(&LayoutSettings {
+            wrap_hard_breaks: false,
+            ..LayoutSettings::default()
+        });
+        layout.append(&[font], &TextStyle::new(text, px, 0));
+
+        // The layout moves to the next character by each glyph's advance
+        // width, rounded up.
+        let width: f32 = layout
+            .glyphs()
+            .iter()
+            .filter(|glyph| !glyph.char_data.is_control())
+            .map(|glyph| {
+                font.metrics_indexed(glyph.key.glyph_index, px)
+                    .advance_width
+                    .ceil()
+            })
+            .sum();
+
+        TextMetrics {
+            width: width as u32,
+            height: layout.height().ceil() as u32,
+        }
+    }
+
     /// Writes text to the display at a given coordinate. Use
     /// [`TextLine::coords`] to convert a line number to a coordinate for
     /// use with this method.
@@ -459,8 +722,7 @@ impl DisplayRenderer {
             return;
         }
 
-        let px = options.size.num as f32 / options.size.denom as f32 * 48.0;
-
+        let px = Self::font_px(options.size);
         let font = match options.font {
             TextFont::Monospace => &self.user_mono,
             TextFont::Proportional => &self.user_proportional,
@@ -0,0 +1,162 @@
+//! Compares drawing inside of clip regions against reference images in
+//! `tests/golden`.
+
+mod common;
+
+use common::{assert_golden, draw_bands, renderer};
+use vex_v5_display_simulator::{TextOptions, DISPLAY_HEIGHT, DISPLAY_WIDTH};
+use vex_v5_qemu_protocol::{
+    display::{Color, ScrollLocation, Shape, TextFont, TextSize},
+    geometry::{Point2, Rect},
+};
+
+const FULL_SCREEN: Rect = Rect {
+    top_left: Point2 { x: 0, y: 0 },
+    bottom_right: Point2 {
+        x: DISPLAY_WIDTH as i32,
+        y: DISPLAY_HEIGHT as i32,
+    },
+};
+
+const CENTER: Rect = Rect {
+    top_left: Point2 { x: 100, y: 80 },
+    bottom_right: Point2 { x: 300, y: 200 },
+};
+
+#[test]
+fn clip_shapes() {
+    let mut display = renderer();
+
+    display.set_clip_region(CENTER);
+    draw_bands(&mut display);
+
+    display.context.foreground_color = Color(0xFFFFFF);
+    display.draw(
+        Shape::Circle {
+            center: Point2 { x: 300, y: 200 },
+            radius: 60,
+        },
+        false,
+    );
+
+    assert_golden(&mut display, "clip_shapes");
+}
+
+#[test]
+fn clip_never_includes_header() {
+    let mut display = renderer();
+
+    display.set_clip_region(FULL_SCREEN);
+    draw_bands(&mut display);
+    display.context.foreground_color = Color(0xFF00FF);
+    display.draw(
+        Shape::Rectangle {
+            top_left: Point2 { x: 0, y: 0 },
+            bottom_right: Point2 { x: 240, y: 136 },
+        },
+        false,
+    );
+
+    assert_golden(&mut display, "clip_never_includes_header");
+}
+
+#[test]
+fn clip_erase() {
+    let mut display = renderer();
+    draw_bands(&mut display);
+
+    display.set_clip_region(CENTER);
+    display.context.background_color = Color(0xFFFFFF);
+    display.erase();
+
+    assert_golden(&mut display, "clip_erase");
+}
+
+#[test]
+fn clip_scroll() {
+    let mut display = renderer();
+    draw_bands(&mut display);
+
+    // Only the part of the scrolled area inside of the clip region moves.
+    display.set_clip_region(Rect {
+        top_left: Point2 { x: 0, y: 100 },
+        bottom_right: Point2 { x: 240, y: 272 },
+    });
+    display.context.background_color = Color(0xFFFFFF);
+    display.scroll(ScrollLocation::Line(50), 30);
+
+    assert_golden(&mut display, "clip_scroll");
+}
+
+#[test]
+fn clip_text_and_buffers() {
+    let mut display = renderer();
+
+    display.set_clip_region(Rect {
+        top_left: Point2 { x: 20, y: 40 },
+        bottom_right: Point2 { x: 120, y: 90 },
+    });
+    display.context.foreground_color = Color(0xFFFFFF);
+    display.draw_text(
+        "Clipped text".to_string(),
+        Point2 { x: 10, y: 40 },
+        true,
+        TextOptions {
+            size: TextSize { num: 2, denom: 3 },
+            font: TextFont::Monospace,
+        },
+    );
+
+    let buffer = [0xFF00FF00u32; 100 * 100];
+    display.draw_buffer(
+        bytemuck::cast_slice(&buffer),
+        Point2 { x: 60, y: 60 },
+        Point2 { x: 160, y: 160 },
+        100,
+    );
+
+    assert_golden(&mut display, "clip_text_and_buffers");
+}
+
+#[test]
+fn switching_clip_regions() {
+    let mut display = renderer();
+
+    // Alternate between regions the way two tasks with their own clip regions
+    // would.
+    let regions = [
+        CENTER,
+        Rect {
+            top_left: Point2 { x: 320, y: 40 },
+            bottom_right: Point2 { x: 460, y: 250 },
+        },
+    ];
+    for (i, y) in (40..260).step_by(20).enumerate() {
+        display.set_clip_region(regions[i % regions.len()]);
+        display.context.foreground_color = Color(0x00FFFF);
+        display.draw(
+            Shape::Rectangle {
+                top_left: Point2 { x: 0, y },
+                bottom_right: Point2 { x: 480, y: y + 10 },
+            },
+            false,
+        );
+    }
+
+    assert_golden(&mut display, "switching_clip_regions");
+}
+
+#[test]
+fn empty_clip_region() {
+    let mut display = renderer();
+    let expected = display.render(false).unwrap();
+
+    display.set_clip_region(Rect {
+        top_left: Point2 { x: 100, y: 100 },
+        bottom_right: Point2 { x: 100, y: 200 },
+    });
+    draw_bands(&mut display);
+    display.erase();
+
+    assert!(display.render(false).unwrap().data() == expected.data());
+}
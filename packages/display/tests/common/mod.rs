@@ -0,0 +1,65 @@
+//! Helpers shared by the golden image tests.
+//!
+//! Run the tests with `UPDATE_GOLDEN=1` to overwrite the reference images in
+//! `tests/golden` with the current output after an intentional change to the
+//! renderer.
+
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use std::{path::PathBuf, time::Duration};
+
+use vex_v5_display_simulator::{ColorTheme, DisplayRenderer, Pixmap};
+use vex_v5_qemu_protocol::{
+    display::{Color, Shape},
+    geometry::Point2,
+};
+
+pub fn renderer() -> DisplayRenderer {
+    let mut display = DisplayRenderer::new(ColorTheme::Dark);
+    display.draw_header("User".to_string(), Duration::ZERO);
+    display
+}
+
+/// Fills the user area of the display with horizontal bands of color so that
+/// the direction and distance of a scroll is easy to see.
+pub fn draw_bands(display: &mut DisplayRenderer) {
+    const COLORS: [Color; 4] = [
+        Color(0xFF0000),
+        Color(0x00FF00),
+        Color(0x0000FF),
+        Color(0xFFFF00),
+    ];
+
+    for (i, y) in (32..272).step_by(24).enumerate() {
+        display.context.foreground_color = COLORS[i % COLORS.len()];
+        display.draw(
+            Shape::Rectangle {
+                top_left: Point2 { x: 0, y },
+                bottom_right: Point2 { x: 480, y: y + 24 },
+            },
+            false,
+        );
+    }
+}
+
+#[track_caller]
+pub fn assert_golden(display: &mut DisplayRenderer, name: &str) {
+    let actual = display.render(false).unwrap();
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.png"));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        actual.save_png(&path).unwrap();
+        return;
+    }
+
+    let expected = Pixmap::load_png(&path)
+        .unwrap_or_else(|err| panic!("couldn't load {}: {err}", path.display()));
+    assert!(
+        actual.data() == expected.data(),
+        "display output doesn't match {}",
+        path.display()
+    );
+}
@@ -0,0 +1,152 @@
+//! Compares shapes drawn with different pen sizes against reference images in
+//! `tests/golden`.
+
+mod common;
+
+use common::{assert_golden, renderer};
+use vex_v5_qemu_protocol::{display::Shape, geometry::Point2};
+
+const PEN_SIZES: [u32; 4] = [1, 2, 5, 10];
+
+#[test]
+fn pen_size_lines() {
+    let mut display = renderer();
+
+    for (i, pen_size) in PEN_SIZES.into_iter().enumerate() {
+        let x = 40 + i as i32 * 110;
+        display.context.pen_size = pen_size;
+
+        display.draw(
+            Shape::Line {
+                start: Point2 { x, y: 50 },
+                end: Point2 { x: x + 80, y: 50 },
+            },
+            false,
+        );
+        display.draw(
+            Shape::Line {
+                start: Point2 { x, y: 80 },
+                end: Point2 { x, y: 240 },
+            },
+            false,
+        );
+        display.draw(
+            Shape::Line {
+                start: Point2 { x: x + 20, y: 80 },
+                end: Point2 { x: x + 80, y: 240 },
+            },
+            false,
+        );
+    }
+
+    assert_golden(&mut display, "pen_size_lines");
+}
+
+#[test]
+fn pen_size_outlines() {
+    let mut display = renderer();
+
+    for (i, pen_size) in PEN_SIZES.into_iter().enumerate() {
+        let x = 60 + i as i32 * 110;
+        display.context.pen_size = pen_size;
+
+        display.draw(
+            Shape::Rectangle {
+                top_left: Point2 { x: x - 40, y: 50 },
+                bottom_right: Point2 { x: x + 40, y: 130 },
+            },
+            true,
+        );
+        display.draw(
+            Shape::Circle {
+                center: Point2 { x, y: 200 },
+                radius: 40,
+            },
+            true,
+        );
+    }
+
+    assert_golden(&mut display, "pen_size_outlines");
+}
+
+#[test]
+fn pen_size_pixels() {
+    let mut display = renderer();
+
+    for (i, pen_size) in PEN_SIZES.into_iter().enumerate() {
+        display.context.pen_size = pen_size;
+
+        for j in 0..5 {
+            display.draw(
+                Shape::Pixel {
+                    position: Point2 {
+                        x: 60 + i as i32 * 110,
+                        y: 60 + j * 40,
+                    },
+                },
+                false,
+            );
+        }
+    }
+
+    assert_golden(&mut display, "pen_size_pixels");
+}
+
+#[test]
+fn zero_pen_size() {
+    let mut display = renderer();
+    display.draw(
+        Shape::Circle {
+            center: Point2 { x: 240, y: 150 },
+            radius: 80,
+        },
+        true,
+    );
+    let expected = display.render(false).unwrap();
+
+    let mut display = renderer();
+    display.context.pen_size = 0;
+    display.draw(
+        Shape::Circle {
+            center: Point2 { x: 240, y: 150 },
+            radius: 80,
+        },
+        true,
+    );
+
+    assert!(display.render(false).unwrap().data() == expected.data());
+}
+
+#[test]
+fn huge_pen_size() {
+    let mut display = renderer();
+    display.context.pen_size = u32::MAX;
+
+    for position in [
+        Point2 { x: 240, y: 150 },
+        Point2 {
+            x: i32::MAX,
+            y: i32::MIN,
+        },
+    ] {
+        display.draw(Shape::Pixel { position }, false);
+    }
+    display.draw(
+        Shape::Line {
+            start: Point2 { x: 0, y: 150 },
+            end: Point2 { x: 480, y: 150 },
+        },
+        false,
+    );
+
+    let mut expected = renderer();
+    expected.draw(
+        Shape::Rectangle {
+            top_left: Point2 { x: 0, y: 0 },
+            bottom_right: Point2 { x: 480, y: 272 },
+        },
+        false,
+    );
+
+    assert!(display.render(false).unwrap().data() == expected.render(false).unwrap().data());
+}
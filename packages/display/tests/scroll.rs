@@ -0,0 +1,113 @@
+//! Compares the results of scrolling the display against reference images in
+//! `tests/golden`.
+
+mod common;
+
+use common::{assert_golden, draw_bands, renderer};
+use vex_v5_display_simulator::TextOptions;
+use vex_v5_qemu_protocol::{
+    display::{Color, ScrollLocation, TextFont, TextSize},
+    geometry::{Point2, Rect},
+};
+
+#[test]
+fn scroll_line_up() {
+    let mut display = renderer();
+    draw_bands(&mut display);
+
+    display.context.background_color = Color(0x202020);
+    display.scroll(ScrollLocation::Line(100), 30);
+
+    assert_golden(&mut display, "scroll_line_up");
+}
+
+#[test]
+fn scroll_line_down() {
+    let mut display = renderer();
+    draw_bands(&mut display);
+
+    display.context.background_color = Color(0x202020);
+    display.scroll(ScrollLocation::Line(100), -30);
+
+    assert_golden(&mut display, "scroll_line_down");
+}
+
+#[test]
+fn scroll_line_past_header() {
+    let mut display = renderer();
+    draw_bands(&mut display);
+
+    // The header must be left alone even if the scrolled area starts above it.
+    display.context.background_color = Color(0x202020);
+    display.scroll(ScrollLocation::Line(0), 12);
+
+    assert_golden(&mut display, "scroll_line_past_header");
+}
+
+#[test]
+fn scroll_rect() {
+    let mut display = renderer();
+    draw_bands(&mut display);
+
+    display.context.background_color = Color(0xFFFFFF);
+    display.scroll(
+        ScrollLocation::Rect(Rect {
+            top_left: Point2 { x: 100, y: 80 },
+            bottom_right: Point2 { x: 300, y: 200 },
+        }),
+        10,
+    );
+    display.scroll(
+        ScrollLocation::Rect(Rect {
+            top_left: Point2 { x: 320, y: 80 },
+            bottom_right: Point2 { x: 460, y: 200 },
+        }),
+        -40,
+    );
+
+    assert_golden(&mut display, "scroll_rect");
+}
+
+#[test]
+fn scroll_rect_entirely() {
+    let mut display = renderer();
+    draw_bands(&mut display);
+
+    // Scrolling by more than the height of the area clears all of it.
+    display.context.background_color = Color(0xFFFFFF);
+    display.scroll(
+        ScrollLocation::Rect(Rect {
+            top_left: Point2 { x: 100, y: 80 },
+            bottom_right: Point2 { x: 300, y: 200 },
+        }),
+        500,
+    );
+
+    assert_golden(&mut display, "scroll_rect_entirely");
+}
+
+#[test]
+fn scrolling_terminal() {
+    let mut display = renderer();
+
+    for line in 0..16 {
+        if line >= 12 {
+            display.scroll(ScrollLocation::Line(34), 20);
+        }
+
+        display.draw_text(
+            format!("Line {line}"),
+            Point2 {
+                x: 0,
+                y: line.min(11) * 20 + 34,
+            },
+            false,
+            TextOptions {
+                size: TextSize { num: 1, denom: 3 },
+                font: TextFont::Monospace,
+            },
+        );
+    }
+
+    assert_golden(&mut display, "scrolling_terminal");
+}
@@ -0,0 +1,123 @@
+//! Checks that measured text matches what is drawn on the display.
+
+mod common;
+
+use common::renderer;
+use vex_v5_display_simulator::{TextOptions, DISPLAY_WIDTH};
+use vex_v5_qemu_protocol::{
+    display::{Color, TextFont, TextMetrics, TextSize},
+    geometry::Point2,
+};
+
+const SIZES: [TextSize; 3] = [
+    TextSize { num: 1, denom: 4 },
+    TextSize { num: 1, denom: 3 },
+    TextSize { num: 2, denom: 3 },
+];
+
+const FONTS: [TextFont; 2] = [TextFont::Monospace, TextFont::Proportional];
+
+/// Returns the last column that drawing the text changed, if any.
+fn last_drawn_column(text: &str, options: TextOptions) -> Option<u32> {
+    let mut display = renderer();
+    display.context.background_color = Color(0);
+    display.erase();
+    display.context.foreground_color = Color(0xFFFFFF);
+    display.draw_text(text.to_string(), Point2 { x: 0, y: 100 }, true, options);
+
+    let canvas = display.render(false).unwrap();
+    (0..DISPLAY_WIDTH).rev().find(|&x| {
+        (40..canvas.height()).any(|y| {
+            let pixel = canvas.pixel(x, y).unwrap();
+            pixel.red() != 0 || pixel.green() != 0 || pixel.blue() != 0
+        })
+    })
+}
+
+#[test]
+fn empty_text_has_no_size() {
+    let display = renderer();
+
+    for font in FONTS {
+        for size in SIZES {
+            assert_eq!(
+                display.measure_text("", TextOptions { size, font }),
+                TextMetrics::default()
+            );
+        }
+    }
+}
+
+#[test]
+fn width_covers_drawn_text() {
+    let display = renderer();
+
+    for font in FONTS {
+        for size in SIZES {
+            let options = TextOptions { size, font };
+            let text = "Hello, World! 123";
+            let metrics = display.measure_text(text, options);
+            let last = last_drawn_column(text, options).unwrap();
+
+            // The last glyph ends somewhere inside of its advance.
+            let last_advance = display.measure_text("3", options).width;
+            assert!(
+                last < metrics.width && last + last_advance >= metrics.width,
+                "{options:?}: drawn up to column {last}, but measured {metrics:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn width_is_sum_of_parts() {
+    let display = renderer();
+
+    for font in FONTS {
+        for size in SIZES {
+            let options = TextOptions { size, font };
+            let whole = display.measure_text("left right", options);
+            let left = display.measure_text("left ", options);
+            let right = display.measure_text("right", options);
+
+            assert_eq!(whole.width, left.width + right.width);
+            assert_eq!(whole.height, left.height);
+        }
+    }
+}
+
+#[test]
+fn monospace_characters_have_same_width() {
+    let display = renderer();
+    let options = TextOptions {
+        size: TextSize { num: 1, denom: 3 },
+        font: TextFont::Monospace,
+    };
+
+    assert_eq!(
+        display.measure_text("iiii", options),
+        display.measure_text("MMMM", options)
+    );
+
+    let options = TextOptions {
+        font: TextFont::Proportional,
+        ..options
+    };
+    assert!(
+        display.measure_text("iiii", options).width < display.measure_text("MMMM", options).width
+    );
+}
+
+#[test]
+fn larger_text_is_larger() {
+    let display = renderer();
+
+    for font in FONTS {
+        let metrics = SIZES.map(|size| display.measure_text("Text", TextOptions { size, font }));
+
+        for pair in metrics.windows(2) {
+            assert!(pair[0].width < pair[1].width);
+            assert!(pair[0].height < pair[1].height);
+        }
+    }
+}
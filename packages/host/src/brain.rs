@@ -1,51 +1,122 @@
 use std::{
+    collections::BTreeMap,
+    ffi::OsString,
     io,
     option::Option,
     path::PathBuf,
     process::{ExitStatus, Stdio},
-    sync::Arc,
+    sync::{Arc, Mutex as StdMutex},
     time::Duration,
     vec::Vec,
 };
 
 use tokio::{
     io::{AsyncReadExt, AsyncWriteExt},
-    process::{Child, Command},
+    net::TcpListener,
+    process::{Child, ChildStdin, ChildStdout, Command},
     sync::{
-        mpsc::{self}, Mutex,
+        mpsc::{self, error::SendError, Receiver, Sender},
+        watch, Mutex,
     },
     task::AbortHandle,
     time::sleep,
 };
-use vex_v5_qemu_protocol::{DisplayCommand, HostBoundPacket, KernelBoundPacket, SmartPortCommand};
+use vex_v5_qemu_protocol::{
+    controller::ControllerId,
+    display::TextMetrics,
+    sd_card::{FileError, FileRequest},
+    DisplayCommand, HostBoundPacket, KernelBoundPacket, SmartPortCommand,
+};
 
-use crate::peripherals::{
-    Peripherals, battery::Battery, display::Display, smartport::SmartPort, touch::Touchscreen, usb::{UsbRead, UsbWrite}
+use crate::{
+    peripherals::{
+        battery::Battery,
+        clock::Clock,
+        competition::Competition,
+        controller::Controller,
+        display::Display,
+        sd_card::SdCard,
+        smartport::SmartPort,
+        touch::Touchscreen,
+        usb::{UsbRead, UsbWrite},
+        Peripherals,
+    },
+    qmp::Qmp,
 };
 
+/// Number of device commands that can be queued up for a smartport before
+/// further commands are dropped.
+const SMARTPORT_BUFFER: usize = 64;
+
 #[derive(Debug, Clone)]
 pub struct Binary {
     pub path: PathBuf,
     pub load_addr: u32,
 }
 
-pub struct Brain {
-    pub peripherals: Option<Peripherals>,
-    tx_task: AbortHandle,
-    rx_task: AbortHandle,
-    qemu: Arc<Mutex<Child>>,
+/// What the brain is currently doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BrainStatus {
+    /// No program is running.
+    Stopped,
+    /// QEMU has started, but the kernel hasn't started the user program yet.
+    Booting,
+    /// The user program is running.
+    Running,
+    /// The virtual machine has been paused with [`Brain::pause`].
+    Paused,
+    /// The program exited with an exit code.
+    Exited(i32),
+    /// QEMU stopped without the kernel asking it to exit.
+    Crashed,
 }
 
-impl Brain {
-    #[allow(clippy::new_without_default)]
-    pub fn new(
-        mut qemu_command: Command,
-        kernel: PathBuf,
-        main_binary: Binary,
-        linked_binary: Option<Binary>,
-    ) -> io::Result<Self> {
-        let link_addr: u32 = linked_binary.clone().map_or(0, |v| v.load_addr);
-        let qemu_command = qemu_command
+impl BrainStatus {
+    /// Returns whether QEMU is running a program, even if it is paused.
+    pub const fn is_alive(&self) -> bool {
+        matches!(self, Self::Booting | Self::Running | Self::Paused)
+    }
+}
+
+/// Waits until the brain starts booting a new program.
+///
+/// Never returns if the [`Brain`] has been dropped.
+pub(crate) async fn wait_for_boot(status: &mut watch::Receiver<BrainStatus>) {
+    loop {
+        if status.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+        if *status.borrow_and_update() == BrainStatus::Booting {
+            return;
+        }
+    }
+}
+
+/// How QEMU is started for a program, kept so that the program can be
+/// restarted.
+#[derive(Debug, Clone)]
+struct ProgramConfig {
+    qemu: OsString,
+    qemu_args: Vec<OsString>,
+    qemu_envs: Vec<(OsString, Option<OsString>)>,
+    kernel: PathBuf,
+    main_binary: Binary,
+    linked_binary: Option<Binary>,
+}
+
+impl ProgramConfig {
+    fn command(&self, qmp_port: u16) -> Command {
+        let mut qemu_command = Command::new(&self.qemu);
+        qemu_command.args(&self.qemu_args);
+        for (key, value) in &self.qemu_envs {
+            match value {
+                Some(value) => qemu_command.env(key, value),
+                None => qemu_command.env_remove(key),
+            };
+        }
+
+        let link_addr: u32 = self.linked_binary.as_ref().map_or(0, |v| v.load_addr);
+        qemu_command
             .args(["-machine", "xilinx-zynq-a9,memory-backend=mem"])
             .args(["-cpu", "cortex-a9"])
             .args(["-object", "memory-backend-ram,id=mem,size=256M"])
@@ -55,14 +126,17 @@ impl Brain {
             ])
             .args([
                 "-device",
-                &format!("loader,file={},addr=0x100000,cpu-num=0", kernel.display()),
+                &format!(
+                    "loader,file={},addr=0x100000,cpu-num=0",
+                    self.kernel.display()
+                ),
             ])
             .args([
                 "-device",
                 &format!(
                     "loader,file={},force-raw=on,addr={}",
-                    main_binary.path.display(),
-                    main_binary.load_addr
+                    self.main_binary.path.display(),
+                    self.main_binary.load_addr
                 ),
             ])
             .args(["-display", "none"])
@@ -74,12 +148,13 @@ impl Brain {
             .args(["-chardev", "stdio,id=uart"])
             .args(["-serial", "null"])
             .args(["-serial", "chardev:uart"])
+            .args(["-qmp", &format!("tcp:127.0.0.1:{qmp_port}")])
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::inherit())
             .kill_on_drop(true);
 
-        if let Some(linked_binary) = linked_binary {
+        if let Some(linked_binary) = &self.linked_binary {
             qemu_command.arg("-device");
             qemu_command.arg(format!(
                 "loader,file={},force-raw=on,addr={}",
@@ -88,125 +163,236 @@ impl Brain {
             ));
         }
 
-        let (peripherals_tx, mut peripherals_rx) = mpsc::channel::<KernelBoundPacket>(1024);
+        qemu_command
+    }
+}
+
+/// The latest packets sent by peripherals that only send updates when their
+/// state changes.
+///
+/// These are sent again whenever a new program starts, so that its kernel sees
+/// the same state as the last one did.
+#[derive(Debug, Default)]
+struct PeripheralState {
+    battery: Option<KernelBoundPacket>,
+    controllers: BTreeMap<ControllerId, KernelBoundPacket>,
+    competition: Option<KernelBoundPacket>,
+    touch: Option<KernelBoundPacket>,
+}
 
-        // Each of these channels represents a serial line for device commands from the
-        // kernel to a smartport. Commands sent to devices by the kernel are
-        // forwarded by the brain's packet event loop task as described later.
-        let (port_1_tx, port_1_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_2_tx, port_2_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_3_tx, port_3_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_4_tx, port_4_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_5_tx, port_5_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_6_tx, port_6_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_7_tx, port_7_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_8_tx, port_8_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_9_tx, port_9_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_10_tx, port_10_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_11_tx, port_11_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_12_tx, port_12_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_13_tx, port_13_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_14_tx, port_14_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_15_tx, port_15_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_16_tx, port_16_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_17_tx, port_17_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_18_tx, port_18_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_19_tx, port_19_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_20_tx, port_20_rx) = mpsc::channel::<SmartPortCommand>(1);
-        let (port_21_tx, port_21_rx) = mpsc::channel::<SmartPortCommand>(1);
+impl PeripheralState {
+    fn observe(&mut self, packet: &KernelBoundPacket) {
+        let slot = match packet {
+            KernelBoundPacket::BatteryUpdate { .. } => &mut self.battery,
+            KernelBoundPacket::CompetitionUpdate(_) => &mut self.competition,
+            KernelBoundPacket::Touch(_) => &mut self.touch,
+            KernelBoundPacket::ControllerUpdate { id, .. } => {
+                self.controllers.insert(*id, packet.clone());
+                return;
+            }
+            _ => return,
+        };
 
-        let (usb_tx, usb_rx) = mpsc::channel::<Vec<u8>>(1);
-        let (display_tx, display_rx) = mpsc::channel::<DisplayCommand>(1);
+        *slot = Some(packet.clone());
+    }
 
-        let mut qemu = qemu_command.spawn()?;
-        let mut qemu_stdin = qemu.stdin.take().unwrap();
-        let mut qemu_stdout = qemu.stdout.take().unwrap();
+    fn packets(&self) -> impl Iterator<Item = &KernelBoundPacket> {
+        self.battery
+            .iter()
+            .chain(self.controllers.values())
+            .chain(&self.competition)
+            .chain(&self.touch)
+    }
+}
 
-        let qemu = Arc::new(Mutex::new(qemu));
+/// Packets sent by the peripherals that are waiting to be forwarded to the
+/// kernel.
+///
+/// Something must always be receiving from the link, even when no program is
+/// running, or the peripherals would block once it fills up.
+#[derive(Debug)]
+struct KernelLink {
+    rx: Receiver<KernelBoundPacket>,
+    state: PeripheralState,
+}
+
+/// Where packets from the kernel are forwarded to.
+#[derive(Debug, Clone)]
+struct Routes {
+    smartports: [Sender<SmartPortCommand>; 22],
+    usb: Sender<Vec<u8>>,
+    display: Sender<DisplayCommand>,
+    sd_card: Sender<FileRequest>,
+    /// Used to answer requests that the kernel is waiting on when the
+    /// peripheral that would normally answer them has been dropped.
+    kernel: Sender<KernelBoundPacket>,
+}
 
-        let tx_task = tokio::task::spawn(async move {
-            loop {
-                if let Some(packet) = peripherals_rx.recv().await {
-                    let encoded =
-                        bincode::encode_to_vec(packet, bincode::config::standard()).unwrap();
-                    let mut bytes = Vec::new();
+/// The QMP connection to a program's QEMU process, which is only established
+/// once it is first needed.
+#[derive(Debug)]
+enum QmpConnection {
+    Listening(TcpListener),
+    Connected(Qmp),
+}
 
-                    bytes.extend((encoded.len() as u32).to_le_bytes());
-                    bytes.extend(encoded);
+/// Publishes the status of a program, holding back changes while the virtual
+/// machine is paused.
+#[derive(Debug, Clone)]
+struct ProgramStatus {
+    status: watch::Sender<BrainStatus>,
+    /// The program's latest status while it is paused, which is published once
+    /// it resumes.
+    paused: Arc<StdMutex<Option<BrainStatus>>>,
+}
 
-                    let Ok(_) = qemu_stdin.write_all(&bytes).await else {
-                        break; // QEMU process has exited
-                    };
-                }
+impl ProgramStatus {
+    fn new(status: watch::Sender<BrainStatus>) -> Self {
+        Self {
+            status,
+            paused: Arc::default(),
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused.lock().unwrap().is_some()
+    }
+
+    /// Publishes a change to the program's status.
+    ///
+    /// While paused, the change is held back until the program resumes, unless
+    /// the program has stopped.
+    fn set(&self, status: BrainStatus) {
+        let mut paused = self.paused.lock().unwrap();
+        match &mut *paused {
+            Some(paused_status) if status.is_alive() => *paused_status = status,
+            _ => {
+                *paused = None;
+                self.status.send_replace(status);
             }
-        })
-        .abort_handle();
+        }
+    }
 
-        Ok(Self {
-            qemu: qemu.clone(),
-            tx_task: tx_task.clone(),
-            rx_task: tokio::spawn(async move {
-                let smartport_senders = [
-                    port_1_tx, port_2_tx, port_3_tx, port_4_tx, port_5_tx, port_6_tx, port_7_tx,
-                    port_8_tx, port_9_tx, port_10_tx, port_11_tx, port_12_tx, port_13_tx,
-                    port_14_tx, port_15_tx, port_16_tx, port_17_tx, port_18_tx, port_19_tx,
-                    port_20_tx, port_21_tx,
-                ];
+    /// Publishes [`BrainStatus::Paused`] if the program is still alive.
+    fn pause(&self) {
+        let mut paused = self.paused.lock().unwrap();
+        if paused.is_none() && self.status.borrow().is_alive() {
+            *paused = Some(self.status.send_replace(BrainStatus::Paused));
+        }
+    }
 
-                loop {
-                    let incoming_packet: HostBoundPacket = {
-                        let packet_size = qemu_stdout.read_u32_le().await.unwrap() as usize;
-                        let mut buf = vec![0u8; packet_size];
+    /// Publishes the program's latest status if it was paused.
+    fn resume(&self) {
+        if let Some(status) = self.paused.lock().unwrap().take() {
+            self.status.send_replace(status);
+        }
+    }
+}
 
-                        qemu_stdout.read_exact(&mut buf).await.unwrap();
+/// A program running in QEMU.
+#[derive(Debug)]
+struct Program {
+    qemu: Arc<Mutex<Child>>,
+    qmp: QmpConnection,
+    rx_task: AbortHandle,
+    status: ProgramStatus,
+}
 
-                        bincode::decode_from_slice(&buf, bincode::config::standard())
-                            .unwrap()
-                            .0
-                    };
+impl Program {
+    async fn execute(&mut self, command: &str) -> io::Result<()> {
+        if let QmpConnection::Listening(listener) = &self.qmp {
+            self.qmp = QmpConnection::Connected(Qmp::accept(listener).await?);
+        }
+        let QmpConnection::Connected(qmp) = &mut self.qmp else {
+            unreachable!();
+        };
 
-                    match incoming_packet {
-                        // Forward sent data to usb peripheral.
-                        HostBoundPacket::UsbSerial(data) => {
-                            _ = usb_tx.send(data).await;
-                        }
+        qmp.execute(command).await?;
+        Ok(())
+    }
+}
 
-                        // Kernel debugging stuff (logs mainly) goes to stderr
-                        HostBoundPacket::KernelSerial(data) => {
-                            let mut stderr = tokio::io::stderr();
-                            stderr.write_all(&data).await.unwrap();
-                            stderr.flush().await.unwrap();
-                        }
+impl Drop for Program {
+    fn drop(&mut self) {
+        self.rx_task.abort();
+    }
+}
 
-                        // Doesn't matter for now.
-                        HostBoundPacket::CodeSignature(_) => {}
+/// A simulated V5 brain.
+///
+/// The brain's peripherals are created once and outlive any programs it runs,
+/// so a program can be stopped and a new one started with
+/// [`Brain::run_program`] without having to set up the peripherals again.
+#[derive(Debug)]
+pub struct Brain {
+    pub peripherals: Option<Peripherals>,
+    link: Arc<Mutex<KernelLink>>,
+    /// The task receiving packets from the link, which forwards them to the
+    /// kernel while a program is running.
+    link_task: AbortHandle,
+    routes: Routes,
+    status: watch::Sender<BrainStatus>,
+    config: Option<ProgramConfig>,
+    program: Option<Program>,
+}
 
-                        // Kill QEMU child process when kernel requests exit.
-                        HostBoundPacket::ExitRequest(code) => {
-                            qemu.lock().await.kill().await.unwrap();
-                            log::info!("Kernel exited with code {code}.");
-                            break;
-                        }
+impl Brain {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let (peripherals_tx, peripherals_rx) = mpsc::channel::<KernelBoundPacket>(1024);
+        let (status, _) = watch::channel(BrainStatus::Stopped);
 
-                        // The kernel has sent a device command packet to a specific smartport,
-                        // so we must forward that packet to the respective smartport's
-                        // receiver.
-                        HostBoundPacket::SmartPortCommand { port, command } => {
-                            if let Some(port_tx) = smartport_senders.get(port as usize) {
-                                // We ignore errors if the packet send fails, since it means
-                                // the user has dropped the smartport and by extension the
-                                // receiver, meaning there's nothing to send to.
-                                _ = port_tx.send(command).await;
-                            }
-                        }
+        // Each of these channels represents a serial line for device commands from the
+        // kernel to a smartport (or to the onboard ADI ports, which the kernel treats
+        // as a 22nd smartport). Commands sent to devices by the kernel are
+        // forwarded by the brain's packet event loop task as described later.
+        let (port_1_tx, port_1_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_2_tx, port_2_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_3_tx, port_3_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_4_tx, port_4_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_5_tx, port_5_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_6_tx, port_6_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_7_tx, port_7_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_8_tx, port_8_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_9_tx, port_9_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_10_tx, port_10_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_11_tx, port_11_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_12_tx, port_12_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_13_tx, port_13_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_14_tx, port_14_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_15_tx, port_15_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_16_tx, port_16_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_17_tx, port_17_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_18_tx, port_18_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_19_tx, port_19_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_20_tx, port_20_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (port_21_tx, port_21_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
+        let (adi_tx, adi_rx) = mpsc::channel::<SmartPortCommand>(SMARTPORT_BUFFER);
 
-                        HostBoundPacket::DisplayCommand { command } => {
-                            _ = display_tx.send(command).await;
-                        }
-                    }
-                }
-            })
-            .abort_handle(),
+        let (usb_tx, usb_rx) = mpsc::channel::<Vec<u8>>(1);
+        let (display_tx, display_rx) = mpsc::channel::<DisplayCommand>(1);
+        let (sd_card_tx, sd_card_rx) = mpsc::channel::<FileRequest>(1);
+
+        let link = Arc::new(Mutex::new(KernelLink {
+            rx: peripherals_rx,
+            state: PeripheralState::default(),
+        }));
+
+        Self {
+            link_task: spawn_link_task(link.clone(), None),
+            link,
+            routes: Routes {
+                smartports: [
+                    port_1_tx, port_2_tx, port_3_tx, port_4_tx, port_5_tx, port_6_tx, port_7_tx,
+                    port_8_tx, port_9_tx, port_10_tx, port_11_tx, port_12_tx, port_13_tx,
+                    port_14_tx, port_15_tx, port_16_tx, port_17_tx, port_18_tx, port_19_tx,
+                    port_20_tx, port_21_tx, adi_tx,
+                ],
+                usb: usb_tx,
+                display: display_tx,
+                sd_card: sd_card_tx,
+                kernel: peripherals_tx.clone(),
+            },
             peripherals: Some(Peripherals {
                 battery: Battery::new(peripherals_tx.clone()),
                 usb_read: UsbRead::new(usb_rx),
@@ -233,35 +419,455 @@ impl Brain {
                 port_19: SmartPort::new(18, peripherals_tx.clone(), port_19_rx),
                 port_20: SmartPort::new(19, peripherals_tx.clone(), port_20_rx),
                 port_21: SmartPort::new(20, peripherals_tx.clone(), port_21_rx),
+                onboard_adi: SmartPort::new(21, peripherals_tx.clone(), adi_rx),
 
-                display: Display::new(peripherals_tx.clone(), display_rx),
+                display: Display::new(peripherals_tx.clone(), display_rx, status.subscribe()),
                 touch: Touchscreen::new(peripherals_tx.clone()),
+
+                master_controller: Controller::new(ControllerId::Master, peripherals_tx.clone()),
+                partner_controller: Controller::new(ControllerId::Partner, peripherals_tx.clone()),
+                competition: Competition::new(peripherals_tx.clone()),
+                sd_card: SdCard::new(peripherals_tx.clone(), sd_card_rx),
+                clock: Clock::new(peripherals_tx.clone(), status.subscribe()),
             }),
-        })
+            status,
+            config: None,
+            program: None,
+        }
+    }
+
+    /// Returns a receiver that is updated whenever the brain's status changes.
+    pub fn status(&self) -> watch::Receiver<BrainStatus> {
+        self.status.subscribe()
+    }
+
+    /// Starts running a program in QEMU, stopping any program that was already
+    /// running.
+    ///
+    /// `qemu_command` is the QEMU executable along with any extra arguments to
+    /// pass to it.
+    pub async fn run_program(
+        &mut self,
+        qemu_command: Command,
+        kernel: PathBuf,
+        main_binary: Binary,
+        linked_binary: Option<Binary>,
+    ) -> io::Result<()> {
+        let qemu_command = qemu_command.as_std();
+        self.config = Some(ProgramConfig {
+            qemu: qemu_command.get_program().to_owned(),
+            qemu_args: qemu_command.get_args().map(ToOwned::to_owned).collect(),
+            qemu_envs: qemu_command
+                .get_envs()
+                .map(|(key, value)| (key.to_owned(), value.map(ToOwned::to_owned)))
+                .collect(),
+            kernel,
+            main_binary,
+            linked_binary,
+        });
+
+        self.restart().await
+    }
+
+    /// Stops the current program and runs different binaries with the same
+    /// QEMU executable and kernel.
+    ///
+    /// Returns an error if no program has been run yet.
+    pub async fn reload_program(
+        &mut self,
+        main_binary: Binary,
+        linked_binary: Option<Binary>,
+    ) -> io::Result<()> {
+        let config = self
+            .config
+            .as_mut()
+            .ok_or_else(|| io::Error::other("No program has been run yet."))?;
+        config.main_binary = main_binary;
+        config.linked_binary = linked_binary;
+
+        self.restart().await
+    }
+
+    /// Stops the current program and runs it again from the start.
+    ///
+    /// Returns an error if no program has been run yet.
+    pub async fn restart(&mut self) -> io::Result<()> {
+        self.kill_program().await?;
+
+        let config = self
+            .config
+            .as_ref()
+            .ok_or_else(|| io::Error::other("No program has been run yet."))?;
+
+        let qmp_listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let mut qemu = config.command(qmp_listener.local_addr()?.port()).spawn()?;
+        let qemu_stdin = qemu.stdin.take().unwrap();
+        let qemu_stdout = qemu.stdout.take().unwrap();
+        let qemu = Arc::new(Mutex::new(qemu));
+
+        // Anything still queued up was meant for the last program, so it's thrown
+        // away. The state of the peripherals is sent again instead.
+        self.link_task.abort();
+        {
+            let mut link = self.link.lock().await;
+            while let Ok(packet) = link.rx.try_recv() {
+                link.state.observe(&packet);
+            }
+        }
+
+        // This must happen before the kernel can report that the program is
+        // running. Peripherals that wait for the brain to boot send their
+        // packets now, which are forwarded once the link task starts.
+        self.status.send_replace(BrainStatus::Booting);
+
+        let status = ProgramStatus::new(self.status.clone());
+        self.link_task = spawn_link_task(self.link.clone(), Some(qemu_stdin));
+        let rx_task = tokio::task::spawn(receive_packets(
+            qemu_stdout,
+            qemu.clone(),
+            self.routes.clone(),
+            status.clone(),
+        ))
+        .abort_handle();
+
+        self.program = Some(Program {
+            qemu,
+            qmp: QmpConnection::Listening(qmp_listener),
+            rx_task,
+            status,
+        });
+
+        Ok(())
+    }
+
+    /// Stops the current program, if there is one.
+    pub async fn kill_program(&mut self) -> io::Result<()> {
+        let Some(program) = self.program.take() else {
+            return Ok(());
+        };
+
+        program.rx_task.abort();
+        self.link_task.abort();
+        self.link_task = spawn_link_task(self.link.clone(), None);
+
+        let mut qemu = program.qemu.lock().await;
+        if qemu.try_wait()?.is_none() {
+            qemu.kill().await?;
+        }
+
+        self.status.send_if_modified(|status| {
+            let alive = status.is_alive();
+            if alive {
+                *status = BrainStatus::Stopped;
+            }
+            alive
+        });
+        Ok(())
+    }
+
+    /// Pauses the virtual machine, freezing the program until
+    /// [`Brain::resume`] is called.
+    pub async fn pause(&mut self) -> io::Result<()> {
+        let program = self.running_program()?;
+        if program.status.is_paused() {
+            return Ok(());
+        }
+
+        program.execute("stop").await?;
+        program.status.pause();
+        Ok(())
     }
 
+    /// Resumes a program paused with [`Brain::pause`].
+    pub async fn resume(&mut self) -> io::Result<()> {
+        let program = self.running_program()?;
+        if !program.status.is_paused() {
+            return Ok(());
+        }
+
+        program.execute("cont").await?;
+        program.status.resume();
+        Ok(())
+    }
+
+    fn running_program(&mut self) -> io::Result<&mut Program> {
+        self.program
+            .as_mut()
+            .filter(|_| self.status.borrow().is_alive())
+            .ok_or_else(|| io::Error::other("No program is running."))
+    }
+
+    /// Waits for the current program's QEMU process to exit, returning
+    /// immediately if there is no program.
     pub async fn wait_for_exit(&mut self) -> io::Result<Option<ExitStatus>> {
+        let Some(program) = &self.program else {
+            return Ok(None);
+        };
+
         loop {
-            if let Some(status) = self.qemu.lock().await.try_wait()? {
+            if let Some(status) = program.qemu.lock().await.try_wait()? {
                 return Ok(Some(status));
             }
             sleep(Duration::from_millis(10)).await;
         }
     }
+}
 
-    pub async fn terminate(&mut self) -> io::Result<()> {
-        self.rx_task.abort();
-        self.tx_task.abort();
+impl Drop for Brain {
+    fn drop(&mut self) {
+        self.link_task.abort();
+    }
+}
 
-        let mut qemu = self.qemu.lock().await;
-        qemu.kill().await?;
-        Ok(())
+/// Spawns a task that receives packets sent by the peripherals and forwards
+/// them to the kernel over `qemu_stdin`, starting with the state of the
+/// peripherals that the last program saw.
+///
+/// Without a kernel, or once QEMU has exited, the packets are still received
+/// to keep track of the peripherals' state.
+fn spawn_link_task(
+    link: Arc<Mutex<KernelLink>>,
+    mut qemu_stdin: Option<ChildStdin>,
+) -> AbortHandle {
+    tokio::task::spawn(async move {
+        let mut link = link.lock().await;
+        let link = &mut *link;
+
+        if let Some(stdin) = &mut qemu_stdin {
+            for packet in link.state.packets() {
+                if send_packet(stdin, packet.clone()).await.is_err() {
+                    qemu_stdin = None;
+                    break;
+                }
+            }
+        }
+
+        while let Some(packet) = link.rx.recv().await {
+            link.state.observe(&packet);
+
+            let Some(stdin) = &mut qemu_stdin else {
+                continue;
+            };
+            if send_packet(stdin, packet).await.is_err() {
+                qemu_stdin = None; // QEMU process has exited
+            }
+        }
+    })
+    .abort_handle()
+}
+
+/// Sends a packet to the kernel over QEMU's stdin.
+async fn send_packet(qemu_stdin: &mut ChildStdin, packet: KernelBoundPacket) -> io::Result<()> {
+    let encoded = bincode::encode_to_vec(packet, bincode::config::standard()).unwrap();
+    let mut bytes = Vec::new();
+
+    bytes.extend((encoded.len() as u32).to_le_bytes());
+    bytes.extend(encoded);
+
+    qemu_stdin.write_all(&bytes).await
+}
+
+/// Forwards packets sent by the kernel to the peripherals until QEMU exits.
+async fn receive_packets(
+    mut qemu_stdout: ChildStdout,
+    qemu: Arc<Mutex<Child>>,
+    routes: Routes,
+    status: ProgramStatus,
+) {
+    loop {
+        let incoming_packet: HostBoundPacket = match read_packet(&mut qemu_stdout).await {
+            Ok(packet) => packet,
+            Err(err) => {
+                log::error!("Lost connection to the kernel: {err}");
+                status.set(BrainStatus::Crashed);
+                break;
+            }
+        };
+
+        match incoming_packet {
+            // Forward sent data to usb peripheral.
+            HostBoundPacket::UsbSerial(data) => {
+                _ = routes.usb.send(data).await;
+            }
+
+            // Kernel debugging stuff (logs mainly) goes to stderr
+            HostBoundPacket::KernelSerial(data) => {
+                let mut stderr = tokio::io::stderr();
+                stderr.write_all(&data).await.unwrap();
+                stderr.flush().await.unwrap();
+            }
+
+            // The kernel sends the code signature right before it starts the
+            // user program.
+            HostBoundPacket::CodeSignature(_) => {
+                status.set(BrainStatus::Running);
+            }
+
+            // Kill QEMU child process when kernel requests exit.
+            HostBoundPacket::ExitRequest(code) => {
+                qemu.lock().await.kill().await.unwrap();
+                log::info!("Kernel exited with code {code}.");
+                status.set(BrainStatus::Exited(code));
+                break;
+            }
+
+            // The kernel has sent a device command packet to a specific smartport,
+            // so we must forward that packet to the respective smartport's
+            // receiver.
+            HostBoundPacket::SmartPortCommand { port, command } => {
+                if let Some(port_tx) = routes.smartports.get(port as usize) {
+                    // We ignore errors if the packet send fails, since it means
+                    // either the user has dropped the smartport and by extension
+                    // the receiver, or nothing is reading commands from the port.
+                    // Waiting here would stall every other packet from the kernel.
+                    _ = port_tx.try_send(command);
+                }
+            }
+
+            HostBoundPacket::DisplayCommand { command } => {
+                // Some commands are waited on by the kernel, so without a
+                // display they must be answered here instead.
+                if let Err(SendError(command)) = routes.display.send(command).await {
+                    let reply = match command {
+                        DisplayCommand::DecodeImage { .. } => KernelBoundPacket::DecodedImage(None),
+                        DisplayCommand::MeasureText { .. } => {
+                            KernelBoundPacket::TextMetrics(TextMetrics::default())
+                        }
+                        _ => continue,
+                    };
+                    _ = routes.kernel.send(reply).await;
+                }
+            }
+
+            HostBoundPacket::SdCardRequest(request) => {
+                // Without an SD card slot, the kernel would wait forever for a
+                // response.
+                if routes.sd_card.send(request).await.is_err() {
+                    _ = routes
+                        .kernel
+                        .send(KernelBoundPacket::SdCardResponse(Err(FileError::NotReady)))
+                        .await;
+                }
+            }
+        }
     }
 }
 
-impl Drop for Brain {
-    fn drop(&mut self) {
-        self.tx_task.abort();
-        self.rx_task.abort();
+/// Reads a packet sent by the kernel over QEMU's stdout.
+async fn read_packet(qemu_stdout: &mut ChildStdout) -> io::Result<HostBoundPacket> {
+    let packet_size = qemu_stdout.read_u32_le().await? as usize;
+    let mut buf = vec![0u8; packet_size];
+
+    qemu_stdout.read_exact(&mut buf).await?;
+
+    bincode::decode_from_slice(&buf, bincode::config::standard())
+        .map(|(packet, _)| packet)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use vex_v5_qemu_protocol::{
+        battery::BatteryData, competition::CompetitionData, touch::TouchData,
+    };
+
+    use super::*;
+
+    const fn battery(timestamp: u32) -> KernelBoundPacket {
+        KernelBoundPacket::BatteryUpdate {
+            data: BatteryData {
+                voltage: 12_800,
+                current: 0,
+                temperature: 25.0,
+                capacity: 100.0,
+            },
+            timestamp,
+        }
+    }
+
+    const fn controller(id: ControllerId, timestamp: u32) -> KernelBoundPacket {
+        KernelBoundPacket::ControllerUpdate {
+            id,
+            data: None,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn peripheral_state_keeps_the_last_packet() {
+        let mut state = PeripheralState::default();
+        state.observe(&battery(1));
+        state.observe(&battery(2));
+
+        assert_eq!(state.packets().collect::<Vec<_>>(), [&battery(2)]);
+    }
+
+    #[test]
+    fn peripheral_state_replays_in_order() {
+        let mut state = PeripheralState::default();
+        let competition = KernelBoundPacket::CompetitionUpdate(CompetitionData::default());
+        let touch = KernelBoundPacket::Touch(TouchData::default());
+
+        state.observe(&touch);
+        state.observe(&competition);
+        state.observe(&controller(ControllerId::Partner, 1));
+        state.observe(&controller(ControllerId::Master, 2));
+        state.observe(&battery(3));
+        state.observe(&controller(ControllerId::Partner, 4));
+        state.observe(&KernelBoundPacket::UsbSerial(vec![1, 2, 3]));
+        state.observe(&KernelBoundPacket::RealTime(0));
+
+        assert_eq!(
+            state.packets().collect::<Vec<_>>(),
+            [
+                &battery(3),
+                &controller(ControllerId::Master, 2),
+                &controller(ControllerId::Partner, 4),
+                &competition,
+                &touch,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn peripherals_dont_block_without_a_program() {
+        let mut brain = Brain::new();
+        let mut peripherals = brain.peripherals.take().unwrap();
+
+        for _ in 0..2048 {
+            tokio::time::timeout(
+                Duration::from_secs(1),
+                peripherals.touch.set_data(TouchData::default()),
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn status_changes_while_paused_are_published_on_resume() {
+        let (tx, rx) = watch::channel(BrainStatus::Booting);
+        let status = ProgramStatus::new(tx);
+
+        status.pause();
+        status.set(BrainStatus::Running);
+        assert_eq!(*rx.borrow(), BrainStatus::Paused);
+
+        status.resume();
+        assert_eq!(*rx.borrow(), BrainStatus::Running);
+    }
+
+    #[test]
+    fn stopping_ends_a_pause() {
+        let (tx, rx) = watch::channel(BrainStatus::Running);
+        let status = ProgramStatus::new(tx);
+
+        status.pause();
+        status.set(BrainStatus::Crashed);
+        assert_eq!(*rx.borrow(), BrainStatus::Crashed);
+        assert!(!status.is_paused());
+
+        status.pause();
+        assert_eq!(*rx.borrow(), BrainStatus::Crashed);
     }
 }
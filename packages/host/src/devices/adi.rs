@@ -0,0 +1,193 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{sync::Mutex, task::AbortHandle, time::interval};
+use vex_v5_qemu_protocol::{
+    adi::{AdiCommand, AdiData, AdiPort, AdiPortConfig, ADI_PORT_COUNT},
+    SmartPortCommand, SmartPortData,
+};
+
+use crate::peripherals::smartport::SmartPort;
+
+/// How often the three-wire ports are sampled.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The longest addressable LED strip that a three-wire port can drive.
+pub const MAX_LEDS: usize = 64;
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+struct AdiModel {
+    ports: [AdiPort; ADI_PORT_COUNT],
+    /// Colors of the addressable LED strip on each port.
+    leds: [Vec<u32>; ADI_PORT_COUNT],
+}
+
+impl AdiModel {
+    /// Applies a command sent to the ports by the brain.
+    fn apply(&mut self, command: AdiCommand) {
+        match command {
+            AdiCommand::SetConfig { port, config } => {
+                if let Some(port) = self.ports.get_mut(port as usize) {
+                    *port = AdiPort { config, value: 0 };
+                }
+            }
+            AdiCommand::SetValue { port, value } => {
+                if let Some(port) = self.ports.get_mut(port as usize) {
+                    port.value = value;
+                }
+            }
+            AdiCommand::SetLeds {
+                port,
+                offset,
+                colors,
+            } => {
+                let Some(leds) = self.leds.get_mut(port as usize) else {
+                    return;
+                };
+
+                // Colors past the end of the longest possible strip have nowhere to go.
+                let offset = (offset as usize).min(MAX_LEDS);
+                let colors = &colors[..colors.len().min(MAX_LEDS - offset)];
+                let end = offset + colors.len();
+
+                if leds.len() < end {
+                    leds.resize(end, 0);
+                }
+                leds[offset..end].copy_from_slice(colors);
+            }
+        }
+    }
+
+    const fn data(&self) -> AdiData {
+        AdiData { ports: self.ports }
+    }
+}
+
+/// A set of eight simulated three-wire ports.
+///
+/// This can be used either for the brain's onboard ports or for a three-wire
+/// expander plugged into a smartport. Ports are zero-indexed, so port A is 0
+/// and port H is 7.
+///
+/// # Panics
+///
+/// Methods taking a port index panic if it is not less than
+/// [`ADI_PORT_COUNT`].
+#[derive(Debug)]
+pub struct Adi {
+    task: AbortHandle,
+    model: Arc<Mutex<AdiModel>>,
+}
+
+impl Adi {
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let model = Arc::new(Mutex::new(AdiModel::default()));
+
+        Self {
+            model: model.clone(),
+            task: tokio::task::spawn(async move {
+                let mut update_interval = interval(UPDATE_INTERVAL);
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            if let SmartPortCommand::Adi(command) = command {
+                                model.lock().await.apply(command);
+                            }
+                        }
+                        _ = update_interval.tick() => {
+                            let data = model.lock().await.data();
+
+                            port.send(SmartPortData::Adi(data), start.elapsed().as_millis() as u32)
+                                .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns what a port has been configured to do by the brain.
+    pub async fn config(&self, port: u8) -> AdiPortConfig {
+        self.model.lock().await.ports[port as usize].config
+    }
+
+    /// Returns the current value of a port.
+    ///
+    /// For outputs, this is the last value written by the brain.
+    pub async fn value(&self, port: u8) -> i32 {
+        self.model.lock().await.ports[port as usize].value
+    }
+
+    /// Sets the value read by the brain from a port.
+    ///
+    /// The meaning of the value depends on how the port is configured. See
+    /// [`AdiPortConfig`] for the units used by each configuration. The value
+    /// is reset to zero whenever the port is reconfigured.
+    pub async fn set_value(&mut self, port: u8, value: i32) {
+        self.model.lock().await.ports[port as usize].value = value;
+    }
+
+    /// Sets the voltage read by an analog input, as a 12-bit value from 0 to
+    /// 4095.
+    pub async fn set_analog(&mut self, port: u8, value: u16) {
+        self.set_value(port, value.min(4095) as i32).await;
+    }
+
+    /// Sets the level read by a digital input, such as a limit switch.
+    pub async fn set_digital(&mut self, port: u8, level: bool) {
+        self.set_value(port, level as i32).await;
+    }
+
+    /// Returns the level of a digital output.
+    pub async fn digital_output(&self, port: u8) -> bool {
+        self.value(port).await != 0
+    }
+
+    /// Returns the colors of the addressable LED strip connected to a port.
+    pub async fn leds(&self, port: u8) -> Vec<u32> {
+        self.model.lock().await.leds[port as usize].clone()
+    }
+}
+
+impl Drop for Adi {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_leds(model: &mut AdiModel, offset: u32, colors: Vec<u32>) {
+        model.apply(AdiCommand::SetLeds {
+            port: 0,
+            offset,
+            colors,
+        });
+    }
+
+    #[test]
+    fn leds_are_written_at_offset() {
+        let mut model = AdiModel::default();
+        set_leds(&mut model, 2, vec![0xFF0000, 0x00FF00]);
+
+        assert_eq!(model.leds[0], [0, 0, 0xFF0000, 0x00FF00]);
+    }
+
+    #[test]
+    fn leds_past_the_strip_are_dropped() {
+        let mut model = AdiModel::default();
+        set_leds(&mut model, MAX_LEDS as u32 - 1, vec![1, 2, 3]);
+        assert_eq!(model.leds[0].len(), MAX_LEDS);
+        assert_eq!(model.leds[0][MAX_LEDS - 1], 1);
+
+        set_leds(&mut model, u32::MAX, vec![4]);
+        assert_eq!(model.leds[0].len(), MAX_LEDS);
+    }
+}
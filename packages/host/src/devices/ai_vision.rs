@@ -0,0 +1,197 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{sync::Mutex, task::AbortHandle, time::interval};
+use vex_v5_qemu_protocol::{
+    ai_vision::{
+        AiVisionCode, AiVisionColor, AiVisionCommand, AiVisionData, AiVisionObject,
+        AI_VISION_CODE_COUNT, AI_VISION_COLOR_COUNT,
+    },
+    SmartPortCommand, SmartPortData,
+};
+
+use super::DEFAULT_TEMPERATURE;
+use crate::peripherals::smartport::SmartPort;
+
+/// How often the sensor reports the objects in view. The sensor processes
+/// frames at roughly 30 frames per second.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(33);
+
+#[derive(Debug, Clone, PartialEq)]
+struct AiVisionModel {
+    objects: Vec<AiVisionObject>,
+    class_names: Vec<String>,
+    colors: Vec<AiVisionColor>,
+    codes: Vec<AiVisionCode>,
+
+    mode: u32,
+    status: u32,
+    temperature: f64,
+    brightness: f64,
+    contrast: f64,
+}
+
+impl AiVisionModel {
+    const fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+            class_names: Vec::new(),
+            colors: Vec::new(),
+            codes: Vec::new(),
+            mode: 0,
+            status: 0,
+            temperature: DEFAULT_TEMPERATURE,
+            brightness: 0.0,
+            contrast: 0.0,
+        }
+    }
+
+    /// Applies a command sent to the sensor by the brain.
+    fn apply(&mut self, command: AiVisionCommand) {
+        match command {
+            AiVisionCommand::SetMode(mode) => self.mode = mode,
+            AiVisionCommand::SetColor(color) => {
+                if !(1..=AI_VISION_COLOR_COUNT as u8).contains(&color.id) {
+                    return;
+                }
+
+                self.colors.retain(|c| c.id != color.id);
+                self.colors.push(color);
+                self.colors.sort_by_key(|c| c.id);
+            }
+            AiVisionCommand::SetCode(code) => {
+                if !(1..=AI_VISION_CODE_COUNT as u8).contains(&code.id) {
+                    return;
+                }
+
+                self.codes.retain(|c| c.id != code.id);
+                self.codes.push(code);
+                self.codes.sort_by_key(|c| c.id);
+            }
+            AiVisionCommand::SetSensor {
+                brightness,
+                contrast,
+            } => {
+                self.brightness = brightness;
+                self.contrast = contrast;
+            }
+        }
+    }
+
+    fn data(&self) -> AiVisionData {
+        AiVisionData {
+            objects: self.objects.clone(),
+            class_names: self.class_names.clone(),
+            colors: self.colors.clone(),
+            codes: self.codes.clone(),
+            mode: self.mode,
+            status: self.status,
+            temperature: self.temperature,
+            brightness: self.brightness,
+            contrast: self.contrast,
+        }
+    }
+}
+
+/// A simulated V5 AI Vision Sensor.
+///
+/// The sensor doesn't look at anything by itself. Instead, the objects that it
+/// detects are set directly with [`AiVision::set_objects`].
+#[derive(Debug)]
+pub struct AiVision {
+    task: AbortHandle,
+    model: Arc<Mutex<AiVisionModel>>,
+}
+
+impl AiVision {
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let model = Arc::new(Mutex::new(AiVisionModel::new()));
+
+        Self {
+            model: model.clone(),
+            task: tokio::task::spawn(async move {
+                let mut update_interval = interval(UPDATE_INTERVAL);
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            if let SmartPortCommand::AiVision(command) = command {
+                                model.lock().await.apply(command);
+                            }
+                        }
+                        _ = update_interval.tick() => {
+                            let data = model.lock().await.data();
+
+                            port.send(
+                                SmartPortData::AiVision(data),
+                                start.elapsed().as_millis() as u32,
+                            )
+                            .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns the data that the sensor is currently reporting to the brain.
+    pub async fn data(&self) -> AiVisionData {
+        self.model.lock().await.data()
+    }
+
+    /// Sets the objects in view of the sensor.
+    pub async fn set_objects(&mut self, objects: Vec<AiVisionObject>) {
+        self.model.lock().await.objects = objects;
+    }
+
+    /// Sets the names of the classes that the sensor's AI model can detect.
+    ///
+    /// The ID of each class is its index in `names`.
+    pub async fn set_class_names(&mut self, names: Vec<String>) {
+        self.model.lock().await.class_names = names;
+    }
+
+    /// Returns the color with the given ID that has been configured by the
+    /// brain, if there is one.
+    pub async fn color(&self, id: u8) -> Option<AiVisionColor> {
+        self.model
+            .lock()
+            .await
+            .colors
+            .iter()
+            .find(|color| color.id == id)
+            .copied()
+    }
+
+    /// Returns the color code with the given ID that has been configured by
+    /// the brain, if there is one.
+    pub async fn code(&self, id: u8) -> Option<AiVisionCode> {
+        self.model
+            .lock()
+            .await
+            .codes
+            .iter()
+            .find(|code| code.id == id)
+            .cloned()
+    }
+
+    /// Sets the status flags reported by the sensor.
+    pub async fn set_status(&mut self, status: u32) {
+        self.model.lock().await.status = status;
+    }
+
+    /// Sets the temperature reported by the sensor (in degrees celsius).
+    pub async fn set_temperature(&mut self, temperature: f64) {
+        self.model.lock().await.temperature = temperature;
+    }
+}
+
+impl Drop for AiVision {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    sync::{
+        mpsc::{self, error::TrySendError, Receiver, Sender},
+        Mutex,
+    },
+    task::AbortHandle,
+};
+use vex_v5_qemu_protocol::{generic_serial::GenericSerialCommand, SmartPortCommand};
+
+use crate::peripherals::smartport::SmartPort;
+
+/// Number of unread chunks of data that can be buffered in each direction.
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+struct GenericSerialState {
+    enabled: bool,
+    baudrate: Option<i32>,
+}
+
+/// A device connected to a smartport in generic serial mode, such as a
+/// coprocessor.
+///
+/// Reading from this device yields the bytes transmitted by the brain, and
+/// writing to it sends bytes to the brain. Bytes written while the brain
+/// hasn't enabled generic serial on the port are lost.
+#[derive(Debug)]
+pub struct GenericSerial {
+    task: AbortHandle,
+    state: Arc<Mutex<GenericSerialState>>,
+    rx: Receiver<Vec<u8>>,
+    tx: Sender<Vec<u8>>,
+    read_buf: Vec<u8>,
+    eof: bool,
+}
+
+impl GenericSerial {
+    pub fn new(mut port: SmartPort) -> Self {
+        let state = Arc::new(Mutex::new(GenericSerialState::default()));
+        let (brain_tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let (tx, mut brain_rx) = mpsc::channel::<Vec<u8>>(CHANNEL_CAPACITY);
+
+        Self {
+            state: state.clone(),
+            rx,
+            tx,
+            read_buf: Vec::new(),
+            eof: false,
+            task: tokio::task::spawn(async move {
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            let SmartPortCommand::GenericSerial(command) = command else {
+                                continue;
+                            };
+
+                            match command {
+                                GenericSerialCommand::Enable { .. } => {
+                                    state.lock().await.enabled = true;
+                                }
+                                GenericSerialCommand::SetBaudrate(baudrate) => {
+                                    state.lock().await.baudrate = Some(baudrate);
+                                }
+                                GenericSerialCommand::Transmit(data) => {
+                                    // The reading half is owned by the same struct that aborts
+                                    // this task, so it can't have been dropped.
+                                    _ = brain_tx.send(data).await;
+                                }
+                            }
+                        }
+                        Some(data) = brain_rx.recv() => {
+                            port.send_serial(data).await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns whether the brain has put the port into generic serial mode.
+    pub async fn enabled(&self) -> bool {
+        self.state.lock().await.enabled
+    }
+
+    /// Returns the baud rate configured by the brain, if it has set one.
+    pub async fn baudrate(&self) -> Option<i32> {
+        self.state.lock().await.baudrate
+    }
+
+    /// Waits for the next chunk of bytes transmitted by the brain.
+    pub async fn recv(&mut self) -> Option<Vec<u8>> {
+        self.rx.recv().await
+    }
+
+    /// Sends bytes to the brain.
+    pub async fn send(&mut self, data: Vec<u8>) -> io::Result<()> {
+        self.tx
+            .send(data)
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Port was disconnected."))
+    }
+}
+
+impl AsyncRead for GenericSerial {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if !self.eof {
+            loop {
+                match self.rx.poll_recv(cx) {
+                    Poll::Ready(Some(data)) => {
+                        self.read_buf.extend(data);
+                    }
+                    Poll::Ready(None) => {
+                        self.eof = true;
+                        break;
+                    }
+                    Poll::Pending => break,
+                }
+            }
+        }
+
+        let read_len = buf.remaining().min(self.read_buf.len());
+        if read_len == 0 && !self.eof {
+            return Poll::Pending;
+        }
+
+        buf.put_slice(&self.read_buf[..read_len]);
+        self.read_buf.drain(0..read_len);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for GenericSerial {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        match self.tx.try_send(buf.to_vec()) {
+            Ok(()) => Poll::Ready(Ok(buf.len())),
+            Err(TrySendError::Full(_)) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Err(TrySendError::Closed(_)) => Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "Port was disconnected.",
+            ))),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Drop for GenericSerial {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,337 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use tokio::{
+    sync::Mutex,
+    task::AbortHandle,
+    time::{self, sleep_until},
+};
+use vex_v5_qemu_protocol::{
+    geometry::{Point2, Vector3},
+    gps::{GpsCommand, GpsData, GpsStatus},
+    imu::{ImuAttitude, ImuQuaternion},
+    SmartPortCommand, SmartPortData,
+};
+
+use super::{supported_data_rate, wrap_degrees, DEFAULT_DATA_RATE, DEFAULT_TEMPERATURE};
+use crate::peripherals::smartport::SmartPort;
+
+/// The position and heading of a robot on the field.
+///
+/// Positions are measured in meters from the center of the field, and
+/// headings are measured in degrees clockwise from the positive Y axis.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct FieldPose {
+    pub x: f64,
+    pub y: f64,
+    pub heading: f64,
+}
+
+impl FieldPose {
+    pub const fn new(x: f64, y: f64, heading: f64) -> Self {
+        Self { x, y, heading }
+    }
+}
+
+/// Converts a vector from a robot's frame of reference (where +Y is forwards
+/// and +X is to the right) to the field's, given the robot's heading.
+fn to_field(heading: f64, vector: Point2<f64>) -> Point2<f64> {
+    let (sin, cos) = heading.to_radians().sin_cos();
+
+    Point2 {
+        x: vector.x * cos + vector.y * sin,
+        y: vector.y * cos - vector.x * sin,
+    }
+}
+
+/// Physical model of a V5 GPS Sensor.
+///
+/// The sensor finds its own position and heading by looking at the field
+/// strip, then applies the origin and rotation offsets configured by the
+/// brain to work out where the robot is. While the field strip is occluded,
+/// the sensor holds its last position and tracks its heading using its
+/// internal gyroscope.
+#[derive(Debug, Clone)]
+struct GpsModel {
+    /// True pose of the robot on the field.
+    pose: FieldPose,
+    /// True position of the sensor relative to the robot's center of
+    /// rotation (in meters).
+    mount_offset: Point2<f64>,
+    /// True heading of the sensor relative to the front of the robot (in
+    /// degrees).
+    mount_rotation: f64,
+    /// Whether the sensor's view of the field strip is blocked.
+    occluded: bool,
+
+    /// Position of the sensor from its most recent fix (in meters).
+    fix: Point2<f64>,
+    /// Unbounded heading of the sensor as estimated by the sensor (in
+    /// degrees).
+    heading: f64,
+    /// True heading of the sensor at the time of the last reading (in
+    /// degrees).
+    last_true_heading: f64,
+    /// Most recent gyroscope reading (in deg/s).
+    gyro: Vector3<f64>,
+
+    /// Configured position of the sensor relative to the robot's center.
+    origin: Point2<f64>,
+    /// Configured heading of the sensor relative to the front of the robot.
+    rotation_offset: f64,
+
+    /// RMS error reported by the sensor (in meters).
+    error: f64,
+    /// Standard deviation of the noise added to each position fix (in meters).
+    position_noise: f64,
+    /// Standard deviation of the noise added to each heading fix (in
+    /// degrees).
+    heading_noise: f64,
+    rng: StdRng,
+
+    temperature: f64,
+    mode: u32,
+    data_rate: u32,
+}
+
+impl GpsModel {
+    fn new() -> Self {
+        Self {
+            pose: FieldPose::default(),
+            mount_offset: Point2 { x: 0.0, y: 0.0 },
+            mount_rotation: 0.0,
+            occluded: false,
+            fix: Point2 { x: 0.0, y: 0.0 },
+            heading: 0.0,
+            last_true_heading: 0.0,
+            gyro: Vector3::default(),
+            origin: Point2 { x: 0.0, y: 0.0 },
+            rotation_offset: 0.0,
+            error: 0.0,
+            position_noise: 0.0,
+            heading_noise: 0.0,
+            rng: StdRng::from_entropy(),
+            temperature: DEFAULT_TEMPERATURE,
+            mode: 0,
+            data_rate: DEFAULT_DATA_RATE,
+        }
+    }
+
+    /// Samples gaussian noise with the given standard deviation.
+    fn noise(&mut self, std_dev: f64) -> f64 {
+        Normal::new(0.0, std_dev).map_or(0.0, |normal| normal.sample(&mut self.rng))
+    }
+
+    /// The robot's heading, as estimated by the sensor (in degrees).
+    fn robot_heading(&self) -> f64 {
+        self.heading - self.rotation_offset
+    }
+
+    /// Applies a command sent to the sensor by the brain.
+    fn apply(&mut self, command: GpsCommand) {
+        match command {
+            GpsCommand::Reset => self.heading = self.heading.rem_euclid(360.0),
+            GpsCommand::SetOrigin(origin) => self.origin = origin,
+            GpsCommand::SetRotation(rotation) => self.rotation_offset = rotation,
+            GpsCommand::SetInitialPosition { position, heading } => {
+                self.heading += wrap_degrees(heading + self.rotation_offset - self.heading);
+
+                let offset = to_field(self.robot_heading(), self.origin);
+                self.fix = Point2 {
+                    x: position.x + offset.x,
+                    y: position.y + offset.y,
+                };
+            }
+            GpsCommand::SetMode(mode) => self.mode = mode,
+            GpsCommand::SetDataRate(rate) => {
+                self.data_rate = supported_data_rate(rate);
+            }
+        }
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    fn step(&mut self, dt: f64) {
+        let true_heading = self.pose.heading + self.mount_rotation;
+        let last_heading = self.heading;
+
+        if self.occluded {
+            // Without the field strip, the sensor can only dead-reckon its
+            // heading using its gyroscope.
+            self.heading += wrap_degrees(true_heading - self.last_true_heading);
+        } else {
+            let offset = to_field(self.pose.heading, self.mount_offset);
+            self.fix = Point2 {
+                x: self.pose.x + offset.x + self.noise(self.position_noise),
+                y: self.pose.y + offset.y + self.noise(self.position_noise),
+            };
+
+            let measured_heading = true_heading + self.noise(self.heading_noise);
+            self.heading += wrap_degrees(measured_heading - self.heading);
+        }
+        self.last_true_heading = true_heading;
+
+        // Headings increase clockwise, while the gyroscope's yaw rate follows
+        // the right-hand rule.
+        self.gyro = Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: -(self.heading - last_heading) / dt,
+        };
+    }
+
+    fn data(&self) -> GpsData {
+        let offset = to_field(self.robot_heading(), self.origin);
+
+        // The robot is assumed to be level, so it only rotates around the Z
+        // axis (counterclockwise, by the right-hand rule).
+        let (sin, cos) = (-self.robot_heading().to_radians() / 2.0).sin_cos();
+
+        GpsData {
+            position: Point2 {
+                x: self.fix.x - offset.x,
+                y: self.fix.y - offset.y,
+            },
+            raw_position: self.fix,
+            heading: self.robot_heading().rem_euclid(360.0),
+            raw_heading: self.heading.rem_euclid(360.0),
+            rotation: self.robot_heading(),
+            quaternion: ImuQuaternion {
+                x: 0.0,
+                y: 0.0,
+                z: sin,
+                w: cos,
+            },
+            attitude: ImuAttitude {
+                pitch: 0.0,
+                roll: 0.0,
+                yaw: wrap_degrees(self.robot_heading()),
+            },
+            origin: self.origin,
+            rotation_offset: self.rotation_offset,
+            error: self.error,
+            gyro: self.gyro,
+            accel: Vector3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            status: if self.occluded {
+                GpsStatus::empty()
+            } else {
+                GpsStatus::POSITION_VALID
+            },
+            temperature: self.temperature,
+            mode: self.mode,
+            data_rate: self.data_rate,
+        }
+    }
+}
+
+/// A simulated V5 GPS Sensor.
+#[derive(Debug)]
+pub struct Gps {
+    task: AbortHandle,
+    model: Arc<Mutex<GpsModel>>,
+}
+
+impl Gps {
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let model = Arc::new(Mutex::new(GpsModel::new()));
+
+        Self {
+            model: model.clone(),
+            task: tokio::task::spawn(async move {
+                let mut next_update = time::Instant::now();
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            if let SmartPortCommand::Gps(command) = command {
+                                model.lock().await.apply(command);
+                            }
+                        }
+                        _ = sleep_until(next_update) => {
+                            let data = {
+                                let mut model = model.lock().await;
+                                let data_rate = Duration::from_millis(model.data_rate as u64);
+
+                                model.step(data_rate.as_secs_f64());
+                                next_update += data_rate;
+
+                                model.data()
+                            };
+
+                            port.send(SmartPortData::Gps(data), start.elapsed().as_millis() as u32)
+                                .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns the data that the sensor is currently reporting to the brain.
+    pub async fn data(&self) -> GpsData {
+        self.model.lock().await.data()
+    }
+
+    /// Moves the robot that the sensor is mounted on.
+    pub async fn set_pose(&mut self, pose: FieldPose) {
+        self.model.lock().await.pose = pose;
+    }
+
+    /// Sets where the sensor is physically mounted on the robot.
+    ///
+    /// `offset` is the position of the sensor relative to the robot's center
+    /// of rotation (in meters) and `rotation` is the heading of the sensor
+    /// relative to the front of the robot (in degrees). The brain must
+    /// configure a matching origin and rotation for the sensor to report the
+    /// robot's true pose.
+    pub async fn set_mounting(&mut self, offset: Point2<f64>, rotation: f64) {
+        let mut model = self.model.lock().await;
+        model.mount_offset = offset;
+        model.mount_rotation = rotation;
+    }
+
+    /// Blocks or unblocks the sensor's view of the field strip.
+    ///
+    /// While occluded, the sensor's position is marked as invalid and stops
+    /// updating.
+    pub async fn set_occluded(&mut self, occluded: bool) {
+        self.model.lock().await.occluded = occluded;
+    }
+
+    /// Sets the RMS error reported by the sensor (in meters).
+    pub async fn set_error(&mut self, error: f64) {
+        self.model.lock().await.error = error;
+    }
+
+    /// Sets the standard deviation of random noise added to each position fix
+    /// (in meters).
+    pub async fn set_position_noise(&mut self, std_dev: f64) {
+        self.model.lock().await.position_noise = std_dev;
+    }
+
+    /// Sets the standard deviation of random noise added to each heading fix
+    /// (in degrees).
+    pub async fn set_heading_noise(&mut self, std_dev: f64) {
+        self.model.lock().await.heading_noise = std_dev;
+    }
+
+    /// Sets the temperature reported by the sensor (in degrees celsius).
+    pub async fn set_temperature(&mut self, temperature: f64) {
+        self.model.lock().await.temperature = temperature;
+    }
+}
+
+impl Drop for Gps {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -0,0 +1,405 @@
+use std::{
+    f64::consts::PI,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use tokio::{
+    sync::Mutex,
+    task::AbortHandle,
+    time::{self, sleep_until},
+};
+use vex_v5_qemu_protocol::{
+    geometry::Vector3,
+    imu::{ImuAttitude, ImuCommand, ImuData, ImuQuaternion, ImuStatus},
+    SmartPortCommand, SmartPortData,
+};
+
+use super::{supported_data_rate, wrap_degrees, DEFAULT_DATA_RATE, DEFAULT_TEMPERATURE};
+use crate::peripherals::smartport::SmartPort;
+
+/// How long the sensor spends calibrating after being reset.
+const CALIBRATION_TIME: Duration = Duration::from_secs(2);
+
+/// A rotation in 3D space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Quaternion {
+    w: f64,
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+impl Quaternion {
+    const IDENTITY: Self = Self {
+        w: 1.0,
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+
+    /// Creates a quaternion from Z-Y-X euler angles (in radians).
+    fn from_euler(roll: f64, pitch: f64, yaw: f64) -> Self {
+        let (sr, cr) = (roll / 2.0).sin_cos();
+        let (sp, cp) = (pitch / 2.0).sin_cos();
+        let (sy, cy) = (yaw / 2.0).sin_cos();
+
+        Self {
+            w: cr * cp * cy + sr * sp * sy,
+            x: sr * cp * cy - cr * sp * sy,
+            y: cr * sp * cy + sr * cp * sy,
+            z: cr * cp * sy - sr * sp * cy,
+        }
+    }
+
+    /// Creates the rotation that results from turning at an angular velocity
+    /// (in rad/s) for `dt` seconds.
+    fn from_angular_velocity(velocity: Vector3<f64>, dt: f64) -> Self {
+        let speed = (velocity.x.powi(2) + velocity.y.powi(2) + velocity.z.powi(2)).sqrt();
+        if speed == 0.0 {
+            return Self::IDENTITY;
+        }
+
+        let (sin, cos) = (speed * dt / 2.0).sin_cos();
+        Self {
+            w: cos,
+            x: velocity.x / speed * sin,
+            y: velocity.y / speed * sin,
+            z: velocity.z / speed * sin,
+        }
+    }
+
+    /// Returns the Z-Y-X euler angles of this rotation as `(roll, pitch,
+    /// yaw)` in radians.
+    fn to_euler(self) -> (f64, f64, f64) {
+        let Self { w, x, y, z } = self;
+
+        (
+            (2.0 * (w * x + y * z)).atan2(1.0 - 2.0 * (x * x + y * y)),
+            (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin(),
+            (2.0 * (w * z + x * y)).atan2(1.0 - 2.0 * (y * y + z * z)),
+        )
+    }
+
+    fn normalize(self) -> Self {
+        let norm = (self.w * self.w + self.x * self.x + self.y * self.y + self.z * self.z).sqrt();
+        Self {
+            w: self.w / norm,
+            x: self.x / norm,
+            y: self.y / norm,
+            z: self.z / norm,
+        }
+    }
+
+    const fn conjugate(self) -> Self {
+        Self {
+            w: self.w,
+            x: -self.x,
+            y: -self.y,
+            z: -self.z,
+        }
+    }
+
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            w: self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            x: self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            y: self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            z: self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        }
+    }
+
+    fn rotate(self, vector: Vector3<f64>) -> Vector3<f64> {
+        let rotated = self
+            .mul(Self {
+                w: 0.0,
+                x: vector.x,
+                y: vector.y,
+                z: vector.z,
+            })
+            .mul(self.conjugate());
+
+        Vector3 {
+            x: rotated.x,
+            y: rotated.y,
+            z: rotated.z,
+        }
+    }
+}
+
+/// Physical model of a V5 Inertial Sensor.
+///
+/// The sensor's true orientation is driven by the angular velocity it is
+/// given, while its reported orientation is integrated from simulated
+/// gyroscope readings. Any drift or noise added to the gyroscope therefore
+/// accumulates in the reported heading, just like on a real sensor.
+#[derive(Debug, Clone)]
+struct ImuModel {
+    /// True orientation of the sensor in the world.
+    orientation: Quaternion,
+    /// Orientation of the sensor as estimated from its gyroscope.
+    estimate: Quaternion,
+    /// Unbounded clockwise rotation estimated from the gyroscope (in
+    /// degrees).
+    rotation: f64,
+    /// Time at which the sensor's current calibration finishes.
+    calibration_end: Option<Instant>,
+
+    /// True angular velocity of the sensor in its own frame (in deg/s).
+    angular_velocity: Vector3<f64>,
+    /// True acceleration of the sensor in its own frame, not including
+    /// gravity (in G).
+    acceleration: Vector3<f64>,
+
+    /// Most recent gyroscope reading (in deg/s).
+    gyro: Vector3<f64>,
+    /// Most recent accelerometer reading (in G).
+    accel: Vector3<f64>,
+
+    /// Constant bias added to gyroscope readings (in deg/s).
+    gyro_drift: Vector3<f64>,
+    /// Standard deviation of the noise added to gyroscope readings (in deg/s).
+    gyro_noise: f64,
+    /// Standard deviation of the noise added to accelerometer readings (in G).
+    accel_noise: f64,
+    rng: StdRng,
+
+    temperature: f64,
+    mode: u32,
+    data_rate: u32,
+}
+
+impl ImuModel {
+    fn new() -> Self {
+        Self {
+            orientation: Quaternion::IDENTITY,
+            estimate: Quaternion::IDENTITY,
+            rotation: 0.0,
+            calibration_end: None,
+            angular_velocity: Vector3::default(),
+            acceleration: Vector3::default(),
+            gyro: Vector3::default(),
+            accel: Vector3 {
+                x: 0.0,
+                y: 0.0,
+                z: 1.0,
+            },
+            gyro_drift: Vector3::default(),
+            gyro_noise: 0.0,
+            accel_noise: 0.0,
+            rng: StdRng::from_entropy(),
+            temperature: DEFAULT_TEMPERATURE,
+            mode: 0,
+            data_rate: DEFAULT_DATA_RATE,
+        }
+    }
+
+    fn is_calibrating(&self) -> bool {
+        self.calibration_end
+            .is_some_and(|calibration_end| Instant::now() < calibration_end)
+    }
+
+    /// Samples a vector of gaussian noise with the given standard deviation.
+    fn noise(&mut self, std_dev: f64) -> Vector3<f64> {
+        let Ok(normal) = Normal::new(0.0, std_dev) else {
+            return Vector3::default();
+        };
+
+        Vector3 {
+            x: normal.sample(&mut self.rng),
+            y: normal.sample(&mut self.rng),
+            z: normal.sample(&mut self.rng),
+        }
+    }
+
+    /// Applies a command sent to the sensor by the brain.
+    fn apply(&mut self, command: ImuCommand) {
+        match command {
+            ImuCommand::Reset => self.calibration_end = Some(Instant::now() + CALIBRATION_TIME),
+            ImuCommand::SetMode(mode) => self.mode = mode,
+            ImuCommand::SetDataRate(rate) => {
+                self.data_rate = supported_data_rate(rate);
+            }
+        }
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    fn step(&mut self, dt: f64) {
+        let to_radians = |v: Vector3<f64>| Vector3 {
+            x: v.x.to_radians(),
+            y: v.y.to_radians(),
+            z: v.z.to_radians(),
+        };
+
+        self.orientation = self
+            .orientation
+            .mul(Quaternion::from_angular_velocity(
+                to_radians(self.angular_velocity),
+                dt,
+            ))
+            .normalize();
+
+        let gyro_noise = self.noise(self.gyro_noise);
+        self.gyro = Vector3 {
+            x: self.angular_velocity.x + self.gyro_drift.x + gyro_noise.x,
+            y: self.angular_velocity.y + self.gyro_drift.y + gyro_noise.y,
+            z: self.angular_velocity.z + self.gyro_drift.z + gyro_noise.z,
+        };
+
+        // An accelerometer at rest measures 1G pointing away from the ground.
+        let gravity = self.orientation.conjugate().rotate(Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: 1.0,
+        });
+        let accel_noise = self.noise(self.accel_noise);
+        self.accel = Vector3 {
+            x: self.acceleration.x + gravity.x + accel_noise.x,
+            y: self.acceleration.y + gravity.y + accel_noise.y,
+            z: self.acceleration.z + gravity.z + accel_noise.z,
+        };
+
+        if self.is_calibrating() {
+            // Calibration levels the sensor using gravity and zeroes its heading.
+            let (roll, pitch, _) = self.orientation.to_euler();
+            self.estimate = Quaternion::from_euler(roll, pitch, 0.0);
+            self.rotation = 0.0;
+        } else {
+            let (_, _, last_yaw) = self.estimate.to_euler();
+            self.estimate = self
+                .estimate
+                .mul(Quaternion::from_angular_velocity(to_radians(self.gyro), dt))
+                .normalize();
+            let (_, _, yaw) = self.estimate.to_euler();
+
+            // Yaw is counterclockwise, while the sensor's rotation is clockwise.
+            self.rotation -= wrap_degrees((yaw - last_yaw) * 180.0 / PI);
+        }
+    }
+
+    fn data(&self) -> ImuData {
+        let (roll, pitch, yaw) = self.estimate.to_euler();
+
+        ImuData {
+            rotation: self.rotation,
+            heading: self.rotation.rem_euclid(360.0),
+            quaternion: ImuQuaternion {
+                x: self.estimate.x,
+                y: self.estimate.y,
+                z: self.estimate.z,
+                w: self.estimate.w,
+            },
+            attitude: ImuAttitude {
+                pitch: pitch.to_degrees(),
+                roll: roll.to_degrees(),
+                yaw: wrap_degrees(-yaw.to_degrees()),
+            },
+            gyro: self.gyro,
+            accel: self.accel,
+            temperature: self.temperature,
+            status: if self.is_calibrating() {
+                ImuStatus::CALIBRATING
+            } else {
+                ImuStatus::empty()
+            },
+            mode: self.mode,
+            data_rate: self.data_rate,
+        }
+    }
+}
+
+/// A simulated V5 Inertial Sensor.
+#[derive(Debug)]
+pub struct Imu {
+    task: AbortHandle,
+    model: Arc<Mutex<ImuModel>>,
+}
+
+impl Imu {
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let model = Arc::new(Mutex::new(ImuModel::new()));
+
+        Self {
+            model: model.clone(),
+            task: tokio::task::spawn(async move {
+                let mut next_update = time::Instant::now();
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            if let SmartPortCommand::Imu(command) = command {
+                                model.lock().await.apply(command);
+                            }
+                        }
+                        _ = sleep_until(next_update) => {
+                            let data = {
+                                let mut model = model.lock().await;
+                                let data_rate = Duration::from_millis(model.data_rate as u64);
+
+                                model.step(data_rate.as_secs_f64());
+                                next_update += data_rate;
+
+                                model.data()
+                            };
+
+                            port.send(SmartPortData::Imu(data), start.elapsed().as_millis() as u32)
+                                .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns the data that the sensor is currently reporting to the brain.
+    pub async fn data(&self) -> ImuData {
+        self.model.lock().await.data()
+    }
+
+    /// Sets the angular velocity of the sensor around its own axes (in deg/s).
+    ///
+    /// Following the right-hand rule, a positive velocity around the Z axis
+    /// turns the sensor counterclockwise and decreases its heading.
+    pub async fn set_angular_velocity(&mut self, velocity: Vector3<f64>) {
+        self.model.lock().await.angular_velocity = velocity;
+    }
+
+    /// Sets the acceleration of the sensor along its own axes, not including
+    /// gravity (in G).
+    pub async fn set_acceleration(&mut self, acceleration: Vector3<f64>) {
+        self.model.lock().await.acceleration = acceleration;
+    }
+
+    /// Sets a constant bias that is added to every gyroscope reading (in
+    /// deg/s), causing the sensor's reported orientation to drift over time.
+    pub async fn set_gyro_drift(&mut self, drift: Vector3<f64>) {
+        self.model.lock().await.gyro_drift = drift;
+    }
+
+    /// Sets the standard deviation of random noise added to every gyroscope
+    /// reading (in deg/s).
+    pub async fn set_gyro_noise(&mut self, std_dev: f64) {
+        self.model.lock().await.gyro_noise = std_dev;
+    }
+
+    /// Sets the standard deviation of random noise added to every
+    /// accelerometer reading (in G).
+    pub async fn set_accel_noise(&mut self, std_dev: f64) {
+        self.model.lock().await.accel_noise = std_dev;
+    }
+
+    /// Sets the temperature reported by the sensor (in degrees celsius).
+    pub async fn set_temperature(&mut self, temperature: f64) {
+        self.model.lock().await.temperature = temperature;
+    }
+}
+
+impl Drop for Imu {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
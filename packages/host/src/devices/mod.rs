@@ -1 +1,41 @@
+pub mod adi;
+pub mod ai_vision;
 pub mod distance_sensor;
+pub mod generic_serial;
+pub mod gps;
+pub mod imu;
+pub mod motor;
+pub mod optical_sensor;
+pub mod pneumatic;
+pub mod radio;
+pub mod rotation_sensor;
+pub mod vision;
+
+/// The fastest rate at which a smart sensor can produce readings (in ms).
+const MIN_DATA_RATE: u32 = 5;
+
+/// The rate at which a smart sensor produces readings until told otherwise
+/// (in ms).
+const DEFAULT_DATA_RATE: u32 = 10;
+
+/// Temperature reported by a smart sensor until told otherwise (in degrees
+/// celsius).
+const DEFAULT_TEMPERATURE: f64 = 25.0;
+
+/// Rounds a data rate requested by the brain (in ms) down to one that a smart
+/// sensor supports.
+///
+/// Sensors only support rates in multiples of [`MIN_DATA_RATE`].
+const fn supported_data_rate(rate: u32) -> u32 {
+    let rate = rate - rate % MIN_DATA_RATE;
+    if rate < MIN_DATA_RATE {
+        MIN_DATA_RATE
+    } else {
+        rate
+    }
+}
+
+/// Wraps an angle in degrees to the range [-180, 180).
+fn wrap_degrees(angle: f64) -> f64 {
+    (angle + 180.0).rem_euclid(360.0) - 180.0
+}
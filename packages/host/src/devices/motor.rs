@@ -0,0 +1,675 @@
+use std::{
+    f64::consts::TAU,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{sync::Mutex, task::AbortHandle, time::interval};
+use vex_v5_qemu_protocol::{
+    motor::{
+        MotorBrakeMode, MotorCommand, MotorControl, MotorData, MotorEncoderUnits, MotorFaults,
+        MotorFlags, MotorGearset, TICKS_PER_ARMATURE_REVOLUTION,
+    },
+    SmartPortCommand, SmartPortData,
+};
+
+use crate::peripherals::smartport::SmartPort;
+
+/// Voltage that the motor's nominal free speed is rated at (in volts).
+const NOMINAL_VOLTAGE: f64 = 12.0;
+
+/// Free speed of the armature at [`NOMINAL_VOLTAGE`] (in rad/s).
+///
+/// Every cartridge is driven by the same 3600 RPM motor, which is why a 36:1
+/// cartridge tops out at 100 RPM and a 6:1 cartridge at 600 RPM.
+const ARMATURE_FREE_SPEED: f64 = 3600.0 / 60.0 * TAU;
+
+/// Back-EMF constant of the motor (in V per rad/s) and, equivalently, its
+/// torque constant (in Nm/A).
+const MOTOR_CONSTANT: f64 = NOMINAL_VOLTAGE / ARMATURE_FREE_SPEED;
+
+/// Resistance of the motor's windings (in ohms).
+///
+/// Chosen such that the motor's peak output power lands at roughly 11W after
+/// gearbox losses.
+const WINDING_RESISTANCE: f64 = 2.4;
+
+/// Fraction of the armature's torque that makes it through the cartridge.
+///
+/// This is what brings the 2.5A stall torque of a 36:1 cartridge down to the
+/// advertised 2.1Nm.
+const GEARBOX_EFFICIENCY: f64 = 0.735;
+
+/// Moment of inertia of the armature (in kg*m^2).
+const ARMATURE_INERTIA: f64 = 8.4e-6;
+
+/// Viscous friction of the armature and cartridge (in Nm per rad/s).
+const VISCOUS_FRICTION: f64 = 8.4e-6;
+
+/// Temperature of the air around the motor (in degrees celsius).
+const AMBIENT_TEMPERATURE: f64 = 25.0;
+
+/// Thermal resistance between the motor's windings and the air (in K/W).
+const THERMAL_RESISTANCE: f64 = 4.0;
+
+/// Heat capacity of the motor (in J/K).
+const THERMAL_CAPACITY: f64 = 20.0;
+
+/// Temperature at which the motor begins limiting its current to protect
+/// itself.
+const DERATE_TEMPERATURE: f64 = 55.0;
+
+/// Temperature at which the motor stops applying current entirely.
+const SHUTDOWN_TEMPERATURE: f64 = 70.0;
+
+/// Default current limit of a V5 Smart Motor (in mA).
+const DEFAULT_CURRENT_LIMIT: i32 = 2500;
+
+/// Default voltage limit of a V5 Smart Motor (in mV).
+const DEFAULT_VOLTAGE_LIMIT: i32 = 12000;
+
+/// Length of each physics step the simulation is broken up into.
+const PHYSICS_STEP: Duration = Duration::from_millis(1);
+
+/// Velocity controller gains, operating on armature velocity in rad/s and
+/// producing volts.
+const VELOCITY_KP: f64 = 0.05;
+const VELOCITY_KI: f64 = 0.5;
+
+/// Position controller gain, producing an output velocity (in RPM) per
+/// revolution of position error.
+const POSITION_KP: f64 = 400.0;
+
+/// Physical model of a V5 Smart Motor.
+///
+/// This simulates a brushed DC motor driven through a gear cartridge, along
+/// with the motor's onboard velocity and position controllers, its current
+/// limiter and a lumped thermal model of its windings.
+#[derive(Debug, Clone, PartialEq)]
+struct MotorModel {
+    /// Angle of the armature (in radians).
+    angle: f64,
+    /// Angular velocity of the armature (in rad/s).
+    angular_velocity: f64,
+    /// Current through the windings (in amps).
+    current: f64,
+    /// Voltage applied to the windings (in volts).
+    voltage: f64,
+    /// Temperature of the windings (in degrees celsius).
+    temperature: f64,
+    /// Whether the current limiter is clamping the motor's current.
+    current_limited: bool,
+
+    /// Offset applied to the reported encoder position (in ticks).
+    position_offset: f64,
+    /// Integral term of the velocity controller (in rad).
+    velocity_integral: f64,
+    /// Position that the motor holds when braking in [`MotorBrakeMode::Hold`]
+    /// (in reported encoder ticks).
+    hold_position: Option<f64>,
+
+    /// Frictional torque that the driven mechanism applies against the output
+    /// shaft (in Nm).
+    load_torque: f64,
+    /// Moment of inertia of the mechanism attached to the output shaft (in
+    /// kg*m^2).
+    load_inertia: f64,
+
+    gearset: MotorGearset,
+    brake_mode: MotorBrakeMode,
+    encoder_units: MotorEncoderUnits,
+    reversed: bool,
+    current_limit: i32,
+    voltage_limit: i32,
+    control: MotorControl,
+}
+
+impl MotorModel {
+    const fn new() -> Self {
+        Self {
+            angle: 0.0,
+            angular_velocity: 0.0,
+            current: 0.0,
+            voltage: 0.0,
+            temperature: AMBIENT_TEMPERATURE,
+            current_limited: false,
+            position_offset: 0.0,
+            velocity_integral: 0.0,
+            hold_position: None,
+            load_torque: 0.0,
+            load_inertia: 0.0,
+            gearset: MotorGearset::Ratio18,
+            brake_mode: MotorBrakeMode::Coast,
+            encoder_units: MotorEncoderUnits::Degrees,
+            reversed: false,
+            current_limit: DEFAULT_CURRENT_LIMIT,
+            voltage_limit: DEFAULT_VOLTAGE_LIMIT,
+            control: MotorControl::Brake,
+        }
+    }
+
+    /// Returns `-1.0` if the motor is reversed, otherwise `1.0`.
+    const fn direction(&self) -> f64 {
+        if self.reversed {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Position of the output shaft as seen by the user (in ticks).
+    fn position(&self) -> f64 {
+        self.direction() * self.angle / TAU * TICKS_PER_ARMATURE_REVOLUTION as f64
+            + self.position_offset
+    }
+
+    /// Velocity of the output shaft as seen by the user (in RPM).
+    fn velocity(&self) -> f64 {
+        self.direction() * self.angular_velocity / TAU * 60.0 / self.gearset.ratio()
+    }
+
+    fn set_position(&mut self, position: f64) {
+        self.position_offset += position - self.position();
+        self.hold_position = None;
+    }
+
+    fn set_control(&mut self, control: MotorControl) {
+        if control != self.control {
+            self.velocity_integral = 0.0;
+            self.hold_position = None;
+        }
+
+        self.control = control;
+    }
+
+    /// Converts a position in the motor's encoder units into ticks.
+    const fn to_ticks(&self, position: f64) -> f64 {
+        self.encoder_units.to_ticks(position, self.gearset)
+    }
+
+    /// Returns the velocity (in RPM) that the motor is currently targeting.
+    const fn target_velocity(&self) -> i32 {
+        match self.control {
+            MotorControl::Velocity(velocity) | MotorControl::Position { velocity, .. } => velocity,
+            _ => self.gearset.max_velocity(),
+        }
+    }
+
+    /// Applies a command sent to the motor by the brain.
+    fn apply(&mut self, command: MotorCommand) {
+        match command {
+            MotorCommand::Stop => self.set_control(MotorControl::Brake),
+            MotorCommand::SetVoltage(voltage) => self.set_control(MotorControl::Voltage(voltage)),
+            MotorCommand::SetVelocity(velocity) => {
+                self.set_control(MotorControl::Velocity(velocity));
+            }
+            MotorCommand::UpdateVelocity(velocity) => match self.control {
+                MotorControl::Position { target, .. } => {
+                    self.set_control(MotorControl::Position { target, velocity });
+                }
+                _ => self.set_control(MotorControl::Velocity(velocity)),
+            },
+            MotorCommand::SetAbsoluteTarget { position, velocity } => {
+                self.set_control(MotorControl::Position {
+                    target: self.to_ticks(position),
+                    velocity,
+                });
+            }
+            MotorCommand::SetRelativeTarget { position, velocity } => {
+                self.set_control(MotorControl::Position {
+                    target: self.position() + self.to_ticks(position),
+                    velocity,
+                });
+            }
+            MotorCommand::SetServoTarget(position) => {
+                self.set_control(MotorControl::Position {
+                    target: self.to_ticks(position),
+                    velocity: self.target_velocity(),
+                });
+            }
+            MotorCommand::SetPosition(position) => self.set_position(self.to_ticks(position)),
+            MotorCommand::SetReversed(reversed) => self.reversed = reversed,
+            MotorCommand::SetGearset(gearset) => self.gearset = gearset,
+            MotorCommand::SetBrakeMode(brake_mode) => self.brake_mode = brake_mode,
+            MotorCommand::SetEncoderUnits(encoder_units) => self.encoder_units = encoder_units,
+            MotorCommand::SetCurrentLimit(limit) => self.current_limit = limit,
+            MotorCommand::SetVoltageLimit(limit) => self.voltage_limit = limit,
+        }
+    }
+
+    /// Returns the control mode that the motor is effectively running in.
+    ///
+    /// Like on a real motor, commanding zero voltage or zero velocity stops
+    /// the motor using its brake mode.
+    const fn effective_control(&self) -> MotorControl {
+        match self.control {
+            MotorControl::Voltage(0) | MotorControl::Velocity(0) => MotorControl::Brake,
+            control => control,
+        }
+    }
+
+    /// Computes the voltage (in volts, user direction) that the velocity
+    /// controller applies to reach an output velocity (in RPM).
+    fn velocity_controller(&mut self, target: f64, dt: f64) -> f64 {
+        let target = target / 60.0 * TAU * self.gearset.ratio();
+        let actual = self.direction() * self.angular_velocity;
+        let error = target - actual;
+
+        self.velocity_integral = (self.velocity_integral + error * dt).clamp(
+            -NOMINAL_VOLTAGE / VELOCITY_KI,
+            NOMINAL_VOLTAGE / VELOCITY_KI,
+        );
+
+        target * MOTOR_CONSTANT + error * VELOCITY_KP + self.velocity_integral * VELOCITY_KI
+    }
+
+    /// Computes the voltage (in volts, user direction) that the position
+    /// controller applies to reach a position (in ticks) without exceeding a
+    /// velocity (in RPM).
+    fn position_controller(&mut self, target: f64, max_velocity: f64, dt: f64) -> f64 {
+        let error = (target - self.position()) / self.gearset.ticks_per_revolution();
+        let velocity = (error * POSITION_KP).clamp(-max_velocity, max_velocity);
+
+        self.velocity_controller(velocity, dt)
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    fn step(&mut self, dt: f64) {
+        let voltage_limit = self.voltage_limit.max(0) as f64 / 1000.0;
+        let max_velocity = self.gearset.max_velocity() as f64;
+
+        // Work out what the motor's controller wants to apply to the windings. `None`
+        // means that the windings are left disconnected (coasting).
+        let voltage = match self.effective_control() {
+            MotorControl::Voltage(voltage) => Some(voltage as f64 / 1000.0),
+            MotorControl::Velocity(velocity) => {
+                let velocity = (velocity as f64).clamp(-max_velocity, max_velocity);
+                Some(self.velocity_controller(velocity, dt))
+            }
+            MotorControl::Position { target, velocity } => {
+                let velocity = (velocity.unsigned_abs() as f64).min(max_velocity);
+                Some(self.position_controller(target, velocity, dt))
+            }
+            MotorControl::Brake => match self.brake_mode {
+                MotorBrakeMode::Coast => None,
+                MotorBrakeMode::Brake => Some(0.0),
+                MotorBrakeMode::Hold => {
+                    let position = self.position();
+                    let target = *self.hold_position.get_or_insert(position);
+                    Some(self.position_controller(target, max_velocity, dt))
+                }
+            },
+        }
+        .map(|voltage| self.direction() * voltage.clamp(-voltage_limit, voltage_limit));
+
+        // Thermal protection kicks in by scaling back the current limit.
+        let current_limit = self.current_limit.max(0) as f64 / 1000.0
+            * ((SHUTDOWN_TEMPERATURE - self.temperature)
+                / (SHUTDOWN_TEMPERATURE - DERATE_TEMPERATURE))
+                .clamp(0.0, 1.0);
+
+        let back_emf = MOTOR_CONSTANT * self.angular_velocity;
+        let current = voltage.map_or(0.0, |voltage| (voltage - back_emf) / WINDING_RESISTANCE);
+
+        // The current limiter works by backing off the voltage applied to the windings,
+        // but it can never apply more than the supply voltage. A motor being
+        // back-driven faster than its free speed will therefore regenerate past
+        // the limit.
+        self.current_limited = current.abs() > current_limit;
+        if voltage.is_some() {
+            self.voltage = (current.clamp(-current_limit, current_limit) * WINDING_RESISTANCE
+                + back_emf)
+                .clamp(-NOMINAL_VOLTAGE, NOMINAL_VOLTAGE);
+            self.current = (self.voltage - back_emf) / WINDING_RESISTANCE;
+        } else {
+            self.voltage = 0.0;
+            self.current = 0.0;
+        }
+
+        // Integrate the armature's motion.
+        let ratio = self.gearset.ratio();
+        let inertia = ARMATURE_INERTIA + self.load_inertia / (ratio * ratio);
+        let load_torque = self.load_torque.abs() / ratio / GEARBOX_EFFICIENCY;
+        let torque = MOTOR_CONSTANT * self.current - VISCOUS_FRICTION * self.angular_velocity;
+
+        // The load behaves like friction: it opposes motion, and holds the shaft still
+        // if the motor can't overcome it.
+        let direction = if self.angular_velocity != 0.0 {
+            self.angular_velocity.signum()
+        } else {
+            torque.signum()
+        };
+        let angular_velocity =
+            self.angular_velocity + (torque - direction * load_torque) / inertia * dt;
+
+        self.angular_velocity = if angular_velocity.signum() != direction
+            && (self.angular_velocity == 0.0 || torque.abs() <= load_torque)
+        {
+            0.0
+        } else {
+            angular_velocity
+        };
+        self.angle += self.angular_velocity * dt;
+
+        // Integrate heat generated in the windings against heat lost to the air.
+        let heat = self.current * self.current * WINDING_RESISTANCE;
+        let cooling = (self.temperature - AMBIENT_TEMPERATURE) / THERMAL_RESISTANCE;
+        self.temperature += (heat - cooling) / THERMAL_CAPACITY * dt;
+    }
+
+    fn data(&self) -> MotorData {
+        let torque = MOTOR_CONSTANT * self.current * self.gearset.ratio() * GEARBOX_EFFICIENCY;
+        let power = torque * self.angular_velocity / self.gearset.ratio();
+        let electrical_power = self.voltage * self.current;
+        let velocity = self.velocity();
+        let position = self.position().round() as i32;
+
+        let mut flags = MotorFlags::empty();
+        if velocity.abs() < 1.0 {
+            flags |= MotorFlags::ZERO_VELOCITY;
+        }
+        if position == 0 {
+            flags |= MotorFlags::ZERO_POSITION;
+        }
+
+        let mut faults = MotorFaults::empty();
+        if self.temperature >= DERATE_TEMPERATURE {
+            faults |= MotorFaults::OVER_TEMPERATURE;
+        }
+        if self.current_limited {
+            faults |= MotorFaults::OVER_CURRENT;
+        }
+
+        MotorData {
+            velocity,
+            position,
+            current: (self.current.abs() * 1000.0) as i32,
+            voltage: (self.direction() * self.voltage * 1000.0) as i32,
+            power: power.max(0.0),
+            torque: torque.abs(),
+            efficiency: if electrical_power > 0.0 && power > 0.0 {
+                (power / electrical_power * 100.0).min(100.0)
+            } else {
+                0.0
+            },
+            // The motor's temperature sensor only reports in increments of 5 degrees.
+            temperature: (self.temperature / 5.0).floor() * 5.0,
+            flags,
+            faults,
+            gearset: self.gearset,
+            brake_mode: self.brake_mode,
+            encoder_units: self.encoder_units,
+            reversed: self.reversed,
+            current_limit: self.current_limit,
+            voltage_limit: self.voltage_limit,
+            control: self.control,
+        }
+    }
+}
+
+/// A simulated V5 Smart Motor.
+#[derive(Debug)]
+pub struct Motor {
+    task: AbortHandle,
+    model: Arc<Mutex<MotorModel>>,
+}
+
+impl Motor {
+    pub const UPDATE_INTERVAL: Duration = Duration::from_millis(10);
+
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let model = Arc::new(Mutex::new(MotorModel::new()));
+
+        Self {
+            model: model.clone(),
+            task: tokio::task::spawn(async move {
+                let mut last_update = Instant::now();
+                let mut update_interval = interval(Self::UPDATE_INTERVAL);
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            if let SmartPortCommand::Motor(command) = command {
+                                model.lock().await.apply(command);
+                            }
+                        }
+                        _ = update_interval.tick() => {
+                            let data = {
+                                let mut model = model.lock().await;
+
+                                // Catch the simulation up to the current time in fixed-size
+                                // steps, so that the integration stays stable even if this
+                                // task gets delayed.
+                                let mut elapsed = last_update.elapsed();
+                                while elapsed >= PHYSICS_STEP {
+                                    model.step(PHYSICS_STEP.as_secs_f64());
+                                    elapsed -= PHYSICS_STEP;
+                                    last_update += PHYSICS_STEP;
+                                }
+
+                                model.data()
+                            };
+
+                            port.send(
+                                SmartPortData::Motor(data),
+                                start.elapsed().as_millis() as u32,
+                            )
+                            .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns the data that the motor is currently reporting to the brain.
+    pub async fn data(&self) -> MotorData {
+        self.model.lock().await.data()
+    }
+
+    /// Sets what the motor's internal controller should be doing.
+    pub async fn set_control(&mut self, control: MotorControl) {
+        self.model.lock().await.set_control(control);
+    }
+
+    pub async fn set_gearset(&mut self, gearset: MotorGearset) {
+        self.model.lock().await.gearset = gearset;
+    }
+
+    pub async fn set_brake_mode(&mut self, brake_mode: MotorBrakeMode) {
+        self.model.lock().await.brake_mode = brake_mode;
+    }
+
+    pub async fn set_encoder_units(&mut self, encoder_units: MotorEncoderUnits) {
+        self.model.lock().await.encoder_units = encoder_units;
+    }
+
+    pub async fn set_reversed(&mut self, reversed: bool) {
+        self.model.lock().await.reversed = reversed;
+    }
+
+    /// Sets the motor's current limit (in mA).
+    pub async fn set_current_limit(&mut self, limit: i32) {
+        self.model.lock().await.current_limit = limit;
+    }
+
+    /// Sets the motor's voltage limit (in mV).
+    pub async fn set_voltage_limit(&mut self, limit: i32) {
+        self.model.lock().await.voltage_limit = limit;
+    }
+
+    /// Sets the reported position of the motor (in raw encoder ticks).
+    pub async fn set_position(&mut self, position: f64) {
+        self.model.lock().await.set_position(position);
+    }
+
+    /// Sets the frictional torque that the driven mechanism applies against
+    /// the output shaft (in Nm).
+    ///
+    /// This torque always opposes the motor's motion, and will stall the motor
+    /// if it is greater than what the motor can produce.
+    pub async fn set_load_torque(&mut self, torque: f64) {
+        self.model.lock().await.load_torque = torque;
+    }
+
+    /// Sets the moment of inertia of the mechanism attached to the output
+    /// shaft (in kg*m^2).
+    pub async fn set_load_inertia(&mut self, inertia: f64) {
+        self.model.lock().await.load_inertia = inertia;
+    }
+}
+
+impl Drop for Motor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the model for `seconds` using the same step size as the device.
+    fn run(model: &mut MotorModel, seconds: f64) {
+        let dt = PHYSICS_STEP.as_secs_f64();
+        for _ in 0..(seconds / dt).round() as usize {
+            model.step(dt);
+        }
+    }
+
+    #[test]
+    fn reaches_free_speed_at_full_voltage() {
+        let mut model = MotorModel::new();
+        model.apply(MotorCommand::SetVoltage(12000));
+        run(&mut model, 2.0);
+
+        // Friction keeps the motor just shy of its rated 200 RPM.
+        let velocity = model.data().velocity;
+        assert!((190.0..200.0).contains(&velocity), "{velocity}");
+    }
+
+    #[test]
+    fn speed_scales_with_voltage() {
+        let mut model = MotorModel::new();
+        model.apply(MotorCommand::SetVoltage(6000));
+        run(&mut model, 2.0);
+
+        let velocity = model.data().velocity;
+        assert!((95.0..100.0).contains(&velocity), "{velocity}");
+
+        model.apply(MotorCommand::SetReversed(true));
+        run(&mut model, 2.0);
+        assert!((model.data().velocity - velocity).abs() < 1.0);
+    }
+
+    #[test]
+    fn velocity_control_reaches_target() {
+        let mut model = MotorModel::new();
+        model.apply(MotorCommand::SetVelocity(-120));
+        run(&mut model, 2.0);
+
+        let velocity = model.data().velocity;
+        assert!((velocity + 120.0).abs() < 2.0, "{velocity}");
+    }
+
+    #[test]
+    fn current_is_limited_when_stalled() {
+        let mut model = MotorModel::new();
+        model.load_torque = 10.0;
+        model.apply(MotorCommand::SetCurrentLimit(1000));
+        model.apply(MotorCommand::SetVoltage(12000));
+        run(&mut model, 0.5);
+
+        let data = model.data();
+        assert_eq!(data.velocity, 0.0);
+        assert!(data.current <= 1000, "{}", data.current);
+        assert!(data.current >= 990, "{}", data.current);
+        assert!(data.faults.contains(MotorFaults::OVER_CURRENT));
+    }
+
+    #[test]
+    fn overcomes_load_within_limits() {
+        let mut model = MotorModel::new();
+        model.load_torque = 0.1;
+        model.apply(MotorCommand::SetVoltage(12000));
+        run(&mut model, 2.0);
+
+        let data = model.data();
+        assert!(data.velocity > 100.0, "{}", data.velocity);
+        assert!(!data.faults.contains(MotorFaults::OVER_CURRENT));
+    }
+
+    /// Spins the motor up, stops it with `brake_mode` and returns its velocity
+    /// shortly after.
+    fn velocity_after_stopping(brake_mode: MotorBrakeMode) -> f64 {
+        let mut model = MotorModel::new();
+        model.apply(MotorCommand::SetBrakeMode(brake_mode));
+        model.apply(MotorCommand::SetVoltage(12000));
+        run(&mut model, 2.0);
+
+        model.apply(MotorCommand::Stop);
+        run(&mut model, 0.2);
+        model.data().velocity
+    }
+
+    #[test]
+    fn brake_stops_faster_than_coast() {
+        let coast = velocity_after_stopping(MotorBrakeMode::Coast);
+        let brake = velocity_after_stopping(MotorBrakeMode::Brake);
+
+        assert!(coast > 150.0, "{coast}");
+        assert!(brake.abs() < 1.0, "{brake}");
+    }
+
+    #[test]
+    fn hold_returns_to_stopping_position() {
+        let mut model = MotorModel::new();
+        model.apply(MotorCommand::SetBrakeMode(MotorBrakeMode::Hold));
+        model.apply(MotorCommand::SetVoltage(12000));
+        run(&mut model, 1.0);
+
+        model.apply(MotorCommand::Stop);
+        model.step(PHYSICS_STEP.as_secs_f64());
+        let hold_position = model.hold_position.unwrap();
+        run(&mut model, 1.0);
+
+        assert!((model.position() - hold_position).abs() < 5.0);
+        assert!(model.data().velocity.abs() < 1.0);
+    }
+
+    #[test]
+    fn hold_moves_with_the_stopping_position() {
+        let mut model = MotorModel::new();
+        model.apply(MotorCommand::SetBrakeMode(MotorBrakeMode::Hold));
+        model.apply(MotorCommand::SetVoltage(12000));
+        run(&mut model, 1.0);
+        model.apply(MotorCommand::SetVoltage(0));
+        run(&mut model, 1.0);
+        let first_position = model.position();
+
+        model.apply(MotorCommand::SetVoltage(12000));
+        run(&mut model, 1.0);
+        model.apply(MotorCommand::SetVoltage(0));
+        model.step(PHYSICS_STEP.as_secs_f64());
+        let hold_position = model.hold_position.unwrap();
+        run(&mut model, 1.0);
+
+        assert!(hold_position - first_position > 1000.0);
+        assert!((model.position() - hold_position).abs() < 5.0);
+        assert!(model.data().velocity.abs() < 1.0);
+    }
+
+    #[test]
+    fn zero_voltage_uses_brake_mode() {
+        let mut model = MotorModel::new();
+        model.apply(MotorCommand::SetBrakeMode(MotorBrakeMode::Brake));
+        model.apply(MotorCommand::SetVoltage(12000));
+        run(&mut model, 1.0);
+
+        model.apply(MotorCommand::SetVoltage(0));
+        run(&mut model, 0.2);
+        assert!(model.data().velocity.abs() < 1.0);
+    }
+}
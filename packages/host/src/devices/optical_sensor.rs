@@ -0,0 +1,272 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    sync::Mutex,
+    task::AbortHandle,
+    time::{self, sleep, sleep_until},
+};
+use vex_v5_qemu_protocol::{
+    optical_sensor::{
+        GestureDirection, OpticalGesture, OpticalRaw, OpticalRgb, OpticalSensorCommand,
+        OpticalSensorData,
+    },
+    SmartPortCommand, SmartPortData,
+};
+
+use crate::peripherals::smartport::SmartPort;
+
+/// Distance at which the sensor stops detecting objects (in mm).
+const PROXIMITY_RANGE: f64 = 100.0;
+
+/// Brightness of the sensor's surroundings when its LED is off.
+const AMBIENT_BRIGHTNESS: f64 = 0.05;
+
+/// The range of integration times supported by the sensor (in ms).
+const MIN_INTEGRATION_TIME: f64 = 3.0;
+const MAX_INTEGRATION_TIME: f64 = 712.0;
+
+/// The integration time used by the sensor until told otherwise (in ms).
+const DEFAULT_INTEGRATION_TIME: f64 = 100.0;
+
+/// A colored object held in front of an optical sensor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OpticalObject {
+    /// Hue of the object in degrees, bounded from 0 to 360.
+    pub hue: f64,
+    /// Saturation of the object, bounded from 0 to 1.
+    pub saturation: f64,
+    /// Distance from the sensor to the object (in mm).
+    pub distance: f64,
+}
+
+impl OpticalObject {
+    pub const fn new(hue: f64, saturation: f64, distance: f64) -> Self {
+        Self {
+            hue,
+            saturation,
+            distance,
+        }
+    }
+}
+
+/// Converts a color from HSV to RGB, with each channel bounded from 0 to 255.
+fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> (f64, f64, f64) {
+    let chroma = value * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+
+    ((r + m) * 255.0, (g + m) * 255.0, (b + m) * 255.0)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct OpticalSensorModel {
+    start: Instant,
+    object: Option<OpticalObject>,
+
+    led_pwm: i32,
+    mode: u32,
+    proximity_threshold: i32,
+    integration_time: f64,
+
+    gestures_enabled: bool,
+    gesture: Option<OpticalGesture>,
+    gesture_count: u16,
+}
+
+impl OpticalSensorModel {
+    const fn new(start: Instant) -> Self {
+        Self {
+            start,
+            object: None,
+            led_pwm: 0,
+            mode: 0,
+            proximity_threshold: 0,
+            integration_time: DEFAULT_INTEGRATION_TIME,
+            gestures_enabled: false,
+            gesture: None,
+            gesture_count: 0,
+        }
+    }
+
+    /// Applies a command sent to the sensor by the brain.
+    fn apply(&mut self, command: OpticalSensorCommand) {
+        match command {
+            OpticalSensorCommand::SetLedPwm(pwm) => self.led_pwm = pwm.clamp(0, 100),
+            OpticalSensorCommand::SetMode(mode) => self.mode = mode,
+            OpticalSensorCommand::SetGesturesEnabled(enabled) => self.gestures_enabled = enabled,
+            OpticalSensorCommand::SetProximityThreshold(threshold) => {
+                self.proximity_threshold = threshold;
+            }
+            // NaN can't be clamped to a valid time, so the sensor keeps its current
+            // one instead.
+            OpticalSensorCommand::SetIntegrationTime(time) if time.is_nan() => {}
+            OpticalSensorCommand::SetIntegrationTime(time) => {
+                self.integration_time = time.clamp(MIN_INTEGRATION_TIME, MAX_INTEGRATION_TIME);
+            }
+        }
+    }
+
+    /// Records a gesture, if the sensor is detecting them.
+    fn gesture(&mut self, direction: GestureDirection) {
+        if !self.gestures_enabled {
+            return;
+        }
+
+        self.gesture_count = self.gesture_count.wrapping_add(1);
+        self.gesture = Some(OpticalGesture {
+            direction,
+            count: self.gesture_count,
+            time: self.start.elapsed().as_millis() as u32,
+        });
+    }
+
+    /// How close the current object is to the sensor, from 0 (out of range)
+    /// to 1 (touching the sensor).
+    fn closeness(&self) -> f64 {
+        self.object.map_or(0.0, |object| {
+            (1.0 - object.distance / PROXIMITY_RANGE).clamp(0.0, 1.0)
+        })
+    }
+
+    fn data(&self) -> OpticalSensorData {
+        let closeness = self.closeness();
+
+        // Objects are lit by the sensor's LED, so they appear brighter the
+        // closer they are.
+        let (hue, saturation, brightness) = match self.object {
+            Some(object) if closeness > 0.0 => (
+                object.hue.rem_euclid(360.0),
+                object.saturation.clamp(0.0, 1.0),
+                (AMBIENT_BRIGHTNESS + self.led_pwm as f64 / 100.0 * closeness).min(1.0),
+            ),
+            _ => (0.0, 0.0, AMBIENT_BRIGHTNESS),
+        };
+        let (red, green, blue) = hsv_to_rgb(hue, saturation, brightness);
+
+        // Longer integration times let more light reach the photodiodes.
+        let exposure = self.integration_time / MAX_INTEGRATION_TIME;
+        let counts = |channel: f64| (channel / 255.0 * exposure * u16::MAX as f64) as u16;
+
+        OpticalSensorData {
+            hue,
+            saturation,
+            brightness,
+            proximity: (closeness * 255.0).round() as i32,
+            rgb: OpticalRgb {
+                red,
+                green,
+                blue,
+                brightness,
+            },
+            raw: OpticalRaw {
+                clear: counts((red + green + blue) / 3.0),
+                red: counts(red),
+                green: counts(green),
+                blue: counts(blue),
+            },
+            led_pwm: self.led_pwm,
+            status: 0,
+            mode: self.mode,
+            proximity_threshold: self.proximity_threshold,
+            integration_time: self.integration_time,
+            gestures_enabled: self.gestures_enabled,
+            gesture: self.gesture,
+        }
+    }
+}
+
+/// A simulated V5 Optical Sensor.
+///
+/// The sensor takes a new reading at the end of each integration period, so
+/// objects that pass by faster than its integration time may be missed.
+#[derive(Debug)]
+pub struct OpticalSensor {
+    task: AbortHandle,
+    model: Arc<Mutex<OpticalSensorModel>>,
+}
+
+impl OpticalSensor {
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let model = Arc::new(Mutex::new(OpticalSensorModel::new(start)));
+
+        Self {
+            model: model.clone(),
+            task: tokio::task::spawn(async move {
+                let mut next_update = time::Instant::now();
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            if let SmartPortCommand::OpticalSensor(command) = command {
+                                model.lock().await.apply(command);
+                            }
+                        }
+                        _ = sleep_until(next_update) => {
+                            let data = {
+                                let model = model.lock().await;
+                                let integration_time = model.integration_time / 1000.0;
+
+                                next_update += Duration::from_secs_f64(integration_time);
+
+                                model.data()
+                            };
+
+                            port.send(
+                                SmartPortData::OpticalSensor(data),
+                                start.elapsed().as_millis() as u32,
+                            )
+                            .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns the data that the sensor is currently reporting to the brain.
+    pub async fn data(&self) -> OpticalSensorData {
+        self.model.lock().await.data()
+    }
+
+    /// Places an object in front of the sensor, or removes it if `object` is
+    /// `None`.
+    pub async fn set_object(&mut self, object: Option<OpticalObject>) {
+        self.model.lock().await.object = object;
+    }
+
+    /// Passes an object in front of the sensor for `duration`, such as a game
+    /// element moving through an intake.
+    pub async fn pass_object(&mut self, object: OpticalObject, duration: Duration) {
+        self.set_object(Some(object)).await;
+        sleep(duration).await;
+        self.set_object(None).await;
+    }
+
+    /// Performs a gesture in front of the sensor.
+    ///
+    /// Gestures are ignored unless the brain has enabled gesture detection.
+    pub async fn gesture(&mut self, direction: GestureDirection) {
+        self.model.lock().await.gesture(direction);
+    }
+}
+
+impl Drop for OpticalSensor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
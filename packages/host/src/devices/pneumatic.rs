@@ -0,0 +1,257 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    sync::{broadcast, Mutex},
+    task::AbortHandle,
+    time::interval,
+};
+use vex_v5_qemu_protocol::{
+    pneumatic::{
+        PneumaticCommand, PneumaticCylinder, PneumaticData, PneumaticStatus,
+        PNEUMATIC_CYLINDER_COUNT,
+    },
+    SmartPortCommand, SmartPortData,
+};
+
+use crate::peripherals::smartport::SmartPort;
+
+/// How often the pneumatic system is simulated.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Atmospheric pressure (in PSI).
+const ATMOSPHERIC_PRESSURE: f64 = 14.7;
+
+/// Pressure at which the compressor shuts off (in PSI).
+const MAX_PRESSURE: f64 = 100.0;
+
+/// Volume of free air moved by the compressor each second at full duty cycle
+/// (in mL).
+const COMPRESSOR_FLOW_RATE: f64 = 8.0;
+
+/// Volume of the air tank until told otherwise (in mL).
+const DEFAULT_TANK_VOLUME: f64 = 200.0;
+
+/// Volume of each cylinder until told otherwise (in mL). This is roughly a
+/// cylinder with a 10mm bore and a 50mm stroke.
+const DEFAULT_CYLINDER_VOLUME: f64 = 4.0;
+
+/// Number of actuation events that can be buffered for each subscriber before
+/// older ones are dropped.
+const ACTUATION_BUFFER: usize = 64;
+
+/// A cylinder being extended or retracted by the brain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PneumaticActuation {
+    /// Zero-based index of the cylinder that was actuated.
+    pub cylinder: u8,
+    pub extended: bool,
+    /// Tank pressure left after the actuation (in PSI).
+    pub pressure: f64,
+    /// Number of times the cylinder has been actuated, including this one.
+    pub count: u16,
+}
+
+#[derive(Debug, Clone)]
+struct PneumaticModel {
+    /// Gauge pressure of the air tank (in PSI).
+    pressure: f64,
+    /// Volume of the air tank (in mL).
+    tank_volume: f64,
+    /// Volume of each cylinder (in mL).
+    cylinder_volumes: [f64; PNEUMATIC_CYLINDER_COUNT],
+
+    compressor_enabled: bool,
+    compressor_pwm: u8,
+    cylinders: [PneumaticCylinder; PNEUMATIC_CYLINDER_COUNT],
+
+    actuation_tx: broadcast::Sender<PneumaticActuation>,
+}
+
+impl PneumaticModel {
+    const fn new(actuation_tx: broadcast::Sender<PneumaticActuation>) -> Self {
+        Self {
+            pressure: MAX_PRESSURE,
+            tank_volume: DEFAULT_TANK_VOLUME,
+            cylinder_volumes: [DEFAULT_CYLINDER_VOLUME; PNEUMATIC_CYLINDER_COUNT],
+            compressor_enabled: false,
+            compressor_pwm: 100,
+            cylinders: [PneumaticCylinder {
+                extended: false,
+                pwm: 100,
+                actuations: 0,
+            }; PNEUMATIC_CYLINDER_COUNT],
+            actuation_tx,
+        }
+    }
+
+    fn compressor_running(&self) -> bool {
+        self.compressor_enabled && self.compressor_pwm > 0 && self.pressure < MAX_PRESSURE
+    }
+
+    /// Applies a command sent to the controller by the brain.
+    fn apply(&mut self, command: PneumaticCommand) {
+        match command {
+            PneumaticCommand::SetCompressor(enabled) => self.compressor_enabled = enabled,
+            PneumaticCommand::SetCompressorPwm(pwm) => self.compressor_pwm = pwm.min(100),
+            PneumaticCommand::SetCylinder { id, extended } => self.actuate(id, extended),
+            PneumaticCommand::SetCylinderPwm { id, pwm } => {
+                if let Some(cylinder) = self.cylinders.get_mut(id as usize) {
+                    cylinder.pwm = pwm.min(100);
+                }
+            }
+        }
+    }
+
+    /// Extends or retracts a cylinder, filling it with air from the tank.
+    fn actuate(&mut self, id: u8, extended: bool) {
+        let Some(cylinder) = self.cylinders.get_mut(id as usize) else {
+            return;
+        };
+        if cylinder.extended == extended {
+            return;
+        }
+
+        cylinder.extended = extended;
+        cylinder.actuations = cylinder.actuations.wrapping_add(1);
+
+        // The air in the tank expands into the cylinder (which starts out at
+        // atmospheric pressure) until the two are at the same pressure.
+        let cylinder_volume = self.cylinder_volumes[id as usize];
+        self.pressure *= self.tank_volume / (self.tank_volume + cylinder_volume);
+
+        // Nobody might be listening for actuations, which is fine.
+        _ = self.actuation_tx.send(PneumaticActuation {
+            cylinder: id,
+            extended,
+            pressure: self.pressure,
+            count: cylinder.actuations,
+        });
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    fn step(&mut self, dt: f64) {
+        if self.compressor_running() {
+            let flow = COMPRESSOR_FLOW_RATE * self.compressor_pwm as f64 / 100.0;
+            self.pressure = (self.pressure + ATMOSPHERIC_PRESSURE * flow * dt / self.tank_volume)
+                .min(MAX_PRESSURE);
+        }
+    }
+
+    fn data(&self) -> PneumaticData {
+        let mut status = PneumaticStatus::empty();
+        for (i, cylinder) in self.cylinders.iter().enumerate() {
+            if cylinder.extended {
+                status |= PneumaticStatus::from_bits_retain(1 << i);
+            }
+        }
+        if self.compressor_running() {
+            status |= PneumaticStatus::COMPRESSOR_RUNNING;
+        }
+
+        PneumaticData {
+            pressure: self.pressure,
+            compressor_enabled: self.compressor_enabled,
+            compressor_pwm: self.compressor_pwm,
+            cylinders: self.cylinders,
+            status,
+        }
+    }
+}
+
+/// A simulated CTE pneumatic controller, with an air tank, a compressor and up
+/// to four cylinders.
+///
+/// Cylinders are zero-indexed.
+///
+/// # Panics
+///
+/// Methods taking a cylinder index panic if it is not less than
+/// [`PNEUMATIC_CYLINDER_COUNT`].
+#[derive(Debug)]
+pub struct Pneumatics {
+    task: AbortHandle,
+    model: Arc<Mutex<PneumaticModel>>,
+    actuation_tx: broadcast::Sender<PneumaticActuation>,
+}
+
+impl Pneumatics {
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let (actuation_tx, _) = broadcast::channel(ACTUATION_BUFFER);
+        let model = Arc::new(Mutex::new(PneumaticModel::new(actuation_tx.clone())));
+
+        Self {
+            model: model.clone(),
+            actuation_tx,
+            task: tokio::task::spawn(async move {
+                let mut update_interval = interval(UPDATE_INTERVAL);
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            if let SmartPortCommand::Pneumatic(command) = command {
+                                model.lock().await.apply(command);
+                            }
+                        }
+                        _ = update_interval.tick() => {
+                            let data = {
+                                let mut model = model.lock().await;
+                                model.step(UPDATE_INTERVAL.as_secs_f64());
+                                model.data()
+                            };
+
+                            port.send(
+                                SmartPortData::Pneumatic(data),
+                                start.elapsed().as_millis() as u32,
+                            )
+                            .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns the data that the controller is currently reporting to the
+    /// brain.
+    pub async fn data(&self) -> PneumaticData {
+        self.model.lock().await.data()
+    }
+
+    /// Returns a receiver that is notified every time the brain extends or
+    /// retracts a cylinder.
+    pub fn subscribe(&self) -> broadcast::Receiver<PneumaticActuation> {
+        self.actuation_tx.subscribe()
+    }
+
+    /// Returns the gauge pressure of the air tank (in PSI).
+    pub async fn pressure(&self) -> f64 {
+        self.model.lock().await.pressure
+    }
+
+    /// Sets the gauge pressure of the air tank (in PSI), such as to pressurize
+    /// it before a match.
+    pub async fn set_pressure(&mut self, pressure: f64) {
+        self.model.lock().await.pressure = pressure.max(0.0);
+    }
+
+    /// Sets the volume of the air tank (in mL).
+    pub async fn set_tank_volume(&mut self, volume: f64) {
+        self.model.lock().await.tank_volume = volume;
+    }
+
+    /// Sets the volume of air that a cylinder takes to actuate once (in mL).
+    pub async fn set_cylinder_volume(&mut self, cylinder: u8, volume: f64) {
+        self.model.lock().await.cylinder_volumes[cylinder as usize] = volume;
+    }
+}
+
+impl Drop for Pneumatics {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
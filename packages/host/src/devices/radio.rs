@@ -0,0 +1,435 @@
+use std::{
+    collections::VecDeque,
+    io,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bincode::{Decode, Encode};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    sync::{
+        mpsc::{self, Receiver, Sender, UnboundedSender},
+        Mutex,
+    },
+    task::AbortHandle,
+    time::interval,
+};
+use vex_v5_qemu_protocol::{
+    radio::{RadioCommand, RadioData, RadioRole, RADIO_TX_BUFFER_SIZE},
+    SmartPortCommand, SmartPortData,
+};
+
+use crate::peripherals::smartport::SmartPort;
+
+/// How often the radio link is simulated.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Rate at which data can be sent over VEXlink (in bytes per second).
+const BANDWIDTH: f64 = 1040.0;
+
+/// Time it takes two radios to establish a link once both are looking for
+/// each other.
+const LINK_DELAY: Duration = Duration::from_millis(1500);
+
+/// Number of frames that can be in flight between two radios.
+const FRAME_BUFFER: usize = 64;
+
+/// The largest encoded frame that can be sent over a stream to another
+/// simulator (in bytes).
+///
+/// This is enough for a full transmit buffer of data or a long link ID, and
+/// stops a misbehaving peer from making us allocate arbitrarily large frames.
+const MAX_FRAME_SIZE: usize = 4 * RADIO_TX_BUFFER_SIZE;
+
+/// The link that a radio is looking for.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+struct LinkConfig {
+    link_id: String,
+    role: RadioRole,
+}
+
+/// A message sent over the air from one radio to another.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+enum Frame {
+    /// The link that the sending radio is looking for, or `None` if it hasn't
+    /// been put into VEXlink mode.
+    Config(Option<LinkConfig>),
+    /// Bytes transmitted by the sending radio.
+    Data(Vec<u8>),
+}
+
+/// One end of the connection between two radios that are in range of each
+/// other.
+#[derive(Debug)]
+struct Antenna {
+    tx: Sender<Frame>,
+    rx: Receiver<Frame>,
+}
+
+impl Antenna {
+    fn pair() -> (Self, Self) {
+        let (a_tx, a_rx) = mpsc::channel(FRAME_BUFFER);
+        let (b_tx, b_rx) = mpsc::channel(FRAME_BUFFER);
+
+        (Self { tx: a_tx, rx: b_rx }, Self { tx: b_tx, rx: a_rx })
+    }
+
+    /// Waits for the next frame from the other radio, or forever if there is
+    /// no other radio.
+    async fn recv(antenna: &mut Option<Self>) -> Option<Frame> {
+        match antenna {
+            Some(antenna) => antenna.rx.recv().await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+struct RadioModel {
+    config: Option<LinkConfig>,
+    peer_config: Option<LinkConfig>,
+    /// When this radio and its peer started looking for each other.
+    matched_at: Option<Instant>,
+    linked: bool,
+
+    /// Bytes transmitted by the brain that haven't been sent over the link.
+    tx_queue: VecDeque<u8>,
+    /// Number of bytes that the link has room to send but hasn't yet.
+    credit: f64,
+}
+
+impl RadioModel {
+    /// Returns whether this radio and its peer are looking for each other.
+    fn matches_peer(&self) -> bool {
+        match (&self.config, &self.peer_config) {
+            (Some(config), Some(peer)) => {
+                config.link_id == peer.link_id && config.role != peer.role
+            }
+            _ => false,
+        }
+    }
+
+    /// Establishes or drops the link depending on what both radios are looking
+    /// for.
+    fn update_link(&mut self, now: Instant) {
+        if self.matches_peer() {
+            let matched_at = *self.matched_at.get_or_insert(now);
+            self.linked = now.duration_since(matched_at) >= LINK_DELAY;
+        } else {
+            self.matched_at = None;
+            self.linked = false;
+        }
+
+        if !self.linked {
+            self.tx_queue.clear();
+            self.credit = 0.0;
+        }
+    }
+
+    /// Queues bytes to be sent over the link, dropping any that don't fit in
+    /// the radio's buffer.
+    fn transmit(&mut self, data: Vec<u8>) {
+        if self.linked {
+            let len = data.len().min(self.write_free());
+            self.tx_queue.extend(&data[..len]);
+        }
+    }
+
+    /// Takes as many bytes from the queue as the link can send in `dt`
+    /// seconds.
+    fn take_outgoing(&mut self, dt: f64) -> Option<Vec<u8>> {
+        self.credit += BANDWIDTH * dt;
+
+        let len = (self.credit as usize).min(self.tx_queue.len());
+        self.credit -= len as f64;
+
+        // Time spent with nothing to send can't be saved up for a burst later.
+        if self.tx_queue.len() == len {
+            self.credit = 0.0;
+        }
+
+        (len > 0).then(|| self.tx_queue.drain(..len).collect())
+    }
+
+    fn write_free(&self) -> usize {
+        if self.linked {
+            RADIO_TX_BUFFER_SIZE - self.tx_queue.len()
+        } else {
+            0
+        }
+    }
+
+    fn data(&self) -> RadioData {
+        RadioData {
+            linked: self.linked,
+            write_free: self.write_free() as u16,
+        }
+    }
+}
+
+/// A simulated V5 radio in VEXlink mode.
+///
+/// A radio can only link with another radio that it has been paired with,
+/// either in the same process with [`Radio::pair`] or over a byte stream with
+/// [`Radio::pair_with_stream`]. Once paired, the two radios establish a link
+/// after a short delay if the brains have given them the same link ID and one
+/// is the manager while the other is a worker. Data is then sent over the link
+/// no faster than the real radio's bandwidth allows.
+#[derive(Debug)]
+pub struct Radio {
+    task: AbortHandle,
+    model: Arc<Mutex<RadioModel>>,
+    antenna_tx: UnboundedSender<Antenna>,
+}
+
+impl Radio {
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let model = Arc::new(Mutex::new(RadioModel::default()));
+        let (antenna_tx, mut antenna_rx) = mpsc::unbounded_channel::<Antenna>();
+
+        Self {
+            model: model.clone(),
+            antenna_tx,
+            task: tokio::task::spawn(async move {
+                let mut update_interval = interval(UPDATE_INTERVAL);
+                let mut antenna: Option<Antenna> = None;
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            let SmartPortCommand::Radio(command) = command else {
+                                continue;
+                            };
+
+                            match command {
+                                RadioCommand::Connect { link_id, role } => {
+                                    let config = Some(LinkConfig { link_id, role });
+                                    model.lock().await.config.clone_from(&config);
+
+                                    if let Some(antenna) = &antenna {
+                                        _ = antenna.tx.send(Frame::Config(config)).await;
+                                    }
+                                }
+                                RadioCommand::Transmit(data) => {
+                                    model.lock().await.transmit(data);
+                                }
+                            }
+                        }
+                        Some(new_antenna) = antenna_rx.recv() => {
+                            let config = {
+                                let mut model = model.lock().await;
+                                model.peer_config = None;
+                                model.config.clone()
+                            };
+
+                            // Let the new peer know what we're looking for, in case the
+                            // brain configured us before we were paired.
+                            _ = new_antenna.tx.send(Frame::Config(config)).await;
+                            antenna = Some(new_antenna);
+                        }
+                        frame = Antenna::recv(&mut antenna) => match frame {
+                            Some(Frame::Config(config)) => {
+                                model.lock().await.peer_config = config;
+                            }
+                            Some(Frame::Data(data)) => {
+                                // Anything the peer sent before we considered the link
+                                // established is lost.
+                                if model.lock().await.linked {
+                                    port.send_radio(data).await;
+                                }
+                            }
+                            // The peer has gone away.
+                            None => {
+                                antenna = None;
+                                model.lock().await.peer_config = None;
+                            }
+                        },
+                        _ = update_interval.tick() => {
+                            let (data, outgoing) = {
+                                let mut model = model.lock().await;
+                                model.update_link(Instant::now());
+                                let outgoing = model.take_outgoing(UPDATE_INTERVAL.as_secs_f64());
+                                (model.data(), outgoing)
+                            };
+
+                            if let (Some(antenna), Some(outgoing)) = (&antenna, outgoing) {
+                                _ = antenna.tx.send(Frame::Data(outgoing)).await;
+                            }
+
+                            port.send(
+                                SmartPortData::Radio(data),
+                                start.elapsed().as_millis() as u32,
+                            )
+                            .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Puts this radio in range of `other`, so that the two can establish a
+    /// link.
+    ///
+    /// This replaces any radio that either of them was previously paired with.
+    pub fn pair(&mut self, other: &mut Radio) {
+        let (ours, theirs) = Antenna::pair();
+
+        // The receivers are owned by tasks that live as long as the radios.
+        _ = self.antenna_tx.send(ours);
+        _ = other.antenna_tx.send(theirs);
+    }
+
+    /// Puts this radio in range of a radio on the other end of `stream`, such
+    /// as a socket connected to another simulated brain.
+    ///
+    /// The other end must also be a radio paired with this method. This
+    /// replaces any radio that this one was previously paired with, and the
+    /// radios are unpaired when the stream is closed.
+    pub fn pair_with_stream<S>(&mut self, stream: S)
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (ours, theirs) = Antenna::pair();
+
+        _ = self.antenna_tx.send(ours);
+        tokio::task::spawn(async move {
+            if let Err(err) = bridge_stream(theirs, stream).await {
+                log::warn!("Radio link stream closed: {err}");
+            }
+        });
+    }
+
+    /// Returns whether a link has been established with another radio.
+    pub async fn linked(&self) -> bool {
+        self.model.lock().await.linked
+    }
+
+    /// Returns the link ID that the brain has configured the radio with, if it
+    /// has put the radio into VEXlink mode.
+    pub async fn link_id(&self) -> Option<String> {
+        self.model
+            .lock()
+            .await
+            .config
+            .as_ref()
+            .map(|config| config.link_id.clone())
+    }
+
+    /// Returns which end of the link the brain has configured the radio to be,
+    /// if it has put the radio into VEXlink mode.
+    pub async fn role(&self) -> Option<RadioRole> {
+        self.model
+            .lock()
+            .await
+            .config
+            .as_ref()
+            .map(|config| config.role)
+    }
+}
+
+impl Drop for Radio {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Forwards frames between `antenna` and `stream` until either side closes.
+///
+/// Frames are encoded in the same length-prefixed format as the packets sent
+/// to and from the kernel. The link is closed with an error if a frame larger
+/// than [`MAX_FRAME_SIZE`] is sent or received.
+async fn bridge_stream<S>(antenna: Antenna, stream: S) -> io::Result<()>
+where
+    S: AsyncRead + AsyncWrite,
+{
+    let Antenna { tx, mut rx } = antenna;
+    let (mut read, mut write) = tokio::io::split(stream);
+
+    let outgoing = async {
+        while let Some(frame) = rx.recv().await {
+            let encoded = bincode::encode_to_vec(frame, bincode::config::standard())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            if encoded.len() > MAX_FRAME_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "frame is too large to send",
+                ));
+            }
+
+            write
+                .write_all(&(encoded.len() as u32).to_le_bytes())
+                .await?;
+            write.write_all(&encoded).await?;
+        }
+        Ok(())
+    };
+
+    let incoming = async {
+        loop {
+            let frame_size = read.read_u32_le().await? as usize;
+            if frame_size > MAX_FRAME_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("peer sent a {frame_size} byte frame"),
+                ));
+            }
+
+            let mut buf = vec![0u8; frame_size];
+            read.read_exact(&mut buf).await?;
+
+            let (frame, _) = bincode::decode_from_slice(&buf, bincode::config::standard())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+            if tx.send(frame).await.is_err() {
+                return Ok(()); // the radio has been paired with something else
+            }
+        }
+    };
+
+    tokio::select! {
+        result = outgoing => result,
+        result = incoming => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn frames_cross_the_stream() {
+        let (mut radio_a, bridge_a) = Antenna::pair();
+        let (mut radio_b, bridge_b) = Antenna::pair();
+        let (stream_a, stream_b) = tokio::io::duplex(64);
+        tokio::spawn(bridge_stream(bridge_a, stream_a));
+        tokio::spawn(bridge_stream(bridge_b, stream_b));
+
+        let frame = Frame::Data(vec![1, 2, 3]);
+        radio_a.tx.send(frame.clone()).await.unwrap();
+        assert_eq!(radio_b.rx.recv().await, Some(frame));
+
+        let frame = Frame::Config(Some(LinkConfig {
+            link_id: "link".to_string(),
+            role: RadioRole::Manager,
+        }));
+        radio_b.tx.send(frame.clone()).await.unwrap();
+        assert_eq!(radio_a.rx.recv().await, Some(frame));
+    }
+
+    #[tokio::test]
+    async fn oversized_frames_close_the_link() {
+        let (mut ours, theirs) = Antenna::pair();
+        let (stream, mut peer) = tokio::io::duplex(64);
+        let bridge = tokio::spawn(bridge_stream(theirs, stream));
+
+        peer.write_all(&u32::MAX.to_le_bytes()).await.unwrap();
+
+        let err = bridge.await.unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(ours.rx.recv().await, None);
+    }
+}
@@ -0,0 +1,187 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    sync::Mutex,
+    task::AbortHandle,
+    time::{self, sleep_until},
+};
+use vex_v5_qemu_protocol::{
+    rotation_sensor::{RotationSensorCommand, RotationSensorData},
+    SmartPortCommand, SmartPortData,
+};
+
+use super::{supported_data_rate, DEFAULT_DATA_RATE, DEFAULT_TEMPERATURE};
+use crate::peripherals::smartport::SmartPort;
+
+/// Physical model of a V5 Rotation Sensor.
+///
+/// The sensor's shaft can be turned directly or spun at a constant velocity.
+/// Its reported position accumulates every change in the shaft's angle, so
+/// reversing the sensor or setting its position never makes it jump.
+#[derive(Debug, Clone, PartialEq)]
+struct RotationSensorModel {
+    /// Unbounded angle of the sensor's shaft (in degrees).
+    shaft: f64,
+    /// Angle of the shaft at the time of the last reading (in degrees).
+    last_shaft: f64,
+    /// Angular velocity that the shaft is being spun at (in deg/s).
+    shaft_velocity: f64,
+
+    /// Continuous position reported by the sensor (in centidegrees).
+    position: f64,
+    /// Most recent velocity reported by the sensor (in centidegrees/s).
+    velocity: f64,
+
+    reversed: bool,
+    temperature: f64,
+    data_rate: u32,
+}
+
+impl RotationSensorModel {
+    const fn new() -> Self {
+        Self {
+            shaft: 0.0,
+            last_shaft: 0.0,
+            shaft_velocity: 0.0,
+            position: 0.0,
+            velocity: 0.0,
+            reversed: false,
+            temperature: DEFAULT_TEMPERATURE,
+            data_rate: DEFAULT_DATA_RATE,
+        }
+    }
+
+    const fn direction(&self) -> f64 {
+        if self.reversed {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Absolute angle of the sensor's magnet (in centidegrees).
+    fn angle(&self) -> i32 {
+        ((self.direction() * self.shaft * 100.0).round() as i32).rem_euclid(36000)
+    }
+
+    /// Applies a command sent to the sensor by the brain.
+    fn apply(&mut self, command: RotationSensorCommand) {
+        match command {
+            RotationSensorCommand::Reset => self.position = self.angle() as f64,
+            RotationSensorCommand::SetPosition(position) => self.position = position as f64,
+            RotationSensorCommand::SetReversed(reversed) => self.reversed = reversed,
+            RotationSensorCommand::SetDataRate(rate) => {
+                self.data_rate = supported_data_rate(rate);
+            }
+        }
+    }
+
+    /// Advances the simulation by `dt` seconds.
+    fn step(&mut self, dt: f64) {
+        self.shaft += self.shaft_velocity * dt;
+
+        let delta = self.direction() * (self.shaft - self.last_shaft) * 100.0;
+        self.last_shaft = self.shaft;
+
+        self.position += delta;
+        self.velocity = delta / dt;
+    }
+
+    fn data(&self) -> RotationSensorData {
+        RotationSensorData {
+            position: self.position.round() as i32,
+            angle: self.angle(),
+            velocity: self.velocity.round() as i32,
+            reversed: self.reversed,
+            status: 0,
+            temperature: self.temperature,
+            data_rate: self.data_rate,
+        }
+    }
+}
+
+/// A simulated V5 Rotation Sensor.
+#[derive(Debug)]
+pub struct RotationSensor {
+    task: AbortHandle,
+    model: Arc<Mutex<RotationSensorModel>>,
+}
+
+impl RotationSensor {
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let model = Arc::new(Mutex::new(RotationSensorModel::new()));
+
+        Self {
+            model: model.clone(),
+            task: tokio::task::spawn(async move {
+                let mut next_update = time::Instant::now();
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            if let SmartPortCommand::RotationSensor(command) = command {
+                                model.lock().await.apply(command);
+                            }
+                        }
+                        _ = sleep_until(next_update) => {
+                            let data = {
+                                let mut model = model.lock().await;
+                                let data_rate = Duration::from_millis(model.data_rate as u64);
+
+                                model.step(data_rate.as_secs_f64());
+                                next_update += data_rate;
+
+                                model.data()
+                            };
+
+                            port.send(
+                                SmartPortData::RotationSensor(data),
+                                start.elapsed().as_millis() as u32,
+                            )
+                            .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns the data that the sensor is currently reporting to the brain.
+    pub async fn data(&self) -> RotationSensorData {
+        self.model.lock().await.data()
+    }
+
+    /// Returns the unbounded angle of the sensor's shaft (in degrees).
+    pub async fn shaft_angle(&self) -> f64 {
+        self.model.lock().await.shaft
+    }
+
+    /// Turns the sensor's shaft to an unbounded angle (in degrees).
+    ///
+    /// The sensor picks up the change on its next reading, as if the shaft
+    /// had been turned that far since the last one.
+    pub async fn set_shaft_angle(&mut self, angle: f64) {
+        self.model.lock().await.shaft = angle;
+    }
+
+    /// Spins the sensor's shaft at a constant angular velocity (in deg/s).
+    pub async fn set_shaft_velocity(&mut self, velocity: f64) {
+        self.model.lock().await.shaft_velocity = velocity;
+    }
+
+    /// Sets the temperature reported by the sensor (in degrees celsius).
+    pub async fn set_temperature(&mut self, temperature: f64) {
+        self.model.lock().await.temperature = temperature;
+    }
+}
+
+impl Drop for RotationSensor {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
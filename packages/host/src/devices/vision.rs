@@ -0,0 +1,185 @@
+use std::{
+    cmp::Reverse,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::{sync::Mutex, task::AbortHandle, time::interval};
+use vex_v5_qemu_protocol::{
+    vision::{
+        VisionCommand, VisionData, VisionObject, VisionRgb, VisionSignature, VISION_SIGNATURE_COUNT,
+    },
+    SmartPortCommand, SmartPortData,
+};
+
+use crate::peripherals::smartport::SmartPort;
+
+/// How often the sensor reports the objects in view. The sensor processes
+/// frames at 50 frames per second.
+const UPDATE_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Exposure of the camera until told otherwise (in percent).
+const DEFAULT_BRIGHTNESS: u8 = 50;
+
+#[derive(Debug, Clone, PartialEq)]
+struct VisionModel {
+    objects: Vec<VisionObject>,
+    signatures: Vec<VisionSignature>,
+
+    mode: u8,
+    brightness: u8,
+    white_balance_mode: u8,
+    white_balance: VisionRgb,
+    led_mode: u8,
+    led_brightness: u8,
+    led_color: VisionRgb,
+    wifi_mode: u8,
+}
+
+impl VisionModel {
+    const fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+            signatures: Vec::new(),
+            mode: 0,
+            brightness: DEFAULT_BRIGHTNESS,
+            white_balance_mode: 0,
+            white_balance: VisionRgb {
+                red: 0,
+                green: 0,
+                blue: 0,
+                brightness: 0,
+            },
+            led_mode: 0,
+            led_brightness: 0,
+            led_color: VisionRgb {
+                red: 0,
+                green: 0,
+                blue: 0,
+                brightness: 0,
+            },
+            wifi_mode: 0,
+        }
+    }
+
+    /// Applies a command sent to the sensor by the brain.
+    fn apply(&mut self, command: VisionCommand) {
+        match command {
+            VisionCommand::SetMode(mode) => self.mode = mode,
+            VisionCommand::SetSignature(signature) => {
+                if !(1..=VISION_SIGNATURE_COUNT as u8).contains(&signature.id) {
+                    return;
+                }
+
+                self.signatures.retain(|s| s.id != signature.id);
+                self.signatures.push(signature);
+                self.signatures.sort_by_key(|s| s.id);
+            }
+            VisionCommand::SetBrightness(brightness) => self.brightness = brightness.min(100),
+            VisionCommand::SetWhiteBalanceMode(mode) => self.white_balance_mode = mode,
+            VisionCommand::SetWhiteBalance(color) => self.white_balance = color,
+            VisionCommand::SetLedMode(mode) => self.led_mode = mode,
+            VisionCommand::SetLedBrightness(brightness) => {
+                self.led_brightness = brightness.min(100);
+            }
+            VisionCommand::SetLedColor(color) => self.led_color = color,
+            VisionCommand::SetWifiMode(mode) => self.wifi_mode = mode,
+        }
+    }
+
+    fn data(&self) -> VisionData {
+        VisionData {
+            objects: self.objects.clone(),
+            signatures: self.signatures.clone(),
+            mode: self.mode,
+            brightness: self.brightness,
+            white_balance_mode: self.white_balance_mode,
+            white_balance: self.white_balance,
+            led_mode: self.led_mode,
+            led_brightness: self.led_brightness,
+            led_color: self.led_color,
+            wifi_mode: self.wifi_mode,
+        }
+    }
+}
+
+/// A simulated V5 Vision Sensor.
+///
+/// The sensor doesn't look at anything by itself. Instead, the objects that it
+/// detects are set directly with [`Vision::set_objects`].
+#[derive(Debug)]
+pub struct Vision {
+    task: AbortHandle,
+    model: Arc<Mutex<VisionModel>>,
+}
+
+impl Vision {
+    pub fn new(mut port: SmartPort) -> Self {
+        let start = Instant::now();
+        let model = Arc::new(Mutex::new(VisionModel::new()));
+
+        Self {
+            model: model.clone(),
+            task: tokio::task::spawn(async move {
+                let mut update_interval = interval(UPDATE_INTERVAL);
+
+                loop {
+                    tokio::select! {
+                        command = port.recv() => {
+                            if let SmartPortCommand::Vision(command) = command {
+                                model.lock().await.apply(command);
+                            }
+                        }
+                        _ = update_interval.tick() => {
+                            let data = model.lock().await.data();
+
+                            port.send(
+                                SmartPortData::Vision(data),
+                                start.elapsed().as_millis() as u32,
+                            )
+                            .await;
+                        }
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Returns the data that the sensor is currently reporting to the brain.
+    pub async fn data(&self) -> VisionData {
+        self.model.lock().await.data()
+    }
+
+    /// Sets the objects in view of the sensor.
+    ///
+    /// Like the real sensor, objects are reported to the brain from largest to
+    /// smallest.
+    pub async fn set_objects(&mut self, mut objects: Vec<VisionObject>) {
+        objects.sort_by_key(|object| Reverse(object.width as u32 * object.height as u32));
+        self.model.lock().await.objects = objects;
+    }
+
+    /// Returns the color signature with the given ID that has been configured
+    /// by the brain, if there is one.
+    pub async fn signature(&self, id: u8) -> Option<VisionSignature> {
+        self.model
+            .lock()
+            .await
+            .signatures
+            .iter()
+            .find(|signature| signature.id == id)
+            .copied()
+    }
+
+    /// Returns the color of the sensor's LED, as set by the brain.
+    pub async fn led_color(&self) -> VisionRgb {
+        self.model.lock().await.led_color
+    }
+}
+
+impl Drop for Vision {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -1,5 +1,6 @@
 pub mod brain;
 pub mod devices;
 pub mod peripherals;
+mod qmp;
 
 pub use vex_v5_qemu_protocol as protocol;
@@ -0,0 +1,117 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::{
+    sync::{mpsc::Sender, watch},
+    task::AbortHandle,
+    time::interval,
+};
+use vex_v5_qemu_protocol::KernelBoundPacket;
+
+use crate::brain::{wait_for_boot, BrainStatus};
+
+/// Where the brain's real-time clock gets its time from.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClockSource {
+    /// The host's current time.
+    ///
+    /// The clock is re-synced with the host every
+    /// [`Clock::SYNC_INTERVAL`] so that it doesn't drift.
+    #[default]
+    Host,
+    /// A fixed time that the clock reads whenever the brain boots to run a
+    /// program, after which it is only advanced by the brain.
+    ///
+    /// This makes the dates and times seen by the robot code reproducible
+    /// between runs.
+    Fixed(SystemTime),
+}
+
+/// The brain's real-time clock, read by `vexGetdate` and `vexGettime`.
+#[derive(Debug)]
+pub struct Clock {
+    source: ClockSource,
+    tx: Sender<KernelBoundPacket>,
+    status: watch::Receiver<BrainStatus>,
+    task: AbortHandle,
+}
+
+impl Clock {
+    /// How often the clock is re-synced when using [`ClockSource::Host`].
+    pub const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+    pub(crate) fn new(tx: Sender<KernelBoundPacket>, status: watch::Receiver<BrainStatus>) -> Self {
+        let source = ClockSource::default();
+
+        Self {
+            task: Self::spawn_sync(source, tx.clone(), status.clone()),
+            source,
+            tx,
+            status,
+        }
+    }
+
+    /// Sends the time from `source` to the brain whenever a program starts,
+    /// and keeps it in sync if the source is the host.
+    fn spawn_sync(
+        source: ClockSource,
+        tx: Sender<KernelBoundPacket>,
+        mut status: watch::Receiver<BrainStatus>,
+    ) -> AbortHandle {
+        tokio::task::spawn(async move {
+            let mut interval = interval(Self::SYNC_INTERVAL);
+            interval.tick().await;
+
+            loop {
+                let time = match source {
+                    ClockSource::Host => SystemTime::now(),
+                    ClockSource::Fixed(time) => time,
+                };
+                // Times before the epoch aren't representable on the brain.
+                let unix_time = time
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis()
+                    .try_into()
+                    .unwrap_or(u64::MAX);
+
+                // A fixed time is when the brain booted rather than when this
+                // packet happens to arrive, so that it is the same every run.
+                let packet = match source {
+                    ClockSource::Host => KernelBoundPacket::RealTime(unix_time),
+                    ClockSource::Fixed(_) => KernelBoundPacket::BootTime(unix_time),
+                };
+                if tx.send(packet).await.is_err() {
+                    break;
+                }
+
+                match source {
+                    ClockSource::Host => tokio::select! {
+                        _ = interval.tick() => {}
+                        () = wait_for_boot(&mut status) => interval.reset(),
+                    },
+                    // A fixed time only needs to be set once per program.
+                    ClockSource::Fixed(_) => wait_for_boot(&mut status).await,
+                }
+            }
+        })
+        .abort_handle()
+    }
+
+    pub const fn source(&self) -> ClockSource {
+        self.source
+    }
+
+    /// Changes where the clock gets its time from, setting the brain's clock
+    /// to the new source's time immediately.
+    pub fn set_source(&mut self, source: ClockSource) {
+        self.task.abort();
+        self.source = source;
+        self.task = Self::spawn_sync(source, self.tx.clone(), self.status.clone());
+    }
+}
+
+impl Drop for Clock {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
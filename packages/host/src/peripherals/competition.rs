@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use tokio::{sync::mpsc::Sender, time::sleep};
+use vex_v5_qemu_protocol::{
+    competition::{CompetitionConnection, CompetitionData, CompetitionMode},
+    KernelBoundPacket,
+};
+
+/// A single period of a scripted match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MatchPeriod {
+    pub mode: CompetitionMode,
+    pub duration: Duration,
+}
+
+impl MatchPeriod {
+    pub const fn new(mode: CompetitionMode, duration: Duration) -> Self {
+        Self { mode, duration }
+    }
+
+    /// The periods of a standard VRC match: 15 seconds of autonomous followed
+    /// by 1 minute and 45 seconds of driver control.
+    pub const STANDARD_MATCH: [MatchPeriod; 2] = [
+        MatchPeriod::new(CompetitionMode::Autonomous, Duration::from_secs(15)),
+        MatchPeriod::new(CompetitionMode::Driver, Duration::from_secs(105)),
+    ];
+}
+
+#[derive(Debug)]
+pub struct Competition {
+    data: CompetitionData,
+    tx: Sender<KernelBoundPacket>,
+}
+
+impl Competition {
+    pub(crate) fn new(tx: Sender<KernelBoundPacket>) -> Self {
+        Self {
+            data: CompetitionData::default(),
+            tx,
+        }
+    }
+
+    async fn update(&mut self) {
+        self.tx
+            .send(KernelBoundPacket::CompetitionUpdate(self.data))
+            .await
+            .unwrap(); // OK to unwrap, since the channel can't be closed.
+    }
+
+    pub async fn set_data(&mut self, data: CompetitionData) {
+        self.data = data;
+        self.update().await
+    }
+
+    pub async fn set_mode(&mut self, mode: CompetitionMode) {
+        self.data.mode = mode;
+        self.update().await
+    }
+
+    /// Connects the brain to a competition controller, or disconnects it if
+    /// `connection` is `None`.
+    pub async fn set_connection(&mut self, connection: Option<CompetitionConnection>) {
+        self.data.connection = connection;
+        self.update().await
+    }
+
+    /// Runs through each period of a match in order, then disables the robot.
+    ///
+    /// This doesn't change how the brain is connected, so callers will usually
+    /// want to call [`Competition::set_connection`] first.
+    pub async fn run_match(&mut self, periods: &[MatchPeriod]) {
+        for period in periods {
+            log::info!(
+                "Entering {:?} for {:.1}s.",
+                period.mode,
+                period.duration.as_secs_f64()
+            );
+            self.set_mode(period.mode).await;
+            sleep(period.duration).await;
+        }
+
+        log::info!("Match over.");
+        self.set_mode(CompetitionMode::Disabled).await;
+    }
+
+    pub const fn mode(&self) -> CompetitionMode {
+        self.data.mode
+    }
+
+    pub const fn connection(&self) -> Option<CompetitionConnection> {
+        self.data.connection
+    }
+
+    pub const fn data(&self) -> CompetitionData {
+        self.data
+    }
+}
@@ -0,0 +1,173 @@
+use std::time::Instant;
+
+use tokio::sync::mpsc::Sender;
+use vex_v5_qemu_protocol::{
+    controller::{ControllerData, ControllerId},
+    KernelBoundPacket,
+};
+
+/// A joystick axis on a V5 Controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControllerAxis {
+    /// Right joystick X axis.
+    Axis1,
+    /// Right joystick Y axis.
+    Axis2,
+    /// Left joystick Y axis.
+    Axis3,
+    /// Left joystick X axis.
+    Axis4,
+}
+
+/// A button on a V5 Controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ControllerButton {
+    A,
+    B,
+    X,
+    Y,
+    Up,
+    Down,
+    Left,
+    Right,
+    L1,
+    L2,
+    R1,
+    R2,
+}
+
+#[derive(Debug)]
+pub struct Controller {
+    id: ControllerId,
+    data: ControllerData,
+    connected: bool,
+    start: Instant,
+    tx: Sender<KernelBoundPacket>,
+}
+
+impl Controller {
+    pub(crate) fn new(id: ControllerId, tx: Sender<KernelBoundPacket>) -> Self {
+        Self {
+            id,
+            data: ControllerData {
+                battery_level: 100,
+                battery_capacity: 100,
+                ..Default::default()
+            },
+            connected: false,
+            start: Instant::now(),
+            tx,
+        }
+    }
+
+    async fn update(&mut self) {
+        let data = &mut self.data;
+        data.button_all = data.button_a
+            || data.button_b
+            || data.button_x
+            || data.button_y
+            || data.button_up
+            || data.button_down
+            || data.button_left
+            || data.button_right
+            || data.button_l1
+            || data.button_l2
+            || data.button_r1
+            || data.button_r2;
+
+        self.tx
+            .send(KernelBoundPacket::ControllerUpdate {
+                id: self.id,
+                data: self.connected.then_some(self.data),
+                timestamp: self.start.elapsed().as_millis() as u32,
+            })
+            .await
+            .unwrap(); // OK to unwrap, since the channel can't be closed.
+    }
+
+    /// Connects the controller to the brain.
+    pub async fn connect(&mut self) {
+        self.connected = true;
+        self.update().await
+    }
+
+    /// Disconnects the controller from the brain.
+    ///
+    /// The controller's state is kept, and will be sent to the brain again
+    /// once the controller is reconnected.
+    pub async fn disconnect(&mut self) {
+        self.connected = false;
+        self.update().await
+    }
+
+    /// Sets the state of the controller.
+    ///
+    /// `button_all` is ignored, since it is always computed from the other
+    /// buttons.
+    pub async fn set_data(&mut self, data: ControllerData) {
+        self.data = data;
+        self.update().await
+    }
+
+    /// Sets the position of a joystick axis, from -127 to 127.
+    pub async fn set_axis(&mut self, axis: ControllerAxis, value: i8) {
+        let value = value.max(-127);
+
+        match axis {
+            ControllerAxis::Axis1 => self.data.axis_1 = value,
+            ControllerAxis::Axis2 => self.data.axis_2 = value,
+            ControllerAxis::Axis3 => self.data.axis_3 = value,
+            ControllerAxis::Axis4 => self.data.axis_4 = value,
+        }
+
+        self.update().await
+    }
+
+    pub async fn set_button(&mut self, button: ControllerButton, pressed: bool) {
+        let data = &mut self.data;
+
+        *match button {
+            ControllerButton::A => &mut data.button_a,
+            ControllerButton::B => &mut data.button_b,
+            ControllerButton::X => &mut data.button_x,
+            ControllerButton::Y => &mut data.button_y,
+            ControllerButton::Up => &mut data.button_up,
+            ControllerButton::Down => &mut data.button_down,
+            ControllerButton::Left => &mut data.button_left,
+            ControllerButton::Right => &mut data.button_right,
+            ControllerButton::L1 => &mut data.button_l1,
+            ControllerButton::L2 => &mut data.button_l2,
+            ControllerButton::R1 => &mut data.button_r1,
+            ControllerButton::R2 => &mut data.button_r2,
+        } = pressed;
+
+        self.update().await
+    }
+
+    /// Sets the controller's battery level in percent.
+    pub async fn set_battery_level(&mut self, level: u8) {
+        self.data.battery_level = level;
+        self.update().await
+    }
+
+    pub const fn id(&self) -> ControllerId {
+        self.id
+    }
+
+    pub const fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub const fn axis(&self, axis: ControllerAxis) -> i8 {
+        match axis {
+            ControllerAxis::Axis1 => self.data.axis_1,
+            ControllerAxis::Axis2 => self.data.axis_2,
+            ControllerAxis::Axis3 => self.data.axis_3,
+            ControllerAxis::Axis4 => self.data.axis_4,
+        }
+    }
+
+    pub const fn data(&self) -> ControllerData {
+        self.data
+    }
+}
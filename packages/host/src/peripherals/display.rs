@@ -1,8 +1,10 @@
 use std::{
+    io,
     sync::Mutex,
     time::{Duration, Instant},
 };
 
+use image::ImageReader;
 use tokio::{
     sync::{
         mpsc::{Receiver, Sender},
@@ -11,7 +13,12 @@ use tokio::{
     task::AbortHandle,
 };
 use vex_v5_display_simulator::{ColorTheme, DisplayRenderer, Pixmap, TextOptions};
-use vex_v5_qemu_protocol::{display::DrawCommand, DisplayCommand, KernelBoundPacket};
+use vex_v5_qemu_protocol::{
+    display::{DecodedImage, DrawCommand, ImageFormat},
+    DisplayCommand, KernelBoundPacket,
+};
+
+use crate::brain::{wait_for_boot, BrainStatus};
 
 #[derive(Debug)]
 pub struct Display {
@@ -23,11 +30,15 @@ impl Display {
     pub const WIDTH: u32 = 480;
     pub const HEIGHT: u32 = 272;
 
-    pub fn new(_tx: Sender<KernelBoundPacket>, mut rx: Receiver<DisplayCommand>) -> Self {
+    pub fn new(
+        tx: Sender<KernelBoundPacket>,
+        mut rx: Receiver<DisplayCommand>,
+        mut status: watch::Receiver<BrainStatus>,
+    ) -> Self {
         let (data_tx, data_rx) = watch::channel(Mutex::new(None));
         Self {
             task: tokio::spawn(async move {
-                let start = Instant::now();
+                let mut start = Instant::now();
                 let mut renderer = DisplayRenderer::new(ColorTheme::Dark);
                 renderer.draw_header("User".to_string(), start.elapsed());
                 let mut interval = tokio::time::interval(Duration::from_secs(1));
@@ -41,6 +52,18 @@ impl Display {
                                 _ = data_tx.send(Mutex::new(Some(frame)));
                             }
                         }
+                        // Each program starts with a blank screen and its own
+                        // program timer.
+                        () = wait_for_boot(&mut status) => {
+                            start = Instant::now();
+                            renderer = DisplayRenderer::new(ColorTheme::Dark);
+                            renderer.draw_header("User".to_string(), start.elapsed());
+                            interval.reset();
+
+                            if let Some(frame) = renderer.render(false) {
+                                _ = data_tx.send(Mutex::new(Some(frame)));
+                            }
+                        }
                         command = rx.recv() => {
                             if let Some(command) = command {
                                 let mut new_frame = None;
@@ -48,9 +71,12 @@ impl Display {
                                     DisplayCommand::Draw {
                                         command,
                                         color,
-                                        clip_region: _,
+                                        pen_size,
+                                        clip_region,
                                     } => {
+                                        renderer.set_clip_region(clip_region);
                                         renderer.context.foreground_color = color;
+                                        renderer.context.pen_size = pen_size;
                                         match command {
                                             DrawCommand::Fill(shape) => {
                                                 renderer.draw(shape, false);
@@ -93,9 +119,10 @@ impl Display {
                                     }
                                     DisplayCommand::Erase {
                                         color,
-                                        clip_region: _,
+                                        clip_region,
                                     } => {
-                                        renderer.context.foreground_color = color;
+                                        renderer.set_clip_region(clip_region);
+                                        renderer.context.background_color = color;
                                         renderer.erase();
                                     }
                                     DisplayCommand::Render => {
@@ -104,8 +131,32 @@ impl Display {
                                     DisplayCommand::DisableDoubleBuffering => {
                                         renderer.disable_double_buffer();
                                     }
-                                    DisplayCommand::Scroll { .. } => {
-                                        todo!()
+                                    DisplayCommand::Scroll {
+                                        location,
+                                        lines,
+                                        background,
+                                        clip_region,
+                                    } => {
+                                        renderer.set_clip_region(clip_region);
+                                        renderer.context.background_color = background;
+                                        renderer.scroll(location, lines);
+                                    }
+                                    DisplayCommand::DecodeImage {
+                                        format,
+                                        data,
+                                        max_width,
+                                        max_height,
+                                    } => {
+                                        let image =
+                                            decode_image(format, &data, max_width, max_height);
+                                        _ = tx.send(KernelBoundPacket::DecodedImage(image)).await;
+                                        continue;
+                                    }
+                                    DisplayCommand::MeasureText { data, font, size } => {
+                                        let metrics =
+                                            renderer.measure_text(&data, TextOptions { size, font });
+                                        _ = tx.send(KernelBoundPacket::TextMetrics(metrics)).await;
+                                        continue;
                                     }
                                 }
 
@@ -142,6 +193,47 @@ impl Display {
     }
 }
 
+/// Decodes an image for the brain, returning `None` if it is invalid or larger
+/// than `max_width` by `max_height` pixels.
+fn decode_image(
+    format: ImageFormat,
+    data: &[u8],
+    max_width: u32,
+    max_height: u32,
+) -> Option<DecodedImage> {
+    let format = match format {
+        ImageFormat::Png => image::ImageFormat::Png,
+        ImageFormat::Bmp => image::ImageFormat::Bmp,
+    };
+
+    let image = ImageReader::with_format(io::Cursor::new(data), format)
+        .decode()
+        .inspect_err(|err| log::warn!("Couldn't decode image: {err}"))
+        .ok()?
+        .into_rgba8();
+
+    if image.width() > max_width || image.height() > max_height {
+        log::warn!(
+            "Image is {}x{}, but only {max_width}x{max_height} was allowed.",
+            image.width(),
+            image.height()
+        );
+        return None;
+    }
+
+    Some(DecodedImage {
+        width: image.width().try_into().ok()?,
+        height: image.height().try_into().ok()?,
+        pixels: image
+            .pixels()
+            .map(|pixel| {
+                let [r, g, b, a] = pixel.0;
+                u32::from_be_bytes([a, r, g, b])
+            })
+            .collect(),
+    })
+}
+
 impl Drop for Display {
     fn drop(&mut self) {
         self.task.abort();
@@ -1,11 +1,19 @@
 pub mod battery;
+pub mod clock;
+pub mod competition;
+pub mod controller;
 pub mod display;
+pub mod sd_card;
 pub mod smartport;
 pub mod touch;
 pub mod usb;
 
 use battery::Battery;
+use clock::Clock;
+use competition::Competition;
+use controller::Controller;
 use display::Display;
+use sd_card::SdCard;
 use smartport::SmartPort;
 use touch::Touchscreen;
 use usb::{UsbRead, UsbWrite};
@@ -37,8 +45,15 @@ pub struct Peripherals {
     pub port_19: SmartPort,
     pub port_20: SmartPort,
     pub port_21: SmartPort,
+    /// The brain's onboard three-wire ports.
+    pub onboard_adi: SmartPort,
 
     pub display: Display,
     pub touch: Touchscreen,
-    // TODO: onboard ADI, controllers, display/touch, usb, sdcard
+
+    pub master_controller: Controller,
+    pub partner_controller: Controller,
+    pub competition: Competition,
+    pub sd_card: SdCard,
+    pub clock: Clock,
 }
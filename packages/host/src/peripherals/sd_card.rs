@@ -0,0 +1,753 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    fs::{self, OpenOptions},
+    io::{self, Read, Seek, Write},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex as StdMutex},
+};
+
+use tokio::{
+    sync::{
+        mpsc::{Receiver, Sender},
+        Mutex,
+    },
+    task::AbortHandle,
+};
+use vex_v5_qemu_protocol::{
+    sd_card::{FileError, FileKind, FileRequest, FileResponse, OpenMode, SeekFrom},
+    KernelBoundPacket,
+};
+
+/// Maximum number of files that can be open at once.
+const MAX_OPEN_FILES: usize = 8;
+
+/// The largest file that can be stored on a FAT32 formatted SD card, which is
+/// a reasonable limit for [`SdCardStorage::Memory`].
+pub const FAT32_MAX_FILE_SIZE: usize = u32::MAX as usize;
+
+/// Where the files on a simulated SD card are stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SdCardStorage {
+    /// A directory on the host, which becomes the root of the card.
+    Directory(PathBuf),
+    /// A filesystem that only exists in memory. It starts out empty, and its
+    /// contents are lost when the card is ejected.
+    Memory {
+        /// The largest a single file can grow to, in bytes. Writes past this
+        /// size fail.
+        max_file_size: usize,
+    },
+}
+
+/// The SD card slot on the brain.
+///
+/// The slot starts out empty, so the brain will report that no SD card is
+/// inserted until [`SdCard::insert`] is called.
+#[derive(Debug)]
+pub struct SdCard {
+    task: AbortHandle,
+    card: Arc<Mutex<Option<Card>>>,
+}
+
+impl SdCard {
+    pub(crate) fn new(tx: Sender<KernelBoundPacket>, mut rx: Receiver<FileRequest>) -> Self {
+        let card = Arc::new(Mutex::new(None::<Card>));
+
+        Self {
+            card: card.clone(),
+            task: tokio::task::spawn(async move {
+                while let Some(request) = rx.recv().await {
+                    let response = match &mut *card.lock().await {
+                        Some(card) => card.handle(request),
+                        None => match request {
+                            FileRequest::DriveStatus => {
+                                Ok(FileResponse::DriveStatus { inserted: false })
+                            }
+                            _ => Err(FileError::NotReady),
+                        },
+                    };
+
+                    // The kernel waits for a response to every request, so one must
+                    // always be sent.
+                    if tx
+                        .send(KernelBoundPacket::SdCardResponse(response))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            })
+            .abort_handle(),
+        }
+    }
+
+    /// Inserts an SD card, replacing any card that was already inserted.
+    ///
+    /// Files that the brain had open on the previous card are closed.
+    pub async fn insert(&mut self, storage: SdCardStorage) -> io::Result<()> {
+        let filesystem: Box<dyn Filesystem> = match storage {
+            SdCardStorage::Directory(root) => {
+                if !root.is_dir() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("SD card directory `{}` does not exist", root.display()),
+                    ));
+                }
+                Box::new(DirectoryFilesystem {
+                    root: root.canonicalize()?,
+                })
+            }
+            SdCardStorage::Memory { max_file_size } => Box::new(MemoryFilesystem {
+                entries: BTreeMap::new(),
+                max_file_size,
+            }),
+        };
+
+        *self.card.lock().await = Some(Card {
+            filesystem,
+            open_files: HashMap::new(),
+            next_fd: 0,
+        });
+        Ok(())
+    }
+
+    /// Removes the SD card from the brain.
+    pub async fn eject(&mut self) {
+        *self.card.lock().await = None;
+    }
+
+    /// Returns whether an SD card is inserted.
+    pub async fn is_inserted(&self) -> bool {
+        self.card.lock().await.is_some()
+    }
+
+    /// Reads the contents of a file on the card, such as a log written by the
+    /// brain.
+    pub async fn read_file(&self, path: &str) -> Result<Vec<u8>, FileError> {
+        let mut card = self.card.lock().await;
+        let card = card.as_mut().ok_or(FileError::NotReady)?;
+
+        let mut file = card.filesystem.open(&normalize(path)?, OpenMode::Read)?;
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents).map_err(io_error)?;
+        Ok(contents)
+    }
+
+    /// Creates or replaces a file on the card, such as a config file for the
+    /// brain to read. Any missing parent directories are created.
+    pub async fn write_file(&mut self, path: &str, contents: &[u8]) -> Result<(), FileError> {
+        let mut card = self.card.lock().await;
+        let card = card.as_mut().ok_or(FileError::NotReady)?;
+
+        let path = normalize(path)?;
+        if let Some(parent) = path.parent() {
+            card.filesystem.create_dir_all(parent)?;
+        }
+
+        let mut file = card.filesystem.open(&path, OpenMode::Create)?;
+        file.write_all(contents).map_err(io_error)
+    }
+}
+
+impl Drop for SdCard {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// An inserted SD card and the files that the brain has open on it.
+#[derive(Debug)]
+struct Card {
+    filesystem: Box<dyn Filesystem>,
+    open_files: HashMap<u32, OpenFile>,
+    next_fd: u32,
+}
+
+#[derive(Debug)]
+struct OpenFile {
+    handle: Box<dyn FileHandle>,
+    mode: OpenMode,
+}
+
+impl Card {
+    fn file(&mut self, fd: u32) -> Result<&mut OpenFile, FileError> {
+        self.open_files.get_mut(&fd).ok_or(FileError::InvalidObject)
+    }
+
+    fn handle(&mut self, request: FileRequest) -> Result<FileResponse, FileError> {
+        match request {
+            FileRequest::DriveStatus => Ok(FileResponse::DriveStatus { inserted: true }),
+            FileRequest::Open { path, mode } => {
+                if self.open_files.len() >= MAX_OPEN_FILES {
+                    return Err(FileError::TooManyOpenFiles);
+                }
+
+                let handle = self.filesystem.open(&normalize(&path)?, mode)?;
+                let fd = self.next_fd;
+                self.next_fd = self.next_fd.wrapping_add(1);
+                self.open_files.insert(fd, OpenFile { handle, mode });
+
+                Ok(FileResponse::Opened { fd })
+            }
+            FileRequest::Close { fd } => {
+                let mut file = self
+                    .open_files
+                    .remove(&fd)
+                    .ok_or(FileError::InvalidObject)?;
+                file.handle.flush().map_err(io_error)?;
+                Ok(FileResponse::Done)
+            }
+            FileRequest::Read { fd, len } => {
+                let file = self.file(fd)?;
+                if file.mode != OpenMode::Read {
+                    return Err(FileError::Denied);
+                }
+
+                let mut data = Vec::new();
+                (&mut file.handle)
+                    .take(len as u64)
+                    .read_to_end(&mut data)
+                    .map_err(io_error)?;
+                Ok(FileResponse::Data(data))
+            }
+            FileRequest::Write { fd, data } => {
+                let file = self.file(fd)?;
+                if file.mode == OpenMode::Read {
+                    return Err(FileError::Denied);
+                }
+
+                file.handle.write_all(&data).map_err(io_error)?;
+                Ok(FileResponse::Written(data.len() as u32))
+            }
+            FileRequest::Seek { fd, position } => {
+                let file = self.file(fd)?;
+                let position = match position {
+                    SeekFrom::Start(offset) => io::SeekFrom::Start(offset as u64),
+                    SeekFrom::Current(offset) => io::SeekFrom::Current(offset as i64),
+                    SeekFrom::End(offset) => io::SeekFrom::End(offset as i64),
+                };
+
+                let position = file.handle.seek(position).map_err(io_error)?;
+                Ok(FileResponse::Position(position as u32))
+            }
+            FileRequest::Tell { fd } => {
+                let position = self.file(fd)?.handle.stream_position().map_err(io_error)?;
+                Ok(FileResponse::Position(position as u32))
+            }
+            FileRequest::Size { fd } => {
+                let size = self.file(fd)?.handle.size().map_err(io_error)?;
+                Ok(FileResponse::Size(size as u32))
+            }
+            FileRequest::Sync { fd } => {
+                self.file(fd)?.handle.sync().map_err(io_error)?;
+                Ok(FileResponse::Done)
+            }
+            FileRequest::ReadDirectory { path } => Ok(FileResponse::Directory(
+                self.filesystem.read_dir(&normalize(&path)?)?,
+            )),
+            FileRequest::Status { path } => Ok(FileResponse::Status(
+                normalize(&path)
+                    .ok()
+                    .and_then(|path| self.filesystem.status(&path)),
+            )),
+        }
+    }
+}
+
+/// Converts a path from the brain into a path relative to the root of the
+/// card.
+///
+/// Paths may start with a slash, and may not refer to anything outside of the
+/// card.
+fn normalize(path: &str) -> Result<PathBuf, FileError> {
+    let mut normalized = PathBuf::new();
+
+    for component in Path::new(path).components() {
+        match component {
+            Component::RootDir | Component::CurDir => {}
+            Component::Normal(name) => normalized.push(name),
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return Err(FileError::InvalidName);
+                }
+            }
+            Component::Prefix(_) => return Err(FileError::InvalidName),
+        }
+    }
+
+    Ok(normalized)
+}
+
+/// Converts an error from the host's filesystem into the closest FatFs error.
+fn io_error(error: io::Error) -> FileError {
+    match error.kind() {
+        io::ErrorKind::NotFound => FileError::NoFile,
+        io::ErrorKind::PermissionDenied => FileError::Denied,
+        io::ErrorKind::AlreadyExists => FileError::Exists,
+        io::ErrorKind::InvalidInput => FileError::InvalidParameter,
+        io::ErrorKind::StorageFull => FileError::Denied,
+        _ => FileError::DiskError,
+    }
+}
+
+/// The storage behind an SD card.
+///
+/// Paths are relative to the root of the card and have already been
+/// normalized.
+trait Filesystem: Debug + Send {
+    fn open(&mut self, path: &Path, mode: OpenMode) -> Result<Box<dyn FileHandle>, FileError>;
+    fn create_dir_all(&mut self, path: &Path) -> Result<(), FileError>;
+    fn read_dir(&self, path: &Path) -> Result<Vec<String>, FileError>;
+    fn status(&self, path: &Path) -> Option<FileKind>;
+}
+
+trait FileHandle: Debug + Read + Write + Seek + Send {
+    fn size(&mut self) -> io::Result<u64>;
+    fn sync(&mut self) -> io::Result<()>;
+}
+
+#[derive(Debug)]
+struct DirectoryFilesystem {
+    /// The canonicalized path of the card's root directory.
+    root: PathBuf,
+}
+
+impl DirectoryFilesystem {
+    /// Returns where a path on the card is on the host, making sure that it
+    /// doesn't lead outside of the card through a symlink.
+    fn host_path(&self, path: &Path) -> Result<PathBuf, FileError> {
+        let host_path = self.root.join(path);
+
+        // Only the part of the path that already exists can contain symlinks, so
+        // that part is resolved and the rest is added back on afterwards.
+        let existing = host_path
+            .ancestors()
+            .find(|ancestor| ancestor.symlink_metadata().is_ok())
+            .unwrap_or(&self.root);
+        let resolved = existing.canonicalize().map_err(io_error)?;
+        if !resolved.starts_with(&self.root) {
+            return Err(FileError::Denied);
+        }
+
+        Ok(match host_path.strip_prefix(existing) {
+            Ok(rest) if !rest.as_os_str().is_empty() => resolved.join(rest),
+            _ => resolved,
+        })
+    }
+}
+
+impl Filesystem for DirectoryFilesystem {
+    fn open(&mut self, path: &Path, mode: OpenMode) -> Result<Box<dyn FileHandle>, FileError> {
+        let host_path = self.host_path(path)?;
+
+        let mut options = OpenOptions::new();
+        match mode {
+            OpenMode::Read => options.read(true),
+            OpenMode::Append => options.write(true).create(true),
+            OpenMode::Create => options.write(true).create(true).truncate(true),
+        };
+
+        let mut file = options.open(&host_path).map_err(|err| {
+            // FatFs tells apart a missing file from a missing directory.
+            let parent_exists = host_path.parent().is_some_and(Path::is_dir);
+            match err.kind() {
+                io::ErrorKind::NotFound if !parent_exists => FileError::NoPath,
+                _ => io_error(err),
+            }
+        })?;
+
+        if mode == OpenMode::Append {
+            file.seek(io::SeekFrom::End(0)).map_err(io_error)?;
+        }
+
+        Ok(Box::new(file))
+    }
+
+    fn create_dir_all(&mut self, path: &Path) -> Result<(), FileError> {
+        fs::create_dir_all(self.host_path(path)?).map_err(io_error)
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<String>, FileError> {
+        let entries = fs::read_dir(self.host_path(path)?).map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => FileError::NoPath,
+            _ => io_error(err),
+        })?;
+
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(io_error)?;
+            names.push(entry.file_name().to_string_lossy().into_owned());
+        }
+        names.sort();
+
+        Ok(names)
+    }
+
+    fn status(&self, path: &Path) -> Option<FileKind> {
+        let metadata = fs::metadata(self.host_path(path).ok()?).ok()?;
+
+        Some(if metadata.is_dir() {
+            FileKind::Directory
+        } else {
+            FileKind::File
+        })
+    }
+}
+
+impl FileHandle for fs::File {
+    fn size(&mut self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.sync_data()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum MemoryEntry {
+    File(Arc<StdMutex<Vec<u8>>>),
+    Directory,
+}
+
+#[derive(Debug)]
+struct MemoryFilesystem {
+    /// Every file and directory on the card other than the root.
+    entries: BTreeMap<PathBuf, MemoryEntry>,
+    max_file_size: usize,
+}
+
+impl MemoryFilesystem {
+    fn is_dir(&self, path: &Path) -> bool {
+        path.as_os_str().is_empty()
+            || matches!(self.entries.get(path), Some(MemoryEntry::Directory))
+    }
+}
+
+impl Filesystem for MemoryFilesystem {
+    fn open(&mut self, path: &Path, mode: OpenMode) -> Result<Box<dyn FileHandle>, FileError> {
+        if !path.parent().is_some_and(|parent| self.is_dir(parent)) {
+            return Err(FileError::NoPath);
+        }
+
+        let data = match (self.entries.get(path), mode) {
+            (Some(MemoryEntry::Directory), _) => return Err(FileError::Denied),
+            (Some(MemoryEntry::File(data)), OpenMode::Create) => {
+                data.lock().unwrap().clear();
+                data.clone()
+            }
+            (Some(MemoryEntry::File(data)), _) => data.clone(),
+            (None, OpenMode::Read) => return Err(FileError::NoFile),
+            (None, _) => {
+                let data = Arc::new(StdMutex::new(Vec::new()));
+                self.entries
+                    .insert(path.to_path_buf(), MemoryEntry::File(data.clone()));
+                data
+            }
+        };
+
+        let position = match mode {
+            OpenMode::Append => data.lock().unwrap().len() as u64,
+            _ => 0,
+        };
+
+        Ok(Box::new(MemoryFile {
+            data,
+            position,
+            max_size: self.max_file_size,
+        }))
+    }
+
+    fn create_dir_all(&mut self, path: &Path) -> Result<(), FileError> {
+        for dir in path.ancestors().filter(|dir| !dir.as_os_str().is_empty()) {
+            match self.entries.get(dir) {
+                Some(MemoryEntry::File(_)) => return Err(FileError::Exists),
+                Some(MemoryEntry::Directory) => {}
+                None => {
+                    self.entries
+                        .insert(dir.to_path_buf(), MemoryEntry::Directory);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<String>, FileError> {
+        if !self.is_dir(path) {
+            return Err(FileError::NoPath);
+        }
+
+        Ok(self
+            .entries
+            .keys()
+            .filter(|entry| entry.parent() == Some(path))
+            .filter_map(|entry| Some(entry.file_name()?.to_string_lossy().into_owned()))
+            .collect())
+    }
+
+    fn status(&self, path: &Path) -> Option<FileKind> {
+        if self.is_dir(path) {
+            return Some(FileKind::Directory);
+        }
+
+        self.entries.get(path).map(|_| FileKind::File)
+    }
+}
+
+#[derive(Debug)]
+struct MemoryFile {
+    data: Arc<StdMutex<Vec<u8>>>,
+    position: u64,
+    max_size: usize,
+}
+
+impl Read for MemoryFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let data = self.data.lock().unwrap();
+        let start = (self.position as usize).min(data.len());
+        let len = buf.len().min(data.len() - start);
+
+        buf[..len].copy_from_slice(&data[start..start + len]);
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl Write for MemoryFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut data = self.data.lock().unwrap();
+        let start = self.position as usize;
+        let end = start
+            .checked_add(buf.len())
+            .filter(|&end| end <= self.max_size)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::StorageFull, "File is too large."))?;
+
+        // Seeking past the end of a file and then writing fills the gap with zeroes.
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        data[start..end].copy_from_slice(buf);
+
+        self.position = end as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for MemoryFile {
+    fn seek(&mut self, position: io::SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match position {
+            io::SeekFrom::Start(offset) => {
+                self.position = offset;
+                return Ok(offset);
+            }
+            io::SeekFrom::Current(offset) => (self.position, offset),
+            io::SeekFrom::End(offset) => (self.data.lock().unwrap().len() as u64, offset),
+        };
+
+        self.position = base.checked_add_signed(offset).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Seeked before start of file.")
+        })?;
+        Ok(self.position)
+    }
+}
+
+impl FileHandle for MemoryFile {
+    fn size(&mut self) -> io::Result<u64> {
+        Ok(self.data.lock().unwrap().len() as u64)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filesystem() -> MemoryFilesystem {
+        MemoryFilesystem {
+            entries: BTreeMap::new(),
+            max_file_size: 16,
+        }
+    }
+
+    fn read_file(filesystem: &mut MemoryFilesystem, path: &str) -> Vec<u8> {
+        let mut contents = Vec::new();
+        filesystem
+            .open(Path::new(path), OpenMode::Read)
+            .unwrap()
+            .read_to_end(&mut contents)
+            .unwrap();
+        contents
+    }
+
+    #[test]
+    fn files_can_be_written_and_read() {
+        let mut filesystem = filesystem();
+        let mut file = filesystem
+            .open(Path::new("a.txt"), OpenMode::Create)
+            .unwrap();
+        file.write_all(b"hello").unwrap();
+
+        assert_eq!(read_file(&mut filesystem, "a.txt"), b"hello");
+        assert_eq!(filesystem.status(Path::new("a.txt")), Some(FileKind::File));
+    }
+
+    #[test]
+    fn create_truncates_and_append_extends() {
+        let mut filesystem = filesystem();
+        let path = Path::new("a.txt");
+        filesystem
+            .open(path, OpenMode::Create)
+            .unwrap()
+            .write_all(b"hello")
+            .unwrap();
+
+        filesystem
+            .open(path, OpenMode::Append)
+            .unwrap()
+            .write_all(b" world")
+            .unwrap();
+        assert_eq!(read_file(&mut filesystem, "a.txt"), b"hello world");
+
+        filesystem
+            .open(path, OpenMode::Create)
+            .unwrap()
+            .write_all(b"bye")
+            .unwrap();
+        assert_eq!(read_file(&mut filesystem, "a.txt"), b"bye");
+    }
+
+    #[test]
+    fn seek_moves_the_position() {
+        let mut filesystem = filesystem();
+        let mut file = filesystem
+            .open(Path::new("a.txt"), OpenMode::Create)
+            .unwrap();
+        file.write_all(b"abcdef").unwrap();
+
+        assert_eq!(file.seek(io::SeekFrom::Start(2)).unwrap(), 2);
+        assert_eq!(file.seek(io::SeekFrom::Current(1)).unwrap(), 3);
+        assert_eq!(file.seek(io::SeekFrom::End(-1)).unwrap(), 5);
+        assert!(file.seek(io::SeekFrom::Current(-6)).is_err());
+
+        file.seek(io::SeekFrom::Start(1)).unwrap();
+        file.write_all(b"X").unwrap();
+        assert_eq!(file.stream_position().unwrap(), 2);
+        assert_eq!(read_file(&mut filesystem, "a.txt"), b"aXcdef");
+    }
+
+    #[test]
+    fn writing_past_the_end_fills_with_zeroes() {
+        let mut filesystem = filesystem();
+        let mut file = filesystem
+            .open(Path::new("a.txt"), OpenMode::Create)
+            .unwrap();
+        file.write_all(b"ab").unwrap();
+        file.seek(io::SeekFrom::Start(4)).unwrap();
+        file.write_all(b"c").unwrap();
+
+        assert_eq!(file.size().unwrap(), 5);
+        assert_eq!(read_file(&mut filesystem, "a.txt"), b"ab\0\0c");
+    }
+
+    #[test]
+    fn files_cannot_grow_past_the_limit() {
+        let mut filesystem = filesystem();
+        let mut file = filesystem
+            .open(Path::new("a.txt"), OpenMode::Create)
+            .unwrap();
+
+        file.seek(io::SeekFrom::Start(u64::MAX)).unwrap();
+        assert_eq!(
+            file.write(b"a").map_err(io_error).unwrap_err(),
+            FileError::Denied
+        );
+
+        file.seek(io::SeekFrom::Start(15)).unwrap();
+        assert!(file.write(b"ab").is_err());
+        file.write_all(b"a").unwrap();
+        assert_eq!(file.size().unwrap(), 16);
+    }
+
+    #[test]
+    fn directories_are_listed() {
+        let mut filesystem = filesystem();
+        filesystem.create_dir_all(Path::new("logs/old")).unwrap();
+        filesystem
+            .open(Path::new("logs/b.txt"), OpenMode::Create)
+            .unwrap();
+        filesystem
+            .open(Path::new("logs/a.txt"), OpenMode::Create)
+            .unwrap();
+
+        assert_eq!(
+            filesystem.read_dir(Path::new("logs")).unwrap(),
+            ["a.txt", "b.txt", "old"]
+        );
+        assert_eq!(filesystem.read_dir(Path::new("")).unwrap(), ["logs"]);
+        assert_eq!(
+            filesystem.status(Path::new("logs/old")),
+            Some(FileKind::Directory)
+        );
+        assert_eq!(
+            filesystem.read_dir(Path::new("missing")).unwrap_err(),
+            FileError::NoPath
+        );
+    }
+
+    #[test]
+    fn missing_files_are_reported() {
+        let mut filesystem = filesystem();
+
+        assert_eq!(
+            filesystem
+                .open(Path::new("a.txt"), OpenMode::Read)
+                .unwrap_err(),
+            FileError::NoFile
+        );
+        assert_eq!(
+            filesystem
+                .open(Path::new("missing/a.txt"), OpenMode::Create)
+                .unwrap_err(),
+            FileError::NoPath
+        );
+        assert_eq!(filesystem.status(Path::new("a.txt")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_cannot_leave_the_card() {
+        let dir = std::env::temp_dir().join(format!("sd-card-{}", std::process::id()));
+        let (root, outside) = (dir.join("card"), dir.join("outside"));
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("secret.txt"), b"secret").unwrap();
+        std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+
+        let mut filesystem = DirectoryFilesystem {
+            root: root.canonicalize().unwrap(),
+        };
+        let opened = filesystem
+            .open(Path::new("link/secret.txt"), OpenMode::Read)
+            .map(|_| ());
+        let created = filesystem
+            .open(Path::new("link/new.txt"), OpenMode::Create)
+            .map(|_| ());
+        let listed = filesystem.read_dir(Path::new("link"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(opened, Err(FileError::Denied));
+        assert_eq!(created, Err(FileError::Denied));
+        assert_eq!(listed, Err(FileError::Denied));
+        assert_eq!(filesystem.status(Path::new("link")), None);
+    }
+}
@@ -33,6 +33,30 @@ impl SmartPort {
             .unwrap()
     }
 
+    /// Sends bytes to the port while it is in generic serial mode.
+    pub async fn send_serial(&mut self, data: Vec<u8>) {
+        // Safe to unwrap since we are dropped at the same time as the receiver.
+        self.tx
+            .send(KernelBoundPacket::GenericSerial {
+                port_index: self.index,
+                data,
+            })
+            .await
+            .unwrap()
+    }
+
+    /// Sends bytes that the port's radio has received over VEXlink.
+    pub async fn send_radio(&mut self, data: Vec<u8>) {
+        // Safe to unwrap since we are dropped at the same time as the receiver.
+        self.tx
+            .send(KernelBoundPacket::GenericRadio {
+                port_index: self.index,
+                data,
+            })
+            .await
+            .unwrap()
+    }
+
     pub async fn recv(&mut self) -> SmartPortCommand {
         // Safe to unwrap since we are dropped at the same time as the sender.
         self.rx.recv().await.unwrap()
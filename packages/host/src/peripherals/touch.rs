@@ -1,9 +1,9 @@
 use std::{sync::Arc, time::Duration};
 
 use tokio::{
-    sync::{mpsc::Sender, Mutex, Notify},
+    sync::{mpsc::Sender, Mutex},
     task::AbortHandle,
-    time::sleep,
+    time::{interval, sleep, MissedTickBehavior},
 };
 use vex_v5_qemu_protocol::{
     geometry::Point2,
@@ -11,51 +11,181 @@ use vex_v5_qemu_protocol::{
     KernelBoundPacket,
 };
 
+/// A touch gesture that can be performed on the screen with
+/// [`Touchscreen::perform`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Gesture {
+    /// Presses a point and releases it shortly after.
+    Tap(Point2<i16>),
+    /// Presses a point and holds it for `duration` before releasing it.
+    LongPress {
+        point: Point2<i16>,
+        duration: Duration,
+    },
+    /// Presses the first point of `path`, drags through the rest of the
+    /// points at an even speed over `duration`, then releases at the last
+    /// point.
+    Swipe {
+        path: Vec<Point2<i16>>,
+        duration: Duration,
+    },
+}
+
 #[derive(Debug)]
 pub struct Touchscreen {
     data: Arc<Mutex<TouchData>>,
-    notify: Arc<Notify>,
+    tx: Sender<KernelBoundPacket>,
     task: AbortHandle,
 }
 
 impl Touchscreen {
+    /// How long a press lasts before the brain is told that the screen is
+    /// being held, and how often the point moves during a [`Gesture::Swipe`].
     pub const UPDATE_INTERVAL: Duration = Duration::from_millis(5);
 
+    /// How long the screen is pressed for during a [`Gesture::Tap`].
+    pub const TAP_DURATION: Duration = Duration::from_millis(50);
+
     pub fn new(tx: Sender<KernelBoundPacket>) -> Self {
         let data = Arc::new(Mutex::new(TouchData::default()));
-        let notify = Arc::new(Notify::new());
 
         Self {
             data: data.clone(),
-            notify: notify.clone(),
+            tx: tx.clone(),
             task: tokio::task::spawn(async move {
-                loop {
-                    notify.notified().await;
+                let mut interval = interval(Self::UPDATE_INTERVAL);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
-                    let data = *data.lock().await;
-                    _ = tx.send(KernelBoundPacket::Touch(data)).await;
+                loop {
+                    interval.tick().await;
 
-                    sleep(Self::UPDATE_INTERVAL).await;
+                    // Once pressed, the screen reports being held. After that, the
+                    // brain is only told about it again when the point moves.
+                    //
+                    // The lock is held while sending so that this can't be
+                    // reordered with a release.
+                    let mut data = data.lock().await;
+                    if data.event == TouchEvent::Press {
+                        data.event = TouchEvent::Held;
+                        _ = tx.send(KernelBoundPacket::Touch(*data)).await;
+                    }
                 }
             })
             .abort_handle(),
         }
     }
 
+    /// Sets the state of the screen and sends it to the brain immediately.
     pub async fn set_data(&mut self, data: TouchData) {
-        *self.data.lock().await = data;
-        self.notify.notify_one();
+        let mut current = self.data.lock().await;
+        *current = data;
+        _ = self.tx.send(KernelBoundPacket::Touch(data)).await;
     }
 
+    /// Moves the point being touched, dragging it if the screen is pressed.
+    ///
+    /// The brain can't sense where the screen will be pressed, so nothing is
+    /// sent to it while the screen is released.
     pub async fn set_point(&mut self, point: Point2<i16>) {
-        self.data.lock().await.point = point;
-        self.notify.notify_one();
+        let mut data = self.data.lock().await;
+        data.point = point;
+
+        if data.event != TouchEvent::Release {
+            data.event = TouchEvent::Held;
+            _ = self.tx.send(KernelBoundPacket::Touch(*data)).await;
+        }
     }
 
+    /// Presses or releases the screen at the current point.
+    ///
+    /// Pressing the screen while it is already pressed is treated as holding
+    /// it.
     pub async fn set_event(&mut self, event: TouchEvent) {
-        self.data.lock().await.event = event;
-        self.notify.notify_one();
+        let data = *self.data.lock().await;
+        let event = match (data.event, event) {
+            (TouchEvent::Press | TouchEvent::Held, TouchEvent::Press) => TouchEvent::Held,
+            (_, event) => event,
+        };
+        self.set_data(TouchData { event, ..data }).await;
+    }
+
+    /// Presses the screen at `point`.
+    pub async fn press(&mut self, point: Point2<i16>) {
+        self.set_data(TouchData {
+            point,
+            event: TouchEvent::Press,
+        })
+        .await;
+    }
+
+    /// Releases the screen at the current point.
+    pub async fn release(&mut self) {
+        self.set_event(TouchEvent::Release).await;
+    }
+
+    /// Performs a gesture on the screen, returning once it has finished.
+    pub async fn perform(&mut self, gesture: Gesture) {
+        match gesture {
+            Gesture::Tap(point) => {
+                self.press(point).await;
+                sleep(Self::TAP_DURATION).await;
+                self.release().await;
+            }
+            Gesture::LongPress { point, duration } => {
+                self.press(point).await;
+                sleep(duration).await;
+                self.release().await;
+            }
+            Gesture::Swipe { path, duration } => {
+                let Some(&start) = path.first() else {
+                    return;
+                };
+
+                self.press(start).await;
+
+                let steps = (duration.as_millis() / Self::UPDATE_INTERVAL.as_millis()).max(1);
+                let mut interval = interval(
+                    (duration / u32::try_from(steps).unwrap_or(u32::MAX))
+                        .max(Duration::from_millis(1)),
+                );
+                interval.tick().await;
+
+                for step in 1..=steps {
+                    interval.tick().await;
+                    let point = point_along_path(&path, step as f32 / steps as f32);
+                    self.set_point(point).await;
+                }
+
+                self.release().await;
+            }
+        }
+    }
+}
+
+/// Returns the point `fraction` of the way along `path`, measured by distance.
+fn point_along_path(path: &[Point2<i16>], fraction: f32) -> Point2<i16> {
+    let segments = path.windows(2).map(|segment| {
+        let (a, b) = (segment[0], segment[1]);
+        let length = f32::hypot(
+            f32::from(b.x) - f32::from(a.x),
+            f32::from(b.y) - f32::from(a.y),
+        );
+        (a, b, length)
+    });
+
+    let mut remaining = segments.clone().map(|(_, _, length)| length).sum::<f32>() * fraction;
+    for (a, b, length) in segments {
+        if remaining <= length && length > 0.0 {
+            let t = remaining / length;
+            return Point2 {
+                x: (f32::from(a.x) + (f32::from(b.x) - f32::from(a.x)) * t).round() as i16,
+                y: (f32::from(a.y) + (f32::from(b.y) - f32::from(a.y)) * t).round() as i16,
+            };
+        }
+        remaining -= length;
     }
+
+    *path.last().unwrap()
 }
 
 impl Drop for Touchscreen {
@@ -63,3 +193,108 @@ impl Drop for Touchscreen {
         self.task.abort();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    const fn point(x: i16, y: i16) -> Point2<i16> {
+        Point2 { x, y }
+    }
+
+    #[test]
+    fn path_endpoints() {
+        let path = [point(0, 0), point(100, 0)];
+
+        assert_eq!(point_along_path(&path, 0.0), point(0, 0));
+        assert_eq!(point_along_path(&path, 0.5), point(50, 0));
+        assert_eq!(point_along_path(&path, 1.0), point(100, 0));
+    }
+
+    #[test]
+    fn path_with_multiple_segments() {
+        let path = [point(0, 0), point(30, 0), point(30, 10)];
+
+        assert_eq!(point_along_path(&path, 0.25), point(10, 0));
+        assert_eq!(point_along_path(&path, 0.75), point(30, 0));
+        assert_eq!(point_along_path(&path, 0.875), point(30, 5));
+    }
+
+    #[test]
+    fn path_with_zero_length_segments() {
+        let path = [point(0, 0), point(0, 0), point(0, 20), point(0, 20)];
+
+        assert_eq!(point_along_path(&path, 0.0), point(0, 0));
+        assert_eq!(point_along_path(&path, 0.5), point(0, 10));
+        assert_eq!(point_along_path(&path, 1.0), point(0, 20));
+    }
+
+    #[test]
+    fn path_with_one_point() {
+        let path = [point(5, 5)];
+
+        assert_eq!(point_along_path(&path, 0.0), point(5, 5));
+        assert_eq!(point_along_path(&path, 1.0), point(5, 5));
+    }
+
+    #[tokio::test]
+    async fn held_is_only_sent_once_while_still() {
+        let (tx, mut rx) = mpsc::channel(1024);
+        let mut touch = Touchscreen::new(tx);
+
+        touch.press(point(10, 10)).await;
+        sleep(Touchscreen::UPDATE_INTERVAL * 10).await;
+        drop(touch);
+
+        let mut events = Vec::new();
+        while let Ok(KernelBoundPacket::Touch(data)) = rx.try_recv() {
+            events.push(data.event);
+        }
+        assert_eq!(events, [TouchEvent::Press, TouchEvent::Held]);
+    }
+
+    #[tokio::test]
+    async fn swipe_presses_drags_and_releases() {
+        let (tx, mut rx) = mpsc::channel(1024);
+        let mut touch = Touchscreen::new(tx);
+
+        touch
+            .perform(Gesture::Swipe {
+                path: vec![point(0, 0), point(100, 0)],
+                duration: Duration::from_millis(50),
+            })
+            .await;
+        drop(touch);
+
+        let mut events = Vec::new();
+        while let Ok(KernelBoundPacket::Touch(data)) = rx.try_recv() {
+            events.push(data);
+        }
+
+        let (first, rest) = events.split_first().unwrap();
+        let (last, held) = rest.split_last().unwrap();
+        assert_eq!(
+            *first,
+            TouchData {
+                event: TouchEvent::Press,
+                point: point(0, 0),
+            }
+        );
+        assert_eq!(
+            *last,
+            TouchData {
+                event: TouchEvent::Release,
+                point: point(100, 0),
+            }
+        );
+
+        assert!(!held.is_empty());
+        assert!(held.iter().all(|data| data.event == TouchEvent::Held));
+        assert!(held
+            .windows(2)
+            .all(|pair| pair[0].point.x <= pair[1].point.x));
+        assert_eq!(held.last().unwrap().point, point(100, 0));
+    }
+}
@@ -0,0 +1,166 @@
+//! A minimal client for the QEMU Machine Protocol (QMP), which is used to
+//! control the virtual machine while it is running.
+
+use std::{io, time::Duration};
+
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpListener,
+    },
+    time::timeout,
+};
+
+/// How long to wait for QEMU to connect to the QMP socket.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A QMP connection to a QEMU process.
+///
+/// QEMU is started as a client that connects to a socket we listen on, so
+/// there's no race between QEMU opening a port and us connecting to it.
+#[derive(Debug)]
+pub struct Qmp {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl Qmp {
+    /// Waits for QEMU to connect to `listener` and negotiates capabilities.
+    pub async fn accept(listener: &TcpListener) -> io::Result<Self> {
+        let (stream, _) = timeout(CONNECT_TIMEOUT, listener.accept())
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "QEMU didn't connect to QMP"))??;
+        let (reader, writer) = stream.into_split();
+
+        let mut qmp = Self {
+            reader: BufReader::new(reader),
+            writer,
+        };
+
+        // QEMU greets us, then waits for us to leave capabilities negotiation mode
+        // before it accepts any other commands.
+        qmp.read_message().await?;
+        qmp.execute("qmp_capabilities").await?;
+
+        Ok(qmp)
+    }
+
+    async fn read_message(&mut self) -> io::Result<Value> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        serde_json::from_str(&line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Runs a QMP command, returning its result.
+    pub async fn execute(&mut self, command: &str) -> io::Result<Value> {
+        let mut message = json!({ "execute": command }).to_string();
+        message.push('\n');
+        self.writer.write_all(message.as_bytes()).await?;
+
+        loop {
+            let mut response = self.read_message().await?;
+
+            // Asynchronous events can arrive before the response, but we have no
+            // use for them.
+            if let Some(result) = response.get_mut("return") {
+                return Ok(result.take());
+            }
+            if let Some(error) = response.get("error") {
+                let description = error["desc"].as_str().unwrap_or("unknown error");
+                return Err(io::Error::other(format!(
+                    "QMP command `{command}` failed: {description}"
+                )));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+    };
+
+    use super::*;
+
+    /// Pretends to be QEMU, answering each command it receives with the next
+    /// group of lines from `replies`.
+    async fn fake_qemu(listener: &TcpListener, replies: &'static [&'static [&'static str]]) {
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (reader, mut writer) = TcpStream::connect(addr).await.unwrap().into_split();
+            let mut lines = BufReader::new(reader).lines();
+
+            writer
+                .write_all(b"{\"QMP\": {\"version\": {}, \"capabilities\": []}}\n")
+                .await
+                .unwrap();
+
+            for reply in replies {
+                lines.next_line().await.unwrap().unwrap();
+                for line in *reply {
+                    writer
+                        .write_all(format!("{line}\n").as_bytes())
+                        .await
+                        .unwrap();
+                }
+            }
+        });
+    }
+
+    #[tokio::test]
+    async fn commands_skip_events_and_return_results() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        fake_qemu(
+            &listener,
+            &[
+                &["{\"return\": {}}"],
+                &[
+                    "{\"event\": \"STOP\", \"timestamp\": {}}",
+                    "{\"return\": {\"running\": false}}",
+                ],
+            ],
+        )
+        .await;
+
+        let mut qmp = Qmp::accept(&listener).await.unwrap();
+        assert_eq!(
+            qmp.execute("query-status").await.unwrap(),
+            json!({ "running": false })
+        );
+    }
+
+    #[tokio::test]
+    async fn errors_are_returned() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        fake_qemu(
+            &listener,
+            &[
+                &["{\"return\": {}}"],
+                &["{\"error\": {\"class\": \"CommandNotFound\", \"desc\": \"no such command\"}}"],
+            ],
+        )
+        .await;
+
+        let mut qmp = Qmp::accept(&listener).await.unwrap();
+        let err = qmp.execute("bogus").await.unwrap_err();
+        assert!(err.to_string().contains("no such command"));
+    }
+
+    #[tokio::test]
+    async fn closed_connections_are_errors() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        fake_qemu(&listener, &[&["{\"return\": {}}"]]).await;
+
+        let mut qmp = Qmp::accept(&listener).await.unwrap();
+        let err = qmp.execute("stop").await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
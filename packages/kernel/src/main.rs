@@ -20,9 +20,15 @@ use log::LevelFilter;
 use logger::KernelLogger;
 use peripherals::{GIC, PRIVATE_TIMER, UART1, WATCHDOG_TIMER};
 use sdk::vexSystemLinkAddrGet;
-use vex_v5_qemu_protocol::{code_signature::CodeSignature, HostBoundPacket};
+use vex_v5_qemu_protocol::{
+    code_signature::{CodeSignature, ProgramFlags},
+    HostBoundPacket,
+};
 
-use crate::{protocol::exit, sdk::{draw_error_box, vexTasksRun}};
+use crate::{
+    protocol::exit,
+    sdk::{draw_error_box, set_kill_tasks_on_exit, vexTasksRun},
+};
 
 extern "C" {
     /// Entrypoint of the user program. (located at 0x03800020)
@@ -117,6 +123,12 @@ pub extern "C" fn _start() -> ! {
         exit(102);
     });
 
+    set_kill_tasks_on_exit(
+        code_signature
+            .flags
+            .contains(ProgramFlags::KILL_TASKS_ON_EXIT),
+    );
+
     // Send user code signature to host.
     log::debug!("Sending code signature to host.");
     protocol::send_packet(HostBoundPacket::CodeSignature(code_signature)).unwrap();
@@ -6,7 +6,7 @@ use semihosting::io::{stdout, Write};
 use snafu::Snafu;
 use vex_v5_qemu_protocol::{HostBoundPacket, KernelBoundPacket};
 
-use crate::{peripherals::UART1, protocol};
+use crate::{peripherals::UART1, protocol, sdk::handle_packet};
 
 #[derive(Debug, Snafu)]
 pub enum ProtocolError {
@@ -71,6 +71,27 @@ pub fn recv_packet() -> Result<Option<KernelBoundPacket>, ProtocolError> {
     ))
 }
 
+/// Sends a packet that the host replies to and waits for the reply, which
+/// `reply` returns as `Ok`.
+///
+/// Any other packets that arrive in the meantime are handled as usual.
+pub fn request<T>(
+    packet: HostBoundPacket,
+    mut reply: impl FnMut(KernelBoundPacket) -> Result<T, KernelBoundPacket>,
+) -> T {
+    _ = send_packet(packet);
+
+    loop {
+        match recv_packet().unwrap() {
+            Some(packet) => match reply(packet) {
+                Ok(reply) => return reply,
+                Err(packet) => handle_packet(packet),
+            },
+            None => core::hint::spin_loop(),
+        }
+    }
+}
+
 pub fn exit(code: i32) -> ! {
     _ = protocol::send_packet(HostBoundPacket::ExitRequest(code));
 
@@ -3,37 +3,76 @@
 use core::ffi::c_double;
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::rotation_sensor::{RotationSensorCommand, RotationSensorData};
 
-pub extern "C" fn vexDeviceAbsEncReset(device: V5_DeviceT) {}
+use super::{send_device_command, with_device};
 
-pub extern "C" fn vexDeviceAbsEncPositionSet(device: V5_DeviceT, position: i32) {}
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncReset(device: V5_DeviceT) {
+    unsafe { send_device_command(device, RotationSensorCommand::Reset) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncPositionSet(device: V5_DeviceT, position: i32) {
+    unsafe { send_device_command(device, RotationSensorCommand::SetPosition(position)) }
+}
 
-pub extern "C" fn vexDeviceAbsEncPositionGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncPositionGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &RotationSensorData| data.position) }.unwrap_or_default()
 }
 
-pub extern "C" fn vexDeviceAbsEncVelocityGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncVelocityGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &RotationSensorData| data.velocity) }.unwrap_or_default()
 }
 
-pub extern "C" fn vexDeviceAbsEncAngleGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncAngleGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &RotationSensorData| data.angle) }.unwrap_or_default()
 }
 
-pub extern "C" fn vexDeviceAbsEncReverseFlagSet(device: V5_DeviceT, value: bool) {
-    Default::default()
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncReverseFlagSet(device: V5_DeviceT, value: bool) {
+    unsafe { send_device_command(device, RotationSensorCommand::SetReversed(value)) }
 }
 
-pub extern "C" fn vexDeviceAbsEncReverseFlagGet(device: V5_DeviceT) -> bool {
-    Default::default()
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncReverseFlagGet(device: V5_DeviceT) -> bool {
+    unsafe { with_device(device, |data: &RotationSensorData| data.reversed) }.unwrap_or_default()
 }
 
-pub extern "C" fn vexDeviceAbsEncStatusGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncStatusGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &RotationSensorData| data.status) }.unwrap_or_default()
 }
 
-pub extern "C" fn vexDeviceAbsEncTemperatureGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncTemperatureGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &RotationSensorData| data.temperature) }.unwrap_or_default()
 }
 
-pub extern "C" fn vexDeviceAbsEncDataRateSet(device: V5_DeviceT, rate: u32) {}
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAbsEncDataRateSet(device: V5_DeviceT, rate: u32) {
+    unsafe { send_device_command(device, RotationSensorCommand::SetDataRate(rate)) }
+}
@@ -1,24 +1,110 @@
+//! Three-wire (ADI) Ports
+
+use alloc::vec::Vec;
 use core::ffi::c_double;
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::adi::{AdiCommand, AdiData, AdiPortConfig};
+
+use super::{send_device_command, three_wire_port, with_device};
+
+/// Calls `f` with the value of the three-wire port that `device` refers to, if
+/// it has one of the given configurations.
+///
+/// This is used by the legacy device functions, which don't take a port
+/// number. Instead, they're given the handle of a single three-wire port from
+/// [`vexDeviceGetByIndex`](super::vexDeviceGetByIndex).
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+unsafe fn with_legacy_port<T>(
+    device: V5_DeviceT,
+    configs: &[AdiPortConfig],
+    f: impl FnOnce(u8, i32) -> T,
+) -> Option<T> {
+    let port = unsafe { three_wire_port(device) }?;
+
+    unsafe {
+        with_device(device, |data: &AdiData| {
+            let value = data.ports.get(port as usize)?;
+            configs
+                .contains(&value.config)
+                .then(|| f(port, value.value))
+        })
+    }
+    .flatten()
+}
 
-pub extern "C" fn vexDeviceAdiPortConfigSet(
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAdiPortConfigSet(
     device: V5_DeviceT,
     port: u32,
     config: V5_AdiPortConfiguration,
 ) {
+    unsafe {
+        send_device_command(
+            device,
+            AdiCommand::SetConfig {
+                port: port as u8,
+                config: config.into(),
+            },
+        )
+    }
 }
-pub extern "C" fn vexDeviceAdiPortConfigGet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAdiPortConfigGet(
     device: V5_DeviceT,
     port: u32,
 ) -> V5_AdiPortConfiguration {
-    Default::default()
+    unsafe {
+        with_device(device, |data: &AdiData| {
+            data.ports.get(port as usize).map(|port| port.config)
+        })
+    }
+    .flatten()
+    .unwrap_or(AdiPortConfig::Undefined)
+    .into()
 }
-pub extern "C" fn vexDeviceAdiValueSet(device: V5_DeviceT, port: u32, value: i32) {}
-pub extern "C" fn vexDeviceAdiValueGet(device: V5_DeviceT, port: u32) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAdiValueSet(device: V5_DeviceT, port: u32, value: i32) {
+    unsafe {
+        send_device_command(
+            device,
+            AdiCommand::SetValue {
+                port: port as u8,
+                value,
+            },
+        )
+    }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAdiValueGet(device: V5_DeviceT, port: u32) -> i32 {
+    unsafe {
+        with_device(device, |data: &AdiData| {
+            data.ports.get(port as usize).map(|port| port.value)
+        })
+    }
+    .flatten()
+    .unwrap_or_default()
 }
-pub extern "C" fn vexDeviceAdiAddrLedSet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `pData` must point to at least `nLength` readable [`u32`]s
+pub unsafe extern "C" fn vexDeviceAdiAddrLedSet(
     device: V5_DeviceT,
     port: u32,
     pData: *mut u32,
@@ -26,17 +112,83 @@ pub extern "C" fn vexDeviceAdiAddrLedSet(
     nLength: u32,
     options: u32,
 ) {
+    let colors = unsafe { core::slice::from_raw_parts(pData, nLength as usize) };
+
+    unsafe {
+        send_device_command(
+            device,
+            AdiCommand::SetLeds {
+                port: port as u8,
+                offset: nOffset,
+                colors: Vec::from(colors),
+            },
+        )
+    }
 }
-pub extern "C" fn vexDeviceBumperGet(device: V5_DeviceT) -> V5_DeviceBumperState {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceBumperGet(device: V5_DeviceT) -> V5_DeviceBumperState {
+    let pressed = unsafe {
+        with_legacy_port(
+            device,
+            &[
+                AdiPortConfig::LegacyButton,
+                AdiPortConfig::SmartButton,
+                AdiPortConfig::DigitalIn,
+            ],
+            |_, value| value != 0,
+        )
+    }
+    .unwrap_or_default();
+
+    if pressed {
+        V5_DeviceBumperState::kBumperPressed
+    } else {
+        V5_DeviceBumperState::kBumperReleased
+    }
 }
-pub extern "C" fn vexDeviceGyroReset(device: V5_DeviceT) {}
-pub extern "C" fn vexDeviceGyroHeadingGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGyroReset(device: V5_DeviceT) {
+    if let Some(port) =
+        unsafe { with_legacy_port(device, &[AdiPortConfig::LegacyGyro], |port, _| port) }
+    {
+        unsafe { send_device_command(device, AdiCommand::SetValue { port, value: 0 }) }
+    }
 }
-pub extern "C" fn vexDeviceGyroDegreesGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGyroHeadingGet(device: V5_DeviceT) -> c_double {
+    unsafe {
+        with_legacy_port(device, &[AdiPortConfig::LegacyGyro], |_, value| {
+            value as c_double / 10.0
+        })
+    }
+    .unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGyroDegreesGet(device: V5_DeviceT) -> c_double {
+    unsafe {
+        with_legacy_port(device, &[AdiPortConfig::LegacyGyro], |_, value| {
+            value.rem_euclid(3600) as c_double / 10.0
+        })
+    }
+    .unwrap_or_default()
 }
-pub extern "C" fn vexDeviceSonarValueGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceSonarValueGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_legacy_port(device, &[AdiPortConfig::Sonar], |_, value| value) }
+        .unwrap_or_default()
 }
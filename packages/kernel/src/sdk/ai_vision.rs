@@ -3,57 +3,208 @@
 use core::ffi::c_double;
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::ai_vision::{AiVisionCommand, AiVisionData};
 
-pub extern "C" fn vexDeviceAiVisionClassNameGet(
+use super::{send_device_command, with_device};
+
+/// Size of the buffer that class names are written to, including the null
+/// terminator.
+const CLASS_NAME_LEN: usize = 20;
+
+/// Writes the null-terminated name of the AI model class with the given `id`
+/// to `pName`, truncating it if it doesn't fit.
+///
+/// Returns the length of the name written, or 0 if there is no such class.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `pName` must be valid for writes of 20 bytes
+pub unsafe extern "C" fn vexDeviceAiVisionClassNameGet(
     device: V5_DeviceT,
     id: i32,
     pName: *mut u8,
 ) -> i32 {
-    Default::default()
+    unsafe {
+        with_device(device, |data: &AiVisionData| {
+            let Some(name) = usize::try_from(id)
+                .ok()
+                .and_then(|id| data.class_names.get(id))
+            else {
+                return 0;
+            };
+
+            let len = name.len().min(CLASS_NAME_LEN - 1);
+            pName.copy_from_nonoverlapping(name.as_ptr(), len);
+            pName.add(len).write(0);
+
+            len as i32
+        })
+    }
+    .unwrap_or_default()
 }
-pub extern "C" fn vexDeviceAiVisionCodeGet(
+
+/// Writes the color code with the given `id` to `pCode`.
+///
+/// Returns false if the code hasn't been configured.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `pCode` must be a valid pointer to a [`V5_DeviceAiVisionCode`]
+pub unsafe extern "C" fn vexDeviceAiVisionCodeGet(
     device: V5_DeviceT,
     id: u32,
     pCode: *mut V5_DeviceAiVisionCode,
 ) -> bool {
-    Default::default()
+    let Some(code) = unsafe {
+        with_device(device, |data: &AiVisionData| {
+            data.codes.iter().find(|code| code.id as u32 == id).cloned()
+        })
+    }
+    .flatten() else {
+        return false;
+    };
+
+    unsafe { pCode.write_unaligned(code.into()) }
+
+    true
 }
-pub extern "C" fn vexDeviceAiVisionCodeSet(device: V5_DeviceT, pCode: *mut V5_DeviceAiVisionCode) {}
-pub extern "C" fn vexDeviceAiVisionColorGet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `pCode` must be a valid pointer to a [`V5_DeviceAiVisionCode`]
+pub unsafe extern "C" fn vexDeviceAiVisionCodeSet(
+    device: V5_DeviceT,
+    pCode: *mut V5_DeviceAiVisionCode,
+) {
+    let code = unsafe { pCode.read_unaligned() };
+
+    unsafe { send_device_command(device, AiVisionCommand::SetCode(code.into())) }
+}
+
+/// Writes the color with the given `id` to `pColor`.
+///
+/// Returns false if the color hasn't been configured.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `pColor` must be a valid pointer to a [`V5_DeviceAiVisionColor`]
+pub unsafe extern "C" fn vexDeviceAiVisionColorGet(
     device: V5_DeviceT,
     id: u32,
     pColor: *mut V5_DeviceAiVisionColor,
 ) -> bool {
-    Default::default()
+    let Some(color) = unsafe {
+        with_device(device, |data: &AiVisionData| {
+            data.colors
+                .iter()
+                .find(|color| color.id as u32 == id)
+                .copied()
+        })
+    }
+    .flatten() else {
+        return false;
+    };
+
+    unsafe { pColor.write_unaligned(color.into()) }
+
+    true
 }
-pub extern "C" fn vexDeviceAiVisionColorSet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `pColor` must be a valid pointer to a [`V5_DeviceAiVisionColor`]
+pub unsafe extern "C" fn vexDeviceAiVisionColorSet(
     device: V5_DeviceT,
     pColor: *mut V5_DeviceAiVisionColor,
 ) {
+    let color = unsafe { pColor.read_unaligned() };
+
+    unsafe { send_device_command(device, AiVisionCommand::SetColor(color.into())) }
 }
-pub extern "C" fn vexDeviceAiVisionModeGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAiVisionModeGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &AiVisionData| data.mode) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAiVisionModeSet(device: V5_DeviceT, mode: u32) {
+    unsafe { send_device_command(device, AiVisionCommand::SetMode(mode)) }
 }
-pub extern "C" fn vexDeviceAiVisionModeSet(device: V5_DeviceT, mode: u32) {}
-pub extern "C" fn vexDeviceAiVisionObjectCountGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAiVisionObjectCountGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &AiVisionData| data.objects.len() as i32) }
+        .unwrap_or_default()
 }
-pub extern "C" fn vexDeviceAiVisionObjectGet(
+
+/// Writes the object at `indexObj` to `pObject`.
+///
+/// Returns 1 if there was an object at `indexObj`, or 0 otherwise.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `pObject` must be a valid pointer to a [`V5_DeviceAiVisionObject`]
+pub unsafe extern "C" fn vexDeviceAiVisionObjectGet(
     device: V5_DeviceT,
     indexObj: u32,
     pObject: *mut V5_DeviceAiVisionObject,
 ) -> i32 {
-    Default::default()
+    let Some(object) = unsafe {
+        with_device(device, |data: &AiVisionData| {
+            data.objects.get(indexObj as usize).copied()
+        })
+    }
+    .flatten() else {
+        return 0;
+    };
+
+    unsafe { pObject.write_unaligned(object.into()) }
+
+    1
 }
-pub extern "C" fn vexDeviceAiVisionSensorSet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAiVisionSensorSet(
     device: V5_DeviceT,
     brightness: c_double,
     contrast: c_double,
 ) {
+    unsafe {
+        send_device_command(
+            device,
+            AiVisionCommand::SetSensor {
+                brightness,
+                contrast,
+            },
+        )
+    }
 }
-pub extern "C" fn vexDeviceAiVisionStatusGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAiVisionStatusGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &AiVisionData| data.status) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceAiVisionTemperatureGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceAiVisionTemperatureGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &AiVisionData| data.temperature) }.unwrap_or_default()
 }
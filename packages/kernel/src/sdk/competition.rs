@@ -1,6 +1,42 @@
 //! Competition Control
 
+use vex_v5_qemu_protocol::competition::{CompetitionConnection, CompetitionData, CompetitionMode};
+
+use crate::sync::Mutex;
+
+pub static COMPETITION: Mutex<CompetitionData> = Mutex::new(CompetitionData {
+    mode: CompetitionMode::Driver,
+    connection: None,
+});
+
+/// The robot is disabled.
+const STATUS_DISABLED: u32 = 1 << 0;
+
+/// The robot is in autonomous mode.
+const STATUS_AUTONOMOUS: u32 = 1 << 1;
+
+/// The brain is connected to a competition controller.
+const STATUS_CONNECTED: u32 = 1 << 2;
+
+/// The competition controller is a field controller rather than a
+/// competition switch.
+const STATUS_FIELD_CONTROL: u32 = 1 << 3;
+
 pub extern "C" fn vexCompetitionStatus() -> u32 {
-    Default::default()
+    let data = *COMPETITION.lock();
+
+    let mode = match data.mode {
+        CompetitionMode::Disabled => STATUS_DISABLED,
+        CompetitionMode::Autonomous => STATUS_AUTONOMOUS,
+        CompetitionMode::Driver => 0,
+    };
+
+    let connection = match data.connection {
+        Some(CompetitionConnection::FieldControl) => STATUS_CONNECTED | STATUS_FIELD_CONTROL,
+        Some(CompetitionConnection::CompetitionSwitch) => STATUS_CONNECTED,
+        None => 0,
+    };
+
+    mode | connection
 }
 pub extern "C" fn vexCompetitionControl(data: u32) {}
@@ -1,12 +1,79 @@
 //! V5 Controller
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::controller::{ControllerData, ControllerId};
+
+use crate::sync::Mutex;
+
+pub static CONTROLLERS: [Mutex<Controller>; 2] =
+    [Mutex::new(Controller::new()), Mutex::new(Controller::new())];
+
+pub struct Controller {
+    /// The controller's most recent state, or `None` if it isn't connected.
+    pub data: Option<ControllerData>,
+    pub timestamp: u32,
+}
+
+impl Controller {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            data: None,
+            timestamp: 0,
+        }
+    }
+
+    pub fn update(&mut self, data: Option<ControllerData>, timestamp: u32) {
+        self.data = data;
+        self.timestamp = timestamp;
+    }
+
+    /// Returns the controller with the given ID.
+    pub const fn get(id: ControllerId) -> &'static Mutex<Controller> {
+        match id {
+            ControllerId::Master => &CONTROLLERS[0],
+            ControllerId::Partner => &CONTROLLERS[1],
+        }
+    }
+}
 
 pub extern "C" fn vexControllerGet(id: V5_ControllerId, index: V5_ControllerIndex) -> i32 {
-    Default::default()
+    let Some(Some(data)) = CONTROLLERS.get(id.0 as usize).map(|c| c.lock().data) else {
+        return 0;
+    };
+
+    match index {
+        V5_ControllerIndex::AnaRightX => data.axis_1 as i32,
+        V5_ControllerIndex::AnaRightY => data.axis_2 as i32,
+        V5_ControllerIndex::AnaLeftY => data.axis_3 as i32,
+        V5_ControllerIndex::AnaLeftX => data.axis_4 as i32,
+        V5_ControllerIndex::AnaSpare1 => data.axis_spare_1 as i32,
+        V5_ControllerIndex::AnaSpare2 => data.axis_spare_2 as i32,
+        V5_ControllerIndex::ButtonL1 => data.button_l1 as i32,
+        V5_ControllerIndex::ButtonL2 => data.button_l2 as i32,
+        V5_ControllerIndex::ButtonR1 => data.button_r1 as i32,
+        V5_ControllerIndex::ButtonR2 => data.button_r2 as i32,
+        V5_ControllerIndex::ButtonUp => data.button_up as i32,
+        V5_ControllerIndex::ButtonDown => data.button_down as i32,
+        V5_ControllerIndex::ButtonLeft => data.button_left as i32,
+        V5_ControllerIndex::ButtonRight => data.button_right as i32,
+        V5_ControllerIndex::ButtonX => data.button_x as i32,
+        V5_ControllerIndex::ButtonB => data.button_b as i32,
+        V5_ControllerIndex::ButtonY => data.button_y as i32,
+        V5_ControllerIndex::ButtonA => data.button_a as i32,
+        V5_ControllerIndex::ButtonSEL => data.button_sel as i32,
+        V5_ControllerIndex::BatteryLevel => data.battery_level as i32,
+        V5_ControllerIndex::ButtonAll => data.button_all as i32,
+        V5_ControllerIndex::Flags => data.flags as i32,
+        V5_ControllerIndex::BatteryCapacity => data.battery_capacity as i32,
+        _ => 0,
+    }
 }
 pub extern "C" fn vexControllerConnectionStatusGet(id: V5_ControllerId) -> V5_ControllerStatus {
-    Default::default()
+    match CONTROLLERS.get(id.0 as usize).map(|c| c.lock().data) {
+        Some(Some(_)) => V5_ControllerStatus::kV5ControllerTethered,
+        _ => V5_ControllerStatus::kV5ControllerOffline,
+    }
 }
 pub extern "C" fn vexControllerTextSet(id: u32, line: u32, col: u32, buf: *const u8) -> u32 {
     Default::default()
@@ -3,10 +3,23 @@
 use core::ffi::{c_double, c_int};
 
 use vex_sdk::*;
-use vex_v5_qemu_protocol::SmartPortData;
+use vex_v5_qemu_protocol::{
+    adi::{AdiData, ADI_PORT_COUNT},
+    ai_vision::AiVisionData,
+    distance_sensor::DistanceSensorData,
+    gps::GpsData,
+    imu::ImuData,
+    motor::MotorData,
+    optical_sensor::OpticalSensorData,
+    pneumatic::PneumaticData,
+    radio::RadioData,
+    rotation_sensor::RotationSensorData,
+    vision::VisionData,
+    HostBoundPacket, SmartPortCommand, SmartPortData,
+};
 
 use super::BATTERY;
-use crate::sync::Mutex;
+use crate::{protocol, sync::Mutex};
 
 pub static SMARTPORTS: [Mutex<SmartPort>; 21] = [
     Mutex::new(SmartPort::new(0)),
@@ -34,27 +47,50 @@ pub static SMARTPORTS: [Mutex<SmartPort>; 21] = [
 
 pub static ONBOARD_ADI: Mutex<SmartPort> = Mutex::new(SmartPort::new(21));
 
+/// The index passed to [`vexDeviceGetByIndex`] for the handle of the first
+/// individual three-wire port.
+///
+/// The legacy three-wire device functions (such as `vexDeviceGyroHeadingGet`)
+/// don't take a port number, so they're given a handle for a single port
+/// instead. Each smartport gets eight consecutive indices starting from
+/// `THREE_WIRE_DEVICE_INDEX + smartport * 8`, one for each of its three-wire
+/// ports, and the brain's onboard ports come after the last smartport.
+pub const THREE_WIRE_DEVICE_INDEX: u32 = 32;
+
 pub struct SmartPort {
     pub index: u8,
     pub timestamp: u32,
     pub data: Option<SmartPortData>,
     pub(crate) handle: V5_Device,
+    /// Handles for each of the three-wire ports on this smartport, which store
+    /// the three-wire port number plus one in `_unknown0`.
+    pub(crate) three_wire_handles: [V5_Device; ADI_PORT_COUNT],
 }
 
 impl SmartPort {
     pub const fn new(index: u8) -> Self {
+        let handle = V5_Device {
+            zero_indexed_port: index,
+            _unknown0: 0,
+            one_indexed_port: index + 1,
+            _unknown1_3: [0; 3],
+            device_type: V5_DeviceType::kDeviceTypeNoSensor,
+            installed: false,
+        };
+
+        let mut three_wire_handles = [handle; ADI_PORT_COUNT];
+        let mut port = 0;
+        while port < ADI_PORT_COUNT {
+            three_wire_handles[port]._unknown0 = port as u8 + 1;
+            port += 1;
+        }
+
         Self {
             index,
             data: None,
             timestamp: 0,
-            handle: V5_Device {
-                zero_indexed_port: index,
-                _unknown0: 0,
-                one_indexed_port: index + 1,
-                _unknown1_3: [0; 3],
-                device_type: V5_DeviceType::kDeviceTypeNoSensor,
-                installed: false,
-            },
+            handle,
+            three_wire_handles,
         }
     }
 
@@ -63,8 +99,127 @@ impl SmartPort {
         self.data = Some(data);
 
         let device_type = self.device_type();
-        self.handle.device_type = device_type;
-        self.handle.installed = device_type != V5_DeviceType::kDeviceTypeNoSensor;
+        let installed = device_type != V5_DeviceType::kDeviceTypeNoSensor;
+        for handle in core::iter::once(&mut self.handle).chain(&mut self.three_wire_handles) {
+            handle.device_type = device_type;
+            handle.installed = installed;
+        }
+    }
+}
+
+/// Returns the three-wire port that `device` refers to, if it is the handle
+/// of a single three-wire port rather than a whole device.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe fn three_wire_port(device: V5_DeviceT) -> Option<u8> {
+    unsafe { *device }._unknown0.checked_sub(1)
+}
+
+/// Data reported by one kind of smart device.
+pub trait DeviceData: Sized {
+    /// Returns the device's data if `data` was reported by this kind of device.
+    fn from_port_data(data: &SmartPortData) -> Option<&Self>;
+
+    /// Returns the device's data if `data` was reported by this kind of device.
+    fn from_port_data_mut(data: &mut SmartPortData) -> Option<&mut Self>;
+}
+
+macro_rules! impl_device_data {
+    ($($variant:ident($data:ty)),* $(,)?) => {
+        $(
+            impl DeviceData for $data {
+                fn from_port_data(data: &SmartPortData) -> Option<&Self> {
+                    match data {
+                        SmartPortData::$variant(data) => Some(data),
+                        _ => None,
+                    }
+                }
+
+                fn from_port_data_mut(data: &mut SmartPortData) -> Option<&mut Self> {
+                    match data {
+                        SmartPortData::$variant(data) => Some(data),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_device_data!(
+    DistanceSensor(DistanceSensorData),
+    Motor(MotorData),
+    Imu(ImuData),
+    RotationSensor(RotationSensorData),
+    OpticalSensor(OpticalSensorData),
+    Gps(GpsData),
+    Adi(AdiData),
+    Pneumatic(PneumaticData),
+    Vision(VisionData),
+    AiVision(AiVisionData),
+    Radio(RadioData),
+);
+
+/// Returns the port that `device` refers to.
+///
+/// The onboard ADI ports are addressed as the port after the last smartport.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+unsafe fn device_port(device: V5_DeviceT) -> Option<&'static Mutex<SmartPort>> {
+    let index = unsafe { *device }.zero_indexed_port as usize;
+
+    SMARTPORTS
+        .get(index)
+        .or((index == SMARTPORTS.len()).then_some(&ONBOARD_ADI))
+}
+
+/// Calls `f` with the data reported by the device that `device` refers to, if
+/// it is a `D`.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe fn with_device<D: DeviceData, T>(
+    device: V5_DeviceT,
+    f: impl FnOnce(&D) -> T,
+) -> Option<T> {
+    let port = unsafe { device_port(device) }?.lock();
+    port.data.as_ref().and_then(D::from_port_data).map(f)
+}
+
+/// Calls `f` with mutable access to the data reported by the device that
+/// `device` refers to, if it is a `D`.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe fn with_device_mut<D: DeviceData, T>(
+    device: V5_DeviceT,
+    f: impl FnOnce(&mut D) -> T,
+) -> Option<T> {
+    let mut port = unsafe { device_port(device) }?.lock();
+    port.data.as_mut().and_then(D::from_port_data_mut).map(f)
+}
+
+/// Sends a command to the device plugged into the smartport that `device`
+/// refers to.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe fn send_device_command(device: V5_DeviceT, command: impl Into<SmartPortCommand>) {
+    let port = unsafe { *device }.zero_indexed_port;
+
+    // The onboard ADI ports are addressed as the port after the last smartport.
+    if (port as usize) <= SMARTPORTS.len() {
+        _ = protocol::send_packet(HostBoundPacket::SmartPortCommand {
+            port,
+            command: command.into(),
+        });
     }
 }
 
@@ -88,6 +243,16 @@ impl Device for SmartPort {
         if let Some(data) = &self.data {
             match data {
                 SmartPortData::DistanceSensor(_) => V5_DeviceType::kDeviceTypeDistanceSensor,
+                SmartPortData::Motor(_) => V5_DeviceType::kDeviceTypeMotorSensor,
+                SmartPortData::Imu(_) => V5_DeviceType::kDeviceTypeImuSensor,
+                SmartPortData::RotationSensor(_) => V5_DeviceType::kDeviceTypeAbsEncSensor,
+                SmartPortData::OpticalSensor(_) => V5_DeviceType::kDeviceTypeOpticalSensor,
+                SmartPortData::Gps(_) => V5_DeviceType::kDeviceTypeGpsSensor,
+                SmartPortData::Adi(_) => V5_DeviceType::kDeviceTypeAdiSensor,
+                SmartPortData::Pneumatic(_) => V5_DeviceType::kDeviceTypePneumaticSensor,
+                SmartPortData::Vision(_) => V5_DeviceType::kDeviceTypeVisionSensor,
+                SmartPortData::AiVision(_) => V5_DeviceType::kDeviceTypeAiVisionSensor,
+                SmartPortData::Radio(_) => V5_DeviceType::kDeviceTypeRadioSensor,
                 // _ => V5_DeviceType::kDeviceTypeNoSensor,
             }
         } else {
@@ -130,6 +295,17 @@ pub extern "C" fn vexDeviceGetByIndex(index: u32) -> V5_DeviceT {
         0..=20 => SMARTPORTS[index as usize].lock().handle(),
         21 => ONBOARD_ADI.lock().handle(),
         24 => BATTERY.lock().handle(),
+        THREE_WIRE_DEVICE_INDEX.. => {
+            let index = (index - THREE_WIRE_DEVICE_INDEX) as usize;
+            let (port, three_wire_port) = (index / ADI_PORT_COUNT, index % ADI_PORT_COUNT);
+
+            let port = match SMARTPORTS.get(port) {
+                Some(port) => port,
+                None if port == SMARTPORTS.len() => &ONBOARD_ADI,
+                None => return core::ptr::null_mut(),
+            };
+            &mut port.lock().three_wire_handles[three_wire_port]
+        }
         _ => core::ptr::null_mut(),
     }
 }
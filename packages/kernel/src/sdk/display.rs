@@ -12,11 +12,14 @@ use core::{
 
 use vex_sdk::*;
 use vex_v5_qemu_protocol::{
-    display::{Color, DrawCommand, ScrollLocation, Shape, TextFont, TextSize},
+    display::{
+        Color, DrawCommand, ImageFormat, ScrollLocation, Shape, TextFont, TextMetrics, TextSize,
+    },
     geometry::{Point2, Rect},
-    DisplayCommand, HostBoundPacket,
+    DisplayCommand, HostBoundPacket, KernelBoundPacket,
 };
 
+use super::task::MAX_TASKS;
 use crate::{
     protocol::{self, ProtocolError},
     sync::Mutex,
@@ -48,7 +51,12 @@ pub static DISPLAY: Mutex<Display> = Mutex::new(Display::new(
 pub struct Display {
     foreground: Color,
     background: Color,
-    clip_region: Rect,
+    /// The clip region of the main program, followed by the clip region of
+    /// each task.
+    clip_regions: [Rect; MAX_TASKS + 1],
+    /// The index into `clip_regions` of the region used for drawing.
+    clip_slot: usize,
+    pen_size: u32,
     text_size: TextSize,
     font: TextFont,
 }
@@ -58,7 +66,9 @@ impl Display {
         Self {
             foreground,
             background,
-            clip_region,
+            clip_regions: [clip_region; MAX_TASKS + 1],
+            clip_slot: 0,
+            pen_size: 1,
             text_size: TextSize { num: 1, denom: 3 },
             font: TextFont::Monospace,
         }
@@ -72,9 +82,8 @@ impl Display {
         self.background
     }
 
-    #[allow(unused)]
     pub fn clip_region(&self) -> Rect {
-        self.clip_region
+        self.clip_regions[self.clip_slot]
     }
 
     pub fn set_foreground(&mut self, color: Color) {
@@ -86,7 +95,35 @@ impl Display {
     }
 
     pub fn set_clip_region(&mut self, rect: Rect) {
-        self.clip_region = rect;
+        self.clip_regions[self.clip_slot] = rect;
+    }
+
+    /// Sets the clip region of the task at `index`, ignoring indices that are
+    /// out of range.
+    pub fn set_clip_region_with_index(&mut self, index: usize, rect: Rect) {
+        if let Some(region) = index
+            .checked_add(1)
+            .and_then(|slot| self.clip_regions.get_mut(slot))
+        {
+            *region = rect;
+        }
+    }
+
+    /// Makes drawing use the clip region of the task at `index`, or the main
+    /// program's if `index` is `None`.
+    pub fn set_task_index(&mut self, index: Option<usize>) {
+        self.clip_slot = match index {
+            Some(index) if index < MAX_TASKS => index + 1,
+            _ => 0,
+        };
+    }
+
+    pub fn pen_size(&self) -> u32 {
+        self.pen_size
+    }
+
+    pub fn set_pen_size(&mut self, size: u32) {
+        self.pen_size = size;
     }
 
     pub fn set_text_size(&mut self, size: TextSize) {
@@ -101,7 +138,7 @@ impl Display {
         protocol::send_packet(HostBoundPacket::DisplayCommand {
             command: DisplayCommand::Erase {
                 color: self.background,
-                clip_region: self.clip_region,
+                clip_region: self.clip_region(),
             },
         })
     }
@@ -112,7 +149,7 @@ impl Display {
                 location,
                 lines,
                 background: self.background,
-                clip_region: self.clip_region,
+                clip_region: self.clip_region(),
             },
         })
     }
@@ -123,7 +160,8 @@ impl Display {
             command: DisplayCommand::Draw {
                 command: DrawCommand::Fill(shape),
                 color,
-                clip_region: self.clip_region,
+                pen_size: self.pen_size,
+                clip_region: self.clip_region(),
             },
         })
     }
@@ -142,7 +180,8 @@ impl Display {
             command: DisplayCommand::Draw {
                 command: DrawCommand::Stroke(shape),
                 color,
-                clip_region: self.clip_region,
+                pen_size: self.pen_size,
+                clip_region: self.clip_region(),
             },
         })
     }
@@ -172,7 +211,8 @@ impl Display {
                     buffer,
                 },
                 color: self.foreground,
-                clip_region: self.clip_region,
+                pen_size: self.pen_size,
+                clip_region: self.clip_region(),
             },
         })
     }
@@ -197,7 +237,8 @@ impl Display {
                     background,
                 },
                 color: foreground,
-                clip_region: self.clip_region,
+                pen_size: self.pen_size,
+                clip_region: self.clip_region(),
             },
         })
     }
@@ -213,6 +254,25 @@ impl Display {
     }
 }
 
+/// Asks the host how much space a string takes up when drawn in the current
+/// font and text size.
+fn measure_text(data: String) -> TextMetrics {
+    let (font, size) = {
+        let display = DISPLAY.lock();
+        (display.font, display.text_size)
+    };
+
+    protocol::request(
+        HostBoundPacket::DisplayCommand {
+            command: DisplayCommand::MeasureText { data, font, size },
+        },
+        |packet| match packet {
+            KernelBoundPacket::TextMetrics(metrics) => Ok(metrics),
+            packet => Err(packet),
+        },
+    )
+}
+
 pub fn draw_error_box(message: [Option<&str>; 3]) {
     let mut display = DISPLAY.lock();
     display
@@ -312,12 +372,8 @@ pub unsafe fn vexDisplayCopyRect(
 pub extern "C" fn vexDisplayPixelSet(x: u32, y: u32) {
     DISPLAY
         .lock()
-        .fill_with_foreground(Shape::Rectangle {
-            top_left: Point2 {
-                x: x as _,
-                y: y as _,
-            },
-            bottom_right: Point2 {
+        .fill_with_foreground(Shape::Pixel {
+            position: Point2 {
                 x: x as _,
                 y: y as _,
             },
@@ -327,12 +383,8 @@ pub extern "C" fn vexDisplayPixelSet(x: u32, y: u32) {
 pub extern "C" fn vexDisplayPixelClear(x: u32, y: u32) {
     DISPLAY
         .lock()
-        .fill_with_background(Shape::Rectangle {
-            top_left: Point2 {
-                x: x as _,
-                y: y as _,
-            },
-            bottom_right: Point2 {
+        .fill_with_background(Shape::Pixel {
+            position: Point2 {
                 x: x as _,
                 y: y as _,
             },
@@ -445,22 +497,36 @@ pub extern "C" fn vexDisplayBackgroundColorGet() -> u32 {
     DISPLAY.lock().background().0
 }
 
+/// Returns the width in pixels of a string drawn in the current font and text
+/// size.
+///
 /// # Safety
 ///
 /// pString must satisfy the safety requirements outlined by [`CStr::from_ptr`].
 pub unsafe extern "C" fn vexDisplayStringWidthGet(pString: *const c_char) -> i32 {
-    (unsafe { CStr::from_ptr(pString) }.count_bytes() * 8) as _
+    let data = unsafe { CStr::from_ptr(pString) }
+        .to_string_lossy()
+        .into_owned();
+    measure_text(data).width as _
 }
 
+/// Returns the height in pixels of a string drawn in the current font and text
+/// size.
+///
 /// # Safety
 ///
 /// pString must satisfy the safety requirements outlined by [`CStr::from_ptr`].
 pub unsafe extern "C" fn vexDisplayStringHeightGet(pString: *const c_char) -> i32 {
-    15
+    let data = unsafe { CStr::from_ptr(pString) }
+        .to_string_lossy()
+        .into_owned();
+    measure_text(data).height as _
+}
+pub extern "C" fn vexDisplayPenSizeSet(width: u32) {
+    DISPLAY.lock().set_pen_size(width);
 }
-pub extern "C" fn vexDisplayPenSizeSet(width: u32) {}
 pub extern "C" fn vexDisplayPenSizeGet() -> u32 {
-    Default::default()
+    DISPLAY.lock().pen_size()
 }
 pub extern "C" fn vexDisplayClipRegionSet(x1: i32, y1: i32, x2: i32, y2: i32) {
     DISPLAY.lock().set_clip_region(Rect {
@@ -481,23 +547,124 @@ pub extern "C" fn vexDisplayDoubleBufferDisable() {
     .unwrap();
 }
 pub extern "C" fn vexDisplayClipRegionSetWithIndex(index: i32, x1: i32, y1: i32, x2: i32, y2: i32) {
+    let Ok(index) = usize::try_from(index) else {
+        return;
+    };
+
+    DISPLAY.lock().set_clip_region_with_index(
+        index,
+        Rect {
+            top_left: Point2 { x: x1, y: y1 },
+            bottom_right: Point2 { x: x2, y: y2 },
+        },
+    );
 }
-pub extern "C" fn vexImageBmpRead(
+/// Asks the host to decode an image and copies the result into `oBuf`,
+/// returning 1 if it succeeded or 0 if the image was invalid or larger than
+/// `maxw` by `maxh` pixels.
+///
+/// # Safety
+///
+/// `oBuf` must be null or point to a `v5_image` whose `data` field is null or
+/// valid for writes of `maxw * maxh` pixels.
+unsafe fn read_image(
+    format: ImageFormat,
+    data: Vec<u8>,
+    oBuf: *mut v5_image,
+    maxw: u32,
+    maxh: u32,
+) -> u32 {
+    let Some(out) = (unsafe { oBuf.as_mut() }) else {
+        return 0;
+    };
+    let out_data = out.data;
+    if out_data.is_null() {
+        return 0;
+    }
+
+    let image = protocol::request(
+        HostBoundPacket::DisplayCommand {
+            command: DisplayCommand::DecodeImage {
+                format,
+                data,
+                max_width: maxw,
+                max_height: maxh,
+            },
+        },
+        |packet| match packet {
+            KernelBoundPacket::DecodedImage(image) => Ok(image),
+            packet => Err(packet),
+        },
+    );
+    let Some(image) = image else {
+        return 0;
+    };
+
+    out.width = image.width;
+    out.height = image.height;
+    unsafe {
+        core::ptr::copy_nonoverlapping(image.pixels.as_ptr(), out_data, image.pixels.len());
+    }
+
+    1
+}
+
+/// Decodes a BMP file into `oBuf`, returning 1 if it succeeded or 0 if the
+/// file was invalid or larger than `maxw` by `maxh` pixels.
+///
+/// The size of the file is read from its header.
+///
+/// # Safety
+///
+/// - `ibuf` must be valid for reads of the size given in the file's header
+/// - `oBuf` must be null or point to a `v5_image` whose `data` field is null or
+///   valid for writes of `maxw * maxh` pixels.
+pub unsafe extern "C" fn vexImageBmpRead(
     ibuf: *const u8,
     oBuf: *mut v5_image,
     maxw: u32,
     maxh: u32,
 ) -> u32 {
-    Default::default()
+    const HEADER_SIZE: usize = 6;
+
+    if ibuf.is_null() {
+        return 0;
+    }
+
+    let header = unsafe { slice::from_raw_parts(ibuf, HEADER_SIZE) };
+    if &header[..2] != b"BM" {
+        return 0;
+    }
+    let len = u32::from_le_bytes(header[2..].try_into().unwrap()) as usize;
+    if len < HEADER_SIZE {
+        return 0;
+    }
+
+    let data = unsafe { slice::from_raw_parts(ibuf, len) }.to_vec();
+    unsafe { read_image(ImageFormat::Bmp, data, oBuf, maxw, maxh) }
 }
-pub extern "C" fn vexImagePngRead(
+
+/// Decodes a PNG file into `oBuf`, returning 1 if it succeeded or 0 if the
+/// file was invalid or larger than `maxw` by `maxh` pixels.
+///
+/// # Safety
+///
+/// - `ibuf` must be valid for reads of `ibuflen` bytes
+/// - `oBuf` must be null or point to a `v5_image` whose `data` field is null or
+///   valid for writes of `maxw * maxh` pixels.
+pub unsafe extern "C" fn vexImagePngRead(
     ibuf: *const u8,
     oBuf: *mut v5_image,
     maxw: u32,
     maxh: u32,
     ibuflen: u32,
 ) -> u32 {
-    Default::default()
+    if ibuf.is_null() {
+        return 0;
+    }
+
+    let data = unsafe { slice::from_raw_parts(ibuf, ibuflen as usize) }.to_vec();
+    unsafe { read_image(ImageFormat::Png, data, oBuf, maxw, maxh) }
 }
 pub unsafe extern "C" fn vexDisplayVPrintf(
     xpos: i32,
@@ -548,20 +715,30 @@ pub unsafe extern "C" fn vexDisplayVStringAt(
 pub unsafe extern "C" fn vexDisplayVBigString(
     nLineNumber: i32,
     format: *const c_char,
-    args: VaList<'_, '_>,
+    mut args: VaList<'_, '_>,
 ) {
-    todo!("measure line height for non-normal text sizes");
-    // unsafe {
-    //     display_string_impl(
-    //         TextSize::Large,
-    //         Point2 {
-    //             x: 0,
-    //             y: nLineNumber * ?? + 34,
-    //         },
-    //         format,
-    //         args,
-    //     );
-    // }
+    let mut data = String::new();
+    unsafe {
+        _ = printf_compat::format(
+            format,
+            args.as_va_list(),
+            printf_compat::output::fmt_write(&mut data),
+        );
+    }
+
+    DISPLAY.lock().set_text_size(BIG_TEXT);
+    let height = measure_text(data.clone()).height as i32;
+    DISPLAY
+        .lock()
+        .draw_text_with_foreground(
+            data,
+            Point2 {
+                x: 0,
+                y: nLineNumber * height + 34,
+            },
+            false,
+        )
+        .unwrap();
 }
 pub unsafe extern "C" fn vexDisplayVBigStringAt(
     xpos: i32,
@@ -588,16 +765,57 @@ pub unsafe extern "C" fn vexDisplayVSmallStringAt(
 pub unsafe extern "C" fn vexDisplayVCenteredString(
     nLineNumber: i32,
     format: *const c_char,
-    args: VaList<'_, '_>,
+    mut args: VaList<'_, '_>,
 ) {
-    todo!();
+    let mut data = String::new();
+    unsafe {
+        _ = printf_compat::format(
+            format,
+            args.as_va_list(),
+            printf_compat::output::fmt_write(&mut data),
+        );
+    }
+
+    let width = measure_text(data.clone()).width as i32;
+    DISPLAY
+        .lock()
+        .draw_text_with_foreground(
+            data,
+            Point2 {
+                x: (RESOLUTION_X - width) / 2,
+                y: nLineNumber * 20 + 34,
+            },
+            false,
+        )
+        .unwrap();
 }
 pub unsafe extern "C" fn vexDisplayVBigCenteredString(
     nLineNumber: i32,
     format: *const c_char,
-    args: VaList<'_, '_>,
+    mut args: VaList<'_, '_>,
 ) {
-    todo!("measure line height for non-normal text sizes");
+    let mut data = String::new();
+    unsafe {
+        _ = printf_compat::format(
+            format,
+            args.as_va_list(),
+            printf_compat::output::fmt_write(&mut data),
+        );
+    }
+
+    DISPLAY.lock().set_text_size(BIG_TEXT);
+    let metrics = measure_text(data.clone());
+    DISPLAY
+        .lock()
+        .draw_text_with_foreground(
+            data,
+            Point2 {
+                x: (RESOLUTION_X - metrics.width as i32) / 2,
+                y: nLineNumber * metrics.height as i32 + 34,
+            },
+            false,
+        )
+        .unwrap();
 }
 
 unsafe fn display_string_impl(point: Point2<i32>, format: *const c_char, mut args: VaList<'_, '_>) {
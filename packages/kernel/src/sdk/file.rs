@@ -1,48 +1,341 @@
 //! Filesystem Access
 
-use core::ffi::c_char;
+use alloc::{boxed::Box, string::String};
+use core::ffi::{c_char, CStr};
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::{
+    sd_card::{FileError, FileKind, FileRequest, FileResponse, OpenMode, SeekFrom},
+    HostBoundPacket, KernelBoundPacket,
+};
+
+use crate::protocol;
+
+/// An open file, pointed to by the `FIL` handles given to user programs.
+struct File {
+    fd: u32,
+}
+
+/// Asks the host to perform a filesystem operation on the SD card and waits
+/// for the result.
+fn request(request: FileRequest) -> Result<FileResponse, FileError> {
+    protocol::request(
+        HostBoundPacket::SdCardRequest(request),
+        |packet| match packet {
+            KernelBoundPacket::SdCardResponse(response) => Ok(response),
+            packet => Err(packet),
+        },
+    )
+}
+
+/// Converts a path passed by a user program to a string.
+///
+/// # Safety
+///
+/// `path` must satisfy the safety requirements outlined by [`CStr::from_ptr`].
+unsafe fn path_string(path: *const c_char) -> Option<String> {
+    if path.is_null() {
+        None
+    } else {
+        Some(
+            unsafe { CStr::from_ptr(path) }
+                .to_string_lossy()
+                .into_owned(),
+        )
+    }
+}
+
+/// Opens a file, returning a handle to it or null if it couldn't be opened.
+///
+/// # Safety
+///
+/// `filename` must satisfy the safety requirements outlined by
+/// [`CStr::from_ptr`].
+unsafe fn open(filename: *const c_char, mode: OpenMode) -> *mut FIL {
+    let Some(path) = (unsafe { path_string(filename) }) else {
+        return core::ptr::null_mut();
+    };
+
+    match request(FileRequest::Open { path, mode }) {
+        Ok(FileResponse::Opened { fd }) => Box::into_raw(Box::new(File { fd })).cast(),
+        _ => core::ptr::null_mut(),
+    }
+}
+
+/// Returns the descriptor of the file that `fdp` refers to.
+///
+/// # Safety
+///
+/// `fdp` must be null or a handle returned by one of the `vexFileOpen`
+/// functions that hasn't been closed.
+unsafe fn descriptor(fdp: *mut FIL) -> Option<u32> {
+    unsafe { fdp.cast::<File>().as_ref() }.map(|file| file.fd)
+}
 
 pub extern "C" fn vexFileMountSD() -> FRESULT {
-    Default::default()
+    match request(FileRequest::DriveStatus) {
+        Ok(FileResponse::DriveStatus { inserted: true }) => FRESULT::FR_OK,
+        Ok(_) => FRESULT::FR_NOT_READY,
+        Err(err) => err.into(),
+    }
 }
-pub extern "C" fn vexFileDirectoryGet(
+
+/// Writes the names of the entries in a directory to `buffer`, separated by
+/// newlines.
+///
+/// Names that don't fit in `len` bytes (including the null terminator) are
+/// left out.
+///
+/// # Safety
+///
+/// - `path` must satisfy the safety requirements outlined by [`CStr::from_ptr`]
+/// - `buffer` must be valid for writes of `len` bytes
+pub unsafe extern "C" fn vexFileDirectoryGet(
     path: *const c_char,
     buffer: *mut c_char,
     len: u32,
 ) -> FRESULT {
-    Default::default()
+    let Some(path) = (unsafe { path_string(path) }) else {
+        return FRESULT::FR_INVALID_PARAMETER;
+    };
+    if buffer.is_null() || len == 0 {
+        return FRESULT::FR_INVALID_PARAMETER;
+    }
+
+    let names = match request(FileRequest::ReadDirectory { path }) {
+        Ok(FileResponse::Directory(names)) => names,
+        Ok(_) => return FRESULT::FR_INT_ERR,
+        Err(err) => return err.into(),
+    };
+
+    let buffer = unsafe { core::slice::from_raw_parts_mut(buffer.cast::<u8>(), len as usize) };
+    let mut written = 0;
+    for name in names {
+        // Room is needed for the name, its separator and the null terminator.
+        let end = written + name.len() + 1;
+        if end >= buffer.len() {
+            break;
+        }
+
+        buffer[written..end - 1].copy_from_slice(name.as_bytes());
+        buffer[end - 1] = b'\n';
+        written = end;
+    }
+    buffer[written] = 0;
+
+    FRESULT::FR_OK
 }
-pub extern "C" fn vexFileOpen(filename: *const c_char, mode: *const c_char) -> *mut FIL {
-    core::ptr::null_mut()
+
+/// Opens an existing file for reading.
+///
+/// `mode` is ignored, as it is on VEXos.
+///
+/// # Safety
+///
+/// `filename` must satisfy the safety requirements outlined by
+/// [`CStr::from_ptr`].
+pub unsafe extern "C" fn vexFileOpen(filename: *const c_char, mode: *const c_char) -> *mut FIL {
+    unsafe { open(filename, OpenMode::Read) }
 }
-pub extern "C" fn vexFileOpenWrite(filename: *const c_char) -> *mut FIL {
-    core::ptr::null_mut()
+
+/// Opens a file for writing at its end, creating it if it doesn't exist.
+///
+/// # Safety
+///
+/// `filename` must satisfy the safety requirements outlined by
+/// [`CStr::from_ptr`].
+pub unsafe extern "C" fn vexFileOpenWrite(filename: *const c_char) -> *mut FIL {
+    unsafe { open(filename, OpenMode::Append) }
+}
+
+/// Creates a file for writing, replacing it if it already exists.
+///
+/// # Safety
+///
+/// `filename` must satisfy the safety requirements outlined by
+/// [`CStr::from_ptr`].
+pub unsafe extern "C" fn vexFileOpenCreate(filename: *const c_char) -> *mut FIL {
+    unsafe { open(filename, OpenMode::Create) }
 }
-pub extern "C" fn vexFileOpenCreate(filename: *const c_char) -> *mut FIL {
-    core::ptr::null_mut()
+
+/// # Safety
+///
+/// `fdp` must be null or a handle returned by one of the `vexFileOpen`
+/// functions that hasn't been closed.
+pub unsafe extern "C" fn vexFileClose(fdp: *mut FIL) {
+    if fdp.is_null() {
+        return;
+    }
+
+    let file = unsafe { Box::from_raw(fdp.cast::<File>()) };
+    _ = request(FileRequest::Close { fd: file.fd });
 }
-pub extern "C" fn vexFileClose(fdp: *mut FIL) {}
-pub extern "C" fn vexFileWrite(buf: *mut c_char, size: u32, nItems: u32, fdp: *mut FIL) -> i32 {
-    Default::default()
+
+/// Writes `nItems` items of `size` bytes each to the file, returning the
+/// number of bytes written.
+///
+/// # Safety
+///
+/// - `buf` must be valid for reads of `size * nItems` bytes
+/// - `fdp` must be null or a handle returned by one of the `vexFileOpen`
+///   functions that hasn't been closed.
+pub unsafe extern "C" fn vexFileWrite(
+    buf: *mut c_char,
+    size: u32,
+    nItems: u32,
+    fdp: *mut FIL,
+) -> i32 {
+    let Some(fd) = (unsafe { descriptor(fdp) }) else {
+        return 0;
+    };
+    if buf.is_null() {
+        return 0;
+    }
+
+    let len = size.saturating_mul(nItems) as usize;
+    let data = unsafe { core::slice::from_raw_parts(buf.cast::<u8>(), len) }.to_vec();
+
+    match request(FileRequest::Write { fd, data }) {
+        Ok(FileResponse::Written(written)) => written as i32,
+        _ => 0,
+    }
 }
-pub extern "C" fn vexFileSize(fdp: *mut FIL) -> i32 {
-    Default::default()
+
+/// Returns the size of the file in bytes, or -1 if it couldn't be determined.
+///
+/// # Safety
+///
+/// `fdp` must be null or a handle returned by one of the `vexFileOpen`
+/// functions that hasn't been closed.
+pub unsafe extern "C" fn vexFileSize(fdp: *mut FIL) -> i32 {
+    let Some(fd) = (unsafe { descriptor(fdp) }) else {
+        return -1;
+    };
+
+    match request(FileRequest::Size { fd }) {
+        Ok(FileResponse::Size(size)) => size as i32,
+        _ => -1,
+    }
 }
-pub extern "C" fn vexFileSeek(fdp: *mut FIL, offset: u32, whence: i32) -> FRESULT {
-    Default::default()
+
+/// Moves the file's position by `offset` bytes relative to the start of the
+/// file, the current position or the end of the file, depending on whether
+/// `whence` is `SEEK_SET`, `SEEK_CUR` or `SEEK_END`.
+///
+/// # Safety
+///
+/// `fdp` must be null or a handle returned by one of the `vexFileOpen`
+/// functions that hasn't been closed.
+pub unsafe extern "C" fn vexFileSeek(fdp: *mut FIL, offset: u32, whence: i32) -> FRESULT {
+    const SEEK_SET: i32 = 0;
+    const SEEK_CUR: i32 = 1;
+    const SEEK_END: i32 = 2;
+
+    let Some(fd) = (unsafe { descriptor(fdp) }) else {
+        return FRESULT::FR_INVALID_OBJECT;
+    };
+
+    // Offsets relative to the current position or the end of the file may be
+    // negative, but are passed as unsigned integers.
+    let position = match whence {
+        SEEK_SET => SeekFrom::Start(offset),
+        SEEK_CUR => SeekFrom::Current(offset as i32),
+        SEEK_END => SeekFrom::End(offset as i32),
+        _ => return FRESULT::FR_INVALID_PARAMETER,
+    };
+
+    match request(FileRequest::Seek { fd, position }) {
+        Ok(_) => FRESULT::FR_OK,
+        Err(err) => err.into(),
+    }
 }
-pub extern "C" fn vexFileRead(buf: *mut c_char, size: u32, nItems: u32, fdp: *mut FIL) -> i32 {
-    Default::default()
+
+/// Reads up to `nItems` items of `size` bytes each from the file, returning
+/// the number of bytes read.
+///
+/// # Safety
+///
+/// - `buf` must be valid for writes of `size * nItems` bytes
+/// - `fdp` must be null or a handle returned by one of the `vexFileOpen`
+///   functions that hasn't been closed.
+pub unsafe extern "C" fn vexFileRead(
+    buf: *mut c_char,
+    size: u32,
+    nItems: u32,
+    fdp: *mut FIL,
+) -> i32 {
+    let Some(fd) = (unsafe { descriptor(fdp) }) else {
+        return 0;
+    };
+    if buf.is_null() {
+        return 0;
+    }
+
+    let len = size.saturating_mul(nItems);
+    let data = match request(FileRequest::Read { fd, len }) {
+        Ok(FileResponse::Data(data)) => data,
+        _ => return 0,
+    };
+
+    let read = data.len().min(len as usize);
+    unsafe {
+        core::ptr::copy_nonoverlapping(data.as_ptr(), buf.cast::<u8>(), read);
+    }
+    read as i32
 }
+
+/// Returns whether an SD card is inserted.
+///
+/// `drive` is ignored, since the SD card is the only drive.
 pub extern "C" fn vexFileDriveStatus(drive: u32) -> bool {
-    Default::default()
+    matches!(
+        request(FileRequest::DriveStatus),
+        Ok(FileResponse::DriveStatus { inserted: true })
+    )
+}
+
+/// Returns the file's current position, or -1 if it couldn't be determined.
+///
+/// # Safety
+///
+/// `fdp` must be null or a handle returned by one of the `vexFileOpen`
+/// functions that hasn't been closed.
+pub unsafe extern "C" fn vexFileTell(fdp: *mut FIL) -> i32 {
+    let Some(fd) = (unsafe { descriptor(fdp) }) else {
+        return -1;
+    };
+
+    match request(FileRequest::Tell { fd }) {
+        Ok(FileResponse::Position(position)) => position as i32,
+        _ => -1,
+    }
 }
-pub extern "C" fn vexFileTell(fdp: *mut FIL) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// `fdp` must be null or a handle returned by one of the `vexFileOpen`
+/// functions that hasn't been closed.
+pub unsafe extern "C" fn vexFileSync(fdp: *mut FIL) {
+    if let Some(fd) = unsafe { descriptor(fdp) } {
+        _ = request(FileRequest::Sync { fd });
+    }
 }
-pub extern "C" fn vexFileSync(fdp: *mut FIL) {}
-pub extern "C" fn vexFileStatus(filename: *const c_char) -> u32 {
-    Default::default()
+
+/// Returns 0 if nothing exists at `filename`, 1 if it is a file, or 3 if it
+/// is a directory.
+///
+/// # Safety
+///
+/// `filename` must satisfy the safety requirements outlined by
+/// [`CStr::from_ptr`].
+pub unsafe extern "C" fn vexFileStatus(filename: *const c_char) -> u32 {
+    let Some(path) = (unsafe { path_string(filename) }) else {
+        return 0;
+    };
+
+    match request(FileRequest::Status { path }) {
+        Ok(FileResponse::Status(Some(FileKind::File))) => 1,
+        Ok(FileResponse::Status(Some(FileKind::Directory))) => 3,
+        _ => 0,
+    }
 }
@@ -1,36 +1,160 @@
 //! V5 Smart Radio
 
-use core::ffi::{c_char, c_int};
+use core::ffi::{c_char, c_int, CStr};
 
+use ringbuffer::{ConstGenericRingBuffer, RingBuffer};
 use vex_sdk::*;
+use vex_v5_qemu_protocol::radio::{RadioCommand, RadioData, RadioRole};
 
-pub extern "C" fn vexDeviceGenericRadioConnection(
+use super::{send_device_command, with_device_mut, SMARTPORTS};
+use crate::sync::Mutex;
+
+/// Size of each radio's receive buffer in bytes.
+const RX_BUFFER_SIZE: usize = 1024;
+
+/// Bytes received over VEXlink that haven't been read by the user program,
+/// for each smartport.
+pub static GENERIC_RADIO_BUFFERS: [Mutex<ConstGenericRingBuffer<u8, RX_BUFFER_SIZE>>;
+    SMARTPORTS.len()] = [const { Mutex::new(ConstGenericRingBuffer::new()) }; SMARTPORTS.len()];
+
+/// Switches the radio into VEXlink mode, looking for a radio with the same
+/// link ID.
+///
+/// A `type` of 1 makes this radio the manager of the link, and anything else
+/// makes it a worker. `ov` is ignored, since controllers are never paired with
+/// simulated radios.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `link_id` must satisfy the safety requirements outlined by
+///   [`CStr::from_ptr`]
+pub unsafe extern "C" fn vexDeviceGenericRadioConnection(
     device: V5_DeviceT,
     link_id: *mut c_char,
     r#type: c_int,
     ov: bool,
 ) {
+    if link_id.is_null() {
+        return;
+    }
+
+    let link_id = unsafe { CStr::from_ptr(link_id) }
+        .to_string_lossy()
+        .into_owned();
+    let role = if r#type == 1 {
+        RadioRole::Manager
+    } else {
+        RadioRole::Worker
+    };
+
+    if let Some(buffer) = GENERIC_RADIO_BUFFERS.get(unsafe { *device }.zero_indexed_port as usize) {
+        buffer.lock().clear();
+    }
+
+    unsafe { send_device_command(device, RadioCommand::Connect { link_id, role }) }
 }
-pub extern "C" fn vexDeviceGenericRadioWriteFree(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// Returns the number of bytes that can be transmitted before the radio's
+/// buffer is full.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericRadioWriteFree(device: V5_DeviceT) -> i32 {
+    unsafe { with_device_mut(device, |data: &mut RadioData| data.write_free as i32) }.unwrap_or(-1)
 }
-pub extern "C" fn vexDeviceGenericRadioTransmit(
+
+/// Queues as much of `data` as fits in the radio's buffer to be sent over the
+/// link.
+///
+/// Returns the number of bytes queued, or -1 if there is no radio on the port.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be valid for reads of `size` bytes
+pub unsafe extern "C" fn vexDeviceGenericRadioTransmit(
     device: V5_DeviceT,
     data: *const u8,
     size: u16,
 ) -> i32 {
-    Default::default()
+    if data.is_null() {
+        return 0;
+    }
+
+    let data = unsafe { core::slice::from_raw_parts(data, size as usize) };
+
+    // The radio will report how much room it has left the next time it sends an
+    // update, but until then we keep track of what we've queued ourselves so that
+    // the buffer isn't overrun by several calls in a row.
+    let Some(len) = (unsafe {
+        with_device_mut(device, |radio: &mut RadioData| {
+            let len = (size as usize).min(radio.write_free as usize);
+            radio.write_free -= len as u16;
+            len
+        })
+    }) else {
+        return -1;
+    };
+
+    if len > 0 {
+        unsafe { send_device_command(device, RadioCommand::Transmit(data[..len].to_vec())) }
+    }
+
+    len as i32
 }
-pub extern "C" fn vexDeviceGenericRadioReceiveAvail(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// Returns the number of received bytes waiting to be read.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericRadioReceiveAvail(device: V5_DeviceT) -> u32 {
+    GENERIC_RADIO_BUFFERS
+        .get(unsafe { *device }.zero_indexed_port as usize)
+        .map_or(0, |buffer| buffer.lock().len() as u32)
 }
-pub extern "C" fn vexDeviceGenericRadioReceive(
+
+/// Reads up to `size` received bytes into `data`.
+///
+/// Returns the number of bytes read, or -1 if there is no radio on the port.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be valid for writes of `size` bytes
+pub unsafe extern "C" fn vexDeviceGenericRadioReceive(
     device: V5_DeviceT,
     data: *mut u8,
     size: u16,
 ) -> i32 {
-    Default::default()
+    if unsafe { with_device_mut(device, |_: &mut RadioData| ()) }.is_none() {
+        return -1;
+    }
+    if data.is_null() {
+        return 0;
+    }
+
+    let Some(buffer) = GENERIC_RADIO_BUFFERS.get(unsafe { *device }.zero_indexed_port as usize)
+    else {
+        return -1;
+    };
+    let data = unsafe { core::slice::from_raw_parts_mut(data, size as usize) };
+
+    let mut buffer = buffer.lock();
+    let len = data.len().min(buffer.len());
+    for (dest, byte) in data.iter_mut().zip(buffer.drain().take(len)) {
+        *dest = byte;
+    }
+    len as i32
 }
-pub extern "C" fn vexDeviceGenericRadioLinkStatus(device: V5_DeviceT) -> bool {
-    Default::default()
+
+/// Returns whether the radio has established a link with another radio.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericRadioLinkStatus(device: V5_DeviceT) -> bool {
+    unsafe { with_device_mut(device, |data: &mut RadioData| data.linked) }.unwrap_or(false)
 }
@@ -1,36 +1,258 @@
 //! Smart Port Generic Serial Communication
 
+use alloc::vec::Vec;
+
+use ringbuffer::{ConstGenericRingBuffer, RingBuffer};
 use vex_sdk::*;
+use vex_v5_qemu_protocol::{
+    generic_serial::GenericSerialCommand, HostBoundPacket, SmartPortCommand,
+};
+
+use super::{send_device_command, SMARTPORTS};
+use crate::{protocol, sync::Mutex};
+
+/// Size of each port's transmit and receive buffers in bytes.
+const BUFFER_SIZE: usize = 1024;
+
+/// The baud rate used by a port until told otherwise.
+const DEFAULT_BAUDRATE: i32 = 115200;
+
+/// The state of a smartport's generic serial mode.
+pub struct GenericSerialPort {
+    pub enabled: bool,
+    pub baudrate: i32,
+    /// Bytes written by the user program that haven't been sent to the host.
+    pub tx: ConstGenericRingBuffer<u8, BUFFER_SIZE>,
+    /// Bytes sent by the host that haven't been read by the user program.
+    pub rx: ConstGenericRingBuffer<u8, BUFFER_SIZE>,
+}
+
+impl GenericSerialPort {
+    const fn new() -> Self {
+        Self {
+            enabled: false,
+            baudrate: DEFAULT_BAUDRATE,
+            tx: ConstGenericRingBuffer::new(),
+            rx: ConstGenericRingBuffer::new(),
+        }
+    }
+
+    fn write_free(&self) -> usize {
+        BUFFER_SIZE - self.tx.len()
+    }
+
+    /// Adds bytes sent by the host to the receive buffer.
+    ///
+    /// Like on a real port, bytes that arrive while the buffer is full are
+    /// dropped rather than overwriting ones that haven't been read yet.
+    pub fn receive(&mut self, data: Vec<u8>) {
+        let free = BUFFER_SIZE - self.rx.len();
+        self.rx.extend(data.into_iter().take(free));
+    }
+}
+
+pub static GENERIC_SERIAL_PORTS: [Mutex<GenericSerialPort>; SMARTPORTS.len()] =
+    [const { Mutex::new(GenericSerialPort::new()) }; SMARTPORTS.len()];
+
+/// Sends the contents of every port's transmit buffer to the host.
+pub fn flush_generic_serial() {
+    for (port, serial) in GENERIC_SERIAL_PORTS.iter().enumerate() {
+        let mut serial = serial.lock();
+        if serial.tx.is_empty() {
+            continue;
+        }
+
+        let data: Vec<u8> = serial.tx.drain().collect();
+        _ = protocol::send_packet(HostBoundPacket::SmartPortCommand {
+            port: port as u8,
+            command: SmartPortCommand::GenericSerial(GenericSerialCommand::Transmit(data)),
+        });
+    }
+}
+
+/// Calls `f` with the generic serial state of the port that `device` refers
+/// to, if the port has been enabled.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+unsafe fn with_serial<T>(
+    device: V5_DeviceT,
+    f: impl FnOnce(&mut GenericSerialPort) -> T,
+) -> Option<T> {
+    let mut serial = GENERIC_SERIAL_PORTS
+        .get(unsafe { *device }.zero_indexed_port as usize)?
+        .lock();
+
+    if serial.enabled {
+        Some(f(&mut serial))
+    } else {
+        None
+    }
+}
 
-pub extern "C" fn vexDeviceGenericSerialEnable(device: V5_DeviceT, options: i32) {}
-pub extern "C" fn vexDeviceGenericSerialBaudrate(device: V5_DeviceT, baudrate: i32) {}
-pub extern "C" fn vexDeviceGenericSerialWriteChar(device: V5_DeviceT, c: u8) -> i32 {
-    Default::default()
+/// Switches the port into generic serial mode, clearing its buffers.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericSerialEnable(device: V5_DeviceT, options: i32) {
+    let Some(serial) = GENERIC_SERIAL_PORTS.get(unsafe { *device }.zero_indexed_port as usize)
+    else {
+        return;
+    };
+
+    {
+        let mut serial = serial.lock();
+        serial.enabled = true;
+        serial.tx.clear();
+        serial.rx.clear();
+    }
+
+    unsafe { send_device_command(device, GenericSerialCommand::Enable { options }) }
 }
-pub extern "C" fn vexDeviceGenericSerialWriteFree(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericSerialBaudrate(device: V5_DeviceT, baudrate: i32) {
+    if unsafe { with_serial(device, |serial| serial.baudrate = baudrate) }.is_some() {
+        unsafe { send_device_command(device, GenericSerialCommand::SetBaudrate(baudrate)) }
+    }
+}
+
+/// Queues a byte to be transmitted.
+///
+/// Returns 1 if the byte was queued, or -1 if the transmit buffer is full or
+/// the port isn't in generic serial mode.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericSerialWriteChar(device: V5_DeviceT, c: u8) -> i32 {
+    unsafe {
+        with_serial(device, |serial| {
+            if serial.tx.is_full() {
+                -1
+            } else {
+                serial.tx.enqueue(c);
+                1
+            }
+        })
+    }
+    .unwrap_or(-1)
 }
-pub extern "C" fn vexDeviceGenericSerialTransmit(
+
+/// Returns the number of bytes that can be queued before the transmit buffer
+/// is full, or -1 if the port isn't in generic serial mode.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericSerialWriteFree(device: V5_DeviceT) -> i32 {
+    unsafe { with_serial(device, |serial| serial.write_free() as i32) }.unwrap_or(-1)
+}
+
+/// Queues as much of `buffer` as fits in the transmit buffer.
+///
+/// Returns the number of bytes queued, or -1 if the port isn't in generic
+/// serial mode.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `buffer` must be valid for reads of `length` bytes
+pub unsafe extern "C" fn vexDeviceGenericSerialTransmit(
     device: V5_DeviceT,
     buffer: *const u8,
     length: i32,
 ) -> i32 {
-    Default::default()
+    if buffer.is_null() || length <= 0 {
+        return 0;
+    }
+
+    let data = unsafe { core::slice::from_raw_parts(buffer, length as usize) };
+
+    unsafe {
+        with_serial(device, |serial| {
+            let len = data.len().min(serial.write_free());
+            serial.tx.extend(data[..len].iter().copied());
+            len as i32
+        })
+    }
+    .unwrap_or(-1)
 }
-pub extern "C" fn vexDeviceGenericSerialReadChar(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// Returns the next received byte, or -1 if there isn't one.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericSerialReadChar(device: V5_DeviceT) -> i32 {
+    unsafe { with_serial(device, |serial| serial.rx.dequeue()) }
+        .flatten()
+        .map_or(-1, i32::from)
 }
-pub extern "C" fn vexDeviceGenericSerialPeekChar(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// Returns the next received byte without removing it from the receive
+/// buffer, or -1 if there isn't one.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericSerialPeekChar(device: V5_DeviceT) -> i32 {
+    unsafe { with_serial(device, |serial| serial.rx.peek().copied()) }
+        .flatten()
+        .map_or(-1, i32::from)
 }
-pub extern "C" fn vexDeviceGenericSerialReceiveAvail(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// Returns the number of received bytes waiting to be read, or -1 if the
+/// port isn't in generic serial mode.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericSerialReceiveAvail(device: V5_DeviceT) -> i32 {
+    unsafe { with_serial(device, |serial| serial.rx.len() as i32) }.unwrap_or(-1)
 }
-pub extern "C" fn vexDeviceGenericSerialReceive(
+
+/// Reads up to `length` received bytes into `buffer`.
+///
+/// Returns the number of bytes read, or -1 if the port isn't in generic
+/// serial mode.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `buffer` must be valid for writes of `length` bytes
+pub unsafe extern "C" fn vexDeviceGenericSerialReceive(
     device: V5_DeviceT,
     buffer: *mut u8,
     length: i32,
 ) -> i32 {
-    Default::default()
+    if buffer.is_null() || length <= 0 {
+        return 0;
+    }
+
+    let buffer = unsafe { core::slice::from_raw_parts_mut(buffer, length as usize) };
+
+    unsafe {
+        with_serial(device, |serial| {
+            let len = buffer.len().min(serial.rx.len());
+            for (dest, byte) in buffer.iter_mut().zip(serial.rx.drain().take(len)) {
+                *dest = byte;
+            }
+            len as i32
+        })
+    }
+    .unwrap_or(-1)
+}
+
+/// Discards any received bytes that haven't been read yet.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGenericSerialFlush(device: V5_DeviceT) {
+    unsafe { with_serial(device, |serial| serial.rx.clear()) };
 }
-pub extern "C" fn vexDeviceGenericSerialFlush(device: V5_DeviceT) {}
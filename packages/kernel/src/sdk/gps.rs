@@ -3,48 +3,231 @@
 use core::ffi::c_double;
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::{
+    geometry::Point2,
+    gps::{GpsCommand, GpsData},
+};
 
-pub extern "C" fn vexDeviceGpsReset(device: V5_DeviceT) {}
-pub extern "C" fn vexDeviceGpsHeadingGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+use super::{send_device_command, with_device};
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsReset(device: V5_DeviceT) {
+    unsafe { send_device_command(device, GpsCommand::Reset) }
 }
-pub extern "C" fn vexDeviceGpsDegreesGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsHeadingGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &GpsData| data.rotation) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceGpsQuaternionGet(device: V5_DeviceT, data: *mut V5_DeviceGpsQuaternion) {
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsDegreesGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &GpsData| data.heading) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceGpsAttitudeGet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceGpsQuaternion`]
+pub unsafe extern "C" fn vexDeviceGpsQuaternionGet(
+    device: V5_DeviceT,
+    data: *mut V5_DeviceGpsQuaternion,
+) {
+    let quaternion = unsafe {
+        with_device(device, |data: &GpsData| V5_DeviceGpsQuaternion {
+            x: data.quaternion.x,
+            y: data.quaternion.y,
+            z: data.quaternion.z,
+            w: data.quaternion.w,
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write_unaligned(quaternion) }
+}
+
+/// Writes the orientation and position of the robot to `data`.
+///
+/// If `bRaw` is true, the position and heading of the sensor itself are
+/// written instead, without the configured offsets applied.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceGpsAttitude`]
+pub unsafe extern "C" fn vexDeviceGpsAttitudeGet(
     device: V5_DeviceT,
     data: *mut V5_DeviceGpsAttitude,
     bRaw: bool,
 ) {
+    let attitude = unsafe {
+        with_device(device, |data: &GpsData| {
+            let position = if bRaw {
+                data.raw_position
+            } else {
+                data.position
+            };
+
+            V5_DeviceGpsAttitude {
+                pitch: data.attitude.pitch,
+                roll: data.attitude.roll,
+                yaw: data.attitude.yaw,
+                position_x: position.x,
+                position_y: position.y,
+                position_z: 0.0,
+                az: if bRaw { data.raw_heading } else { data.heading },
+                el: 0.0,
+                rot: data.rotation,
+            }
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write_unaligned(attitude) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceGpsRaw`]
+pub unsafe extern "C" fn vexDeviceGpsRawGyroGet(device: V5_DeviceT, data: *mut V5_DeviceGpsRaw) {
+    let gyro = unsafe {
+        with_device(device, |data: &GpsData| V5_DeviceGpsRaw {
+            x: data.gyro.x,
+            y: data.gyro.y,
+            z: data.gyro.z,
+            w: 0.0,
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write_unaligned(gyro) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceGpsRaw`]
+pub unsafe extern "C" fn vexDeviceGpsRawAccelGet(device: V5_DeviceT, data: *mut V5_DeviceGpsRaw) {
+    let accel = unsafe {
+        with_device(device, |data: &GpsData| V5_DeviceGpsRaw {
+            x: data.accel.x,
+            y: data.accel.y,
+            z: data.accel.z,
+            w: 0.0,
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write_unaligned(accel) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsStatusGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &GpsData| data.status.bits()) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsTemperatureGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &GpsData| data.temperature) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsModeSet(device: V5_DeviceT, mode: u32) {
+    unsafe { send_device_command(device, GpsCommand::SetMode(mode)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsModeGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &GpsData| data.mode) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsDataRateSet(device: V5_DeviceT, rate: u32) {
+    unsafe { send_device_command(device, GpsCommand::SetDataRate(rate)) }
 }
-pub extern "C" fn vexDeviceGpsRawGyroGet(device: V5_DeviceT, data: *mut V5_DeviceGpsRaw) {}
-pub extern "C" fn vexDeviceGpsRawAccelGet(device: V5_DeviceT, data: *mut V5_DeviceGpsRaw) {}
-pub extern "C" fn vexDeviceGpsStatusGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsOriginSet(device: V5_DeviceT, ox: c_double, oy: c_double) {
+    unsafe { send_device_command(device, GpsCommand::SetOrigin(Point2 { x: ox, y: oy })) }
 }
-pub extern "C" fn vexDeviceGpsTemperatureGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `ox` and `oy` must be valid pointers to [`c_double`]s
+pub unsafe extern "C" fn vexDeviceGpsOriginGet(
+    device: V5_DeviceT,
+    ox: *mut c_double,
+    oy: *mut c_double,
+) {
+    let origin = unsafe { with_device(device, |data: &GpsData| data.origin) }
+        .unwrap_or(Point2 { x: 0.0, y: 0.0 });
+
+    unsafe {
+        ox.write(origin.x);
+        oy.write(origin.y);
+    }
 }
-pub extern "C" fn vexDeviceGpsModeSet(device: V5_DeviceT, mode: u32) {}
-pub extern "C" fn vexDeviceGpsModeGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsRotationSet(device: V5_DeviceT, value: c_double) {
+    unsafe { send_device_command(device, GpsCommand::SetRotation(value)) }
 }
-pub extern "C" fn vexDeviceGpsDataRateSet(device: V5_DeviceT, rate: u32) {}
-pub extern "C" fn vexDeviceGpsOriginSet(device: V5_DeviceT, ox: c_double, oy: c_double) {}
-pub extern "C" fn vexDeviceGpsOriginGet(device: V5_DeviceT, ox: *mut c_double, oy: *mut c_double) {}
-pub extern "C" fn vexDeviceGpsRotationSet(device: V5_DeviceT, value: c_double) {}
-pub extern "C" fn vexDeviceGpsRotationGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsRotationGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &GpsData| data.rotation_offset) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceGpsInitialPositionSet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsInitialPositionSet(
     device: V5_DeviceT,
     initial_x: c_double,
     initial_y: c_double,
     initial_rotation: c_double,
 ) {
+    unsafe {
+        send_device_command(
+            device,
+            GpsCommand::SetInitialPosition {
+                position: Point2 {
+                    x: initial_x,
+                    y: initial_y,
+                },
+                heading: initial_rotation,
+            },
+        )
+    }
 }
-pub extern "C" fn vexDeviceGpsErrorGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceGpsErrorGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &GpsData| data.error) }.unwrap_or_default()
 }
@@ -3,27 +3,139 @@
 use core::ffi::c_double;
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::imu::{ImuCommand, ImuData};
 
-pub extern "C" fn vexDeviceImuReset(device: V5_DeviceT) {}
-pub extern "C" fn vexDeviceImuHeadingGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+use super::{send_device_command, with_device};
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceImuReset(device: V5_DeviceT) {
+    unsafe { send_device_command(device, ImuCommand::Reset) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceImuHeadingGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &ImuData| data.rotation) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceImuDegreesGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &ImuData| data.heading) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceImuQuaternion`]
+pub unsafe extern "C" fn vexDeviceImuQuaternionGet(
+    device: V5_DeviceT,
+    data: *mut V5_DeviceImuQuaternion,
+) {
+    let quaternion = unsafe {
+        with_device(device, |data: &ImuData| V5_DeviceImuQuaternion {
+            a: data.quaternion.x,
+            b: data.quaternion.y,
+            c: data.quaternion.z,
+            d: data.quaternion.w,
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write_unaligned(quaternion) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceImuAttitude`]
+pub unsafe extern "C" fn vexDeviceImuAttitudeGet(
+    device: V5_DeviceT,
+    data: *mut V5_DeviceImuAttitude,
+) {
+    let attitude = unsafe {
+        with_device(device, |data: &ImuData| V5_DeviceImuAttitude {
+            pitch: data.attitude.pitch,
+            roll: data.attitude.roll,
+            yaw: data.attitude.yaw,
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write_unaligned(attitude) }
 }
-pub extern "C" fn vexDeviceImuDegreesGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceImuRaw`]
+pub unsafe extern "C" fn vexDeviceImuRawGyroGet(device: V5_DeviceT, data: *mut V5_DeviceImuRaw) {
+    let gyro = unsafe {
+        with_device(device, |data: &ImuData| V5_DeviceImuRaw {
+            x: data.gyro.x,
+            y: data.gyro.y,
+            z: data.gyro.z,
+            w: 0.0,
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write_unaligned(gyro) }
 }
-pub extern "C" fn vexDeviceImuQuaternionGet(device: V5_DeviceT, data: *mut V5_DeviceImuQuaternion) {
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceImuRaw`]
+pub unsafe extern "C" fn vexDeviceImuRawAccelGet(device: V5_DeviceT, data: *mut V5_DeviceImuRaw) {
+    let accel = unsafe {
+        with_device(device, |data: &ImuData| V5_DeviceImuRaw {
+            x: data.accel.x,
+            y: data.accel.y,
+            z: data.accel.z,
+            w: 0.0,
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write_unaligned(accel) }
 }
-pub extern "C" fn vexDeviceImuAttitudeGet(device: V5_DeviceT, data: *mut V5_DeviceImuAttitude) {}
-pub extern "C" fn vexDeviceImuRawGyroGet(device: V5_DeviceT, data: *mut V5_DeviceImuRaw) {}
-pub extern "C" fn vexDeviceImuRawAccelGet(device: V5_DeviceT, data: *mut V5_DeviceImuRaw) {}
-pub extern "C" fn vexDeviceImuStatusGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceImuStatusGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &ImuData| data.status.bits()) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceImuTemperatureGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceImuTemperatureGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &ImuData| data.temperature) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceImuModeSet(device: V5_DeviceT, mode: u32) {}
-pub extern "C" fn vexDeviceImuModeGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceImuModeSet(device: V5_DeviceT, mode: u32) {
+    unsafe { send_device_command(device, ImuCommand::SetMode(mode)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceImuModeGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &ImuData| data.mode) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceImuDataRateSet(device: V5_DeviceT, rate: u32) {
+    unsafe { send_device_command(device, ImuCommand::SetDataRate(rate)) }
 }
-pub extern "C" fn vexDeviceImuDataRateSet(device: V5_DeviceT, rate: u32) {}
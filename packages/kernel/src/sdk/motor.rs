@@ -3,113 +3,421 @@
 use core::ffi::c_double;
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::{
+    motor::{MotorBrakeMode, MotorCommand, MotorControl, MotorData, MotorFaults, MotorFlags},
+    SmartPortData,
+};
 
-pub extern "C" fn vexDeviceMotorVelocitySet(device: V5_DeviceT, velocity: i32) {}
-pub extern "C" fn vexDeviceMotorVelocityGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+use super::{send_device_command, with_device, SMARTPORTS};
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorVelocitySet(device: V5_DeviceT, velocity: i32) {
+    unsafe { send_device_command(device, MotorCommand::SetVelocity(velocity)) }
 }
-pub extern "C" fn vexDeviceMotorActualVelocityGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorVelocityGet(device: V5_DeviceT) -> i32 {
+    unsafe {
+        with_device(device, |data: &MotorData| match data.control {
+            MotorControl::Velocity(velocity) => velocity,
+            MotorControl::Position { velocity, .. } => velocity,
+            _ => 0,
+        })
+    }
+    .unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorDirectionGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorActualVelocityGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &MotorData| data.velocity) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorModeSet(device: V5_DeviceT, mode: V5MotorControlMode) {}
-pub extern "C" fn vexDeviceMotorModeGet(device: V5_DeviceT) -> V5MotorControlMode {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorDirectionGet(device: V5_DeviceT) -> i32 {
+    unsafe {
+        with_device(device, |data: &MotorData| {
+            if data.flags.contains(MotorFlags::ZERO_VELOCITY) {
+                0
+            } else {
+                data.velocity.signum() as i32
+            }
+        })
+    }
+    .unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorPwmSet(device: V5_DeviceT, pwm: i32) {}
-pub extern "C" fn vexDeviceMotorPwmGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorModeSet(device: V5_DeviceT, mode: V5MotorControlMode) {
+    // The other modes are entered by setting a velocity or position target.
+    let brake_mode = match mode {
+        V5MotorControlMode::kMotorControlModeOFF => MotorBrakeMode::Coast,
+        V5MotorControlMode::kMotorControlModeBRAKE => MotorBrakeMode::Brake,
+        V5MotorControlMode::kMotorControlModeHOLD => MotorBrakeMode::Hold,
+        _ => return,
+    };
+
+    unsafe {
+        send_device_command(device, MotorCommand::SetBrakeMode(brake_mode));
+        send_device_command(device, MotorCommand::Stop);
+    }
 }
-pub extern "C" fn vexDeviceMotorCurrentLimitSet(device: V5_DeviceT, limit: i32) {}
-pub extern "C" fn vexDeviceMotorCurrentLimitGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorModeGet(device: V5_DeviceT) -> V5MotorControlMode {
+    unsafe { with_device(device, |data: &MotorData| data.control.into()) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorCurrentGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorPwmSet(device: V5_DeviceT, pwm: i32) {
+    unsafe {
+        send_device_command(
+            device,
+            MotorCommand::SetVoltage(pwm.clamp(-100, 100) * 12000 / 100),
+        )
+    }
 }
-pub extern "C" fn vexDeviceMotorPowerGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorPwmGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &MotorData| data.voltage * 100 / 12000) }
+        .unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorTorqueGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorCurrentLimitSet(device: V5_DeviceT, limit: i32) {
+    unsafe { send_device_command(device, MotorCommand::SetCurrentLimit(limit)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorCurrentLimitGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &MotorData| data.current_limit) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorCurrentGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &MotorData| data.current) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorEfficiencyGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorPowerGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &MotorData| data.power) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorTemperatureGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorTorqueGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &MotorData| data.torque) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorOverTempFlagGet(device: V5_DeviceT) -> bool {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorEfficiencyGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &MotorData| data.efficiency) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorTemperatureGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &MotorData| data.temperature) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorOverTempFlagGet(device: V5_DeviceT) -> bool {
+    unsafe {
+        with_device(device, |data: &MotorData| {
+            data.faults.contains(MotorFaults::OVER_TEMPERATURE)
+        })
+    }
+    .unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorCurrentLimitFlagGet(device: V5_DeviceT) -> bool {
+    unsafe {
+        with_device(device, |data: &MotorData| {
+            data.faults.contains(MotorFaults::OVER_CURRENT)
+        })
+    }
+    .unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorZeroVelocityFlagGet(device: V5_DeviceT) -> bool {
+    unsafe {
+        with_device(device, |data: &MotorData| {
+            data.flags.contains(MotorFlags::ZERO_VELOCITY)
+        })
+    }
+    .unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorZeroPositionFlagGet(device: V5_DeviceT) -> bool {
+    unsafe {
+        with_device(device, |data: &MotorData| {
+            data.flags.contains(MotorFlags::ZERO_POSITION)
+        })
+    }
+    .unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorReverseFlagSet(device: V5_DeviceT, reverse: bool) {
+    unsafe { send_device_command(device, MotorCommand::SetReversed(reverse)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorReverseFlagGet(device: V5_DeviceT) -> bool {
+    unsafe { with_device(device, |data: &MotorData| data.reversed) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorEncoderUnitsSet(
+    device: V5_DeviceT,
+    units: V5MotorEncoderUnits,
+) {
+    unsafe { send_device_command(device, MotorCommand::SetEncoderUnits(units.into())) }
 }
-pub extern "C" fn vexDeviceMotorCurrentLimitFlagGet(device: V5_DeviceT) -> bool {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorEncoderUnitsGet(device: V5_DeviceT) -> V5MotorEncoderUnits {
+    unsafe { with_device(device, |data: &MotorData| data.encoder_units.into()) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorZeroVelocityFlagGet(device: V5_DeviceT) -> bool {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorBrakeModeSet(device: V5_DeviceT, mode: V5MotorBrakeMode) {
+    unsafe { send_device_command(device, MotorCommand::SetBrakeMode(mode.into())) }
 }
-pub extern "C" fn vexDeviceMotorZeroPositionFlagGet(device: V5_DeviceT) -> bool {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorBrakeModeGet(device: V5_DeviceT) -> V5MotorBrakeMode {
+    unsafe { with_device(device, |data: &MotorData| data.brake_mode.into()) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorReverseFlagSet(device: V5_DeviceT, reverse: bool) {}
-pub extern "C" fn vexDeviceMotorReverseFlagGet(device: V5_DeviceT) -> bool {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorPositionSet(device: V5_DeviceT, position: c_double) {
+    unsafe { send_device_command(device, MotorCommand::SetPosition(position)) }
 }
-pub extern "C" fn vexDeviceMotorEncoderUnitsSet(device: V5_DeviceT, units: V5MotorEncoderUnits) {}
-pub extern "C" fn vexDeviceMotorEncoderUnitsGet(device: V5_DeviceT) -> V5MotorEncoderUnits {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorPositionGet(device: V5_DeviceT) -> c_double {
+    unsafe {
+        with_device(device, |data: &MotorData| {
+            data.encoder_units
+                .from_ticks(data.position as f64, data.gearset)
+        })
+    }
+    .unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorBrakeModeSet(device: V5_DeviceT, mode: V5MotorBrakeMode) {}
-pub extern "C" fn vexDeviceMotorBrakeModeGet(device: V5_DeviceT) -> V5MotorBrakeMode {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `timestamp` must be null or a valid pointer to a `u32`
+pub unsafe extern "C" fn vexDeviceMotorPositionRawGet(
+    device: V5_DeviceT,
+    timestamp: *mut u32,
+) -> i32 {
+    let port_index = unsafe { *device }.zero_indexed_port as usize;
+
+    if let Some(port) = SMARTPORTS.get(port_index) {
+        let port = port.lock();
+        if let Some(SmartPortData::Motor(data)) = &port.data {
+            if !timestamp.is_null() {
+                unsafe {
+                    *timestamp = port.timestamp;
+                }
+            }
+
+            return data.position;
+        }
+    }
+
+    0
 }
-pub extern "C" fn vexDeviceMotorPositionSet(device: V5_DeviceT, position: c_double) {}
-pub extern "C" fn vexDeviceMotorPositionGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorPositionReset(device: V5_DeviceT) {
+    unsafe { send_device_command(device, MotorCommand::SetPosition(0.0)) }
 }
-pub extern "C" fn vexDeviceMotorPositionRawGet(device: V5_DeviceT, timestamp: *mut u32) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorTargetGet(device: V5_DeviceT) -> c_double {
+    unsafe {
+        with_device(device, |data: &MotorData| match data.control {
+            MotorControl::Position { target, .. } => {
+                data.encoder_units.from_ticks(target, data.gearset)
+            }
+            _ => 0.0,
+        })
+    }
+    .unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorPositionReset(device: V5_DeviceT) {}
-pub extern "C" fn vexDeviceMotorTargetGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorServoTargetSet(device: V5_DeviceT, position: c_double) {
+    unsafe { send_device_command(device, MotorCommand::SetServoTarget(position)) }
 }
-pub extern "C" fn vexDeviceMotorServoTargetSet(device: V5_DeviceT, position: c_double) {}
-pub extern "C" fn vexDeviceMotorAbsoluteTargetSet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorAbsoluteTargetSet(
     device: V5_DeviceT,
     position: c_double,
     veloctiy: i32,
 ) {
+    unsafe {
+        send_device_command(
+            device,
+            MotorCommand::SetAbsoluteTarget {
+                position,
+                velocity: veloctiy,
+            },
+        )
+    }
 }
-pub extern "C" fn vexDeviceMotorRelativeTargetSet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorRelativeTargetSet(
     device: V5_DeviceT,
     position: c_double,
     velocity: i32,
 ) {
+    unsafe {
+        send_device_command(
+            device,
+            MotorCommand::SetRelativeTarget { position, velocity },
+        )
+    }
 }
-pub extern "C" fn vexDeviceMotorFaultsGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorFaultsGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &MotorData| data.faults.bits()) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorFlagsGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &MotorData| data.flags.bits()) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorVoltageSet(device: V5_DeviceT, voltage: i32) {
+    unsafe { send_device_command(device, MotorCommand::SetVoltage(voltage)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorVoltageGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &MotorData| data.voltage) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorGearingSet(device: V5_DeviceT, gearset: V5MotorGearset) {
+    unsafe { send_device_command(device, MotorCommand::SetGearset(gearset.into())) }
 }
-pub extern "C" fn vexDeviceMotorFlagsGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorGearingGet(device: V5_DeviceT) -> V5MotorGearset {
+    unsafe { with_device(device, |data: &MotorData| data.gearset.into()) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorVoltageSet(device: V5_DeviceT, voltage: i32) {}
-pub extern "C" fn vexDeviceMotorVoltageGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorVoltageLimitSet(device: V5_DeviceT, limit: i32) {
+    unsafe { send_device_command(device, MotorCommand::SetVoltageLimit(limit)) }
 }
-pub extern "C" fn vexDeviceMotorGearingSet(device: V5_DeviceT, gearset: V5MotorGearset) {}
-pub extern "C" fn vexDeviceMotorGearingGet(device: V5_DeviceT) -> V5MotorGearset {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorVoltageLimitGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &MotorData| data.voltage_limit) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceMotorVoltageLimitSet(device: V5_DeviceT, limit: i32) {}
-pub extern "C" fn vexDeviceMotorVoltageLimitGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceMotorVelocityUpdate(device: V5_DeviceT, velocity: i32) {
+    unsafe { send_device_command(device, MotorCommand::UpdateVelocity(velocity)) }
 }
-pub extern "C" fn vexDeviceMotorVelocityUpdate(device: V5_DeviceT, velocity: i32) {}
+
 pub extern "C" fn vexDeviceMotorPositionPidSet(device: V5_DeviceT, pid: *mut V5_DeviceMotorPid) {}
+
 pub extern "C" fn vexDeviceMotorVelocityPidSet(device: V5_DeviceT, pid: *mut V5_DeviceMotorPid) {}
+
 pub extern "C" fn vexDeviceMotorExternalProfileSet(
     device: V5_DeviceT,
     position: c_double,
@@ -3,42 +3,173 @@
 use core::ffi::c_double;
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::optical_sensor::{OpticalSensorCommand, OpticalSensorData};
 
-pub extern "C" fn vexDeviceOpticalHueGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+use super::{send_device_command, with_device};
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalHueGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &OpticalSensorData| data.hue) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalSatGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &OpticalSensorData| data.saturation) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalBrightnessGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &OpticalSensorData| data.brightness) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalProximityGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &OpticalSensorData| data.proximity) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceOpticalRgb`]
+pub unsafe extern "C" fn vexDeviceOpticalRgbGet(
+    device: V5_DeviceT,
+    data: *mut V5_DeviceOpticalRgb,
+) {
+    let rgb = unsafe {
+        with_device(device, |data: &OpticalSensorData| V5_DeviceOpticalRgb {
+            red: data.rgb.red,
+            green: data.rgb.green,
+            blue: data.rgb.blue,
+            brightness: data.rgb.brightness,
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write(rgb) }
 }
-pub extern "C" fn vexDeviceOpticalSatGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalLedPwmSet(device: V5_DeviceT, value: i32) {
+    unsafe { send_device_command(device, OpticalSensorCommand::SetLedPwm(value)) }
 }
-pub extern "C" fn vexDeviceOpticalBrightnessGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalLedPwmGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &OpticalSensorData| data.led_pwm) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceOpticalProximityGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalStatusGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &OpticalSensorData| data.status) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceOpticalRgbGet(device: V5_DeviceT, data: *mut V5_DeviceOpticalRgb) {}
-pub extern "C" fn vexDeviceOpticalLedPwmSet(device: V5_DeviceT, value: i32) {}
-pub extern "C" fn vexDeviceOpticalLedPwmGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `data` must be a valid pointer to a [`V5_DeviceOpticalRaw`]
+pub unsafe extern "C" fn vexDeviceOpticalRawGet(
+    device: V5_DeviceT,
+    data: *mut V5_DeviceOpticalRaw,
+) {
+    let raw = unsafe {
+        with_device(device, |data: &OpticalSensorData| V5_DeviceOpticalRaw {
+            clear: data.raw.clear,
+            red: data.raw.red,
+            green: data.raw.green,
+            blue: data.raw.blue,
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe { data.write(raw) }
 }
-pub extern "C" fn vexDeviceOpticalStatusGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalModeSet(device: V5_DeviceT, mode: u32) {
+    unsafe { send_device_command(device, OpticalSensorCommand::SetMode(mode)) }
 }
-pub extern "C" fn vexDeviceOpticalRawGet(device: V5_DeviceT, data: *mut V5_DeviceOpticalRaw) {}
-pub extern "C" fn vexDeviceOpticalModeSet(device: V5_DeviceT, mode: u32) {}
-pub extern "C" fn vexDeviceOpticalModeGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalModeGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &OpticalSensorData| data.mode) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceOpticalGestureGet(
+
+/// Writes the most recent gesture detected by the sensor to `pData` and
+/// returns its direction, or 0 if no gesture has been detected.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `pData` must be a valid pointer to a [`V5_DeviceOpticalGesture`]
+pub unsafe extern "C" fn vexDeviceOpticalGestureGet(
     device: V5_DeviceT,
     pData: *mut V5_DeviceOpticalGesture,
 ) -> u32 {
-    Default::default()
-}
-pub extern "C" fn vexDeviceOpticalGestureEnable(device: V5_DeviceT) {}
-pub extern "C" fn vexDeviceOpticalGestureDisable(device: V5_DeviceT) {}
-pub extern "C" fn vexDeviceOpticalProximityThreshold(device: V5_DeviceT, value: i32) {}
-pub extern "C" fn vexDeviceOpticalIntegrationTimeSet(device: V5_DeviceT, timeMs: c_double) {}
-pub extern "C" fn vexDeviceOpticalIntegrationTimeGet(device: V5_DeviceT) -> c_double {
-    Default::default()
+    let gesture = unsafe { with_device(device, |data: &OpticalSensorData| data.gesture) }
+        .flatten()
+        .map(|gesture| V5_DeviceOpticalGesture {
+            gesture_type: gesture.direction as u8,
+            count: gesture.count,
+            time: gesture.time,
+            ..Default::default()
+        })
+        .unwrap_or_default();
+
+    unsafe { pData.write(gesture) }
+
+    gesture.gesture_type as u32
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalGestureEnable(device: V5_DeviceT) {
+    unsafe { send_device_command(device, OpticalSensorCommand::SetGesturesEnabled(true)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalGestureDisable(device: V5_DeviceT) {
+    unsafe { send_device_command(device, OpticalSensorCommand::SetGesturesEnabled(false)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalProximityThreshold(device: V5_DeviceT, value: i32) {
+    unsafe { send_device_command(device, OpticalSensorCommand::SetProximityThreshold(value)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalIntegrationTimeSet(device: V5_DeviceT, timeMs: c_double) {
+    unsafe { send_device_command(device, OpticalSensorCommand::SetIntegrationTime(timeMs)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceOpticalIntegrationTimeGet(device: V5_DeviceT) -> c_double {
+    unsafe { with_device(device, |data: &OpticalSensorData| data.integration_time) }
+        .unwrap_or_default()
 }
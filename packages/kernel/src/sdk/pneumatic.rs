@@ -1,34 +1,139 @@
 //! CTE Workcell Pneumatics Control
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::pneumatic::{PneumaticCommand, PneumaticData};
 
-pub extern "C" fn vexDevicePneumaticActuationStatusGet(
+use super::{send_device_command, with_device};
+
+/// Writes the number of times each cylinder has been actuated to `ac1`
+/// through `ac4` and returns the total across all cylinders.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `ac1`, `ac2`, `ac3` and `ac4` must be valid pointers to [`u16`]s
+pub unsafe extern "C" fn vexDevicePneumaticActuationStatusGet(
     device: V5_DeviceT,
     ac1: *mut u16,
     ac2: *mut u16,
     ac3: *mut u16,
     ac4: *mut u16,
 ) -> u32 {
-    Default::default()
+    let counts = unsafe {
+        with_device(device, |data: &PneumaticData| {
+            data.cylinders.map(|c| c.actuations)
+        })
+    }
+    .unwrap_or_default();
+
+    unsafe {
+        ac1.write(counts[0]);
+        ac2.write(counts[1]);
+        ac3.write(counts[2]);
+        ac4.write(counts[3]);
+    }
+
+    counts.iter().map(|&count| count as u32).sum()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDevicePneumaticCompressorSet(device: V5_DeviceT, bState: bool) {
+    unsafe { send_device_command(device, PneumaticCommand::SetCompressor(bState)) }
 }
-pub extern "C" fn vexDevicePneumaticCompressorSet(device: V5_DeviceT, bState: bool) {}
-pub extern "C" fn vexDevicePneumaticCtrlSet(
+
+/// Sets the PWM of each cylinder and of the compressor at once.
+///
+/// The flags and timing fields of `pCtrl` are ignored, since their meaning is
+/// undocumented.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `pCtrl` must be a valid pointer to a [`V5_DevicePneumaticCtrl`]
+pub unsafe extern "C" fn vexDevicePneumaticCtrlSet(
     device: V5_DeviceT,
     pCtrl: *mut V5_DevicePneumaticCtrl,
 ) {
+    let ctrl = unsafe { pCtrl.read() };
+
+    for (id, pwm) in [ctrl.m1_pwm, ctrl.m2_pwm, ctrl.m3_pwm, ctrl.m4_pwm]
+        .into_iter()
+        .enumerate()
+    {
+        unsafe {
+            send_device_command(
+                device,
+                PneumaticCommand::SetCylinderPwm { id: id as u8, pwm },
+            );
+        }
+    }
+    unsafe { send_device_command(device, PneumaticCommand::SetCompressorPwm(ctrl.comp_pwm)) }
 }
-pub extern "C" fn vexDevicePneumaticCylinderPwmSet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDevicePneumaticCylinderPwmSet(
     device: V5_DeviceT,
     id: u32,
     bState: bool,
     pwm: u8,
 ) {
+    let Ok(id) = u8::try_from(id) else {
+        return;
+    };
+
+    unsafe {
+        send_device_command(device, PneumaticCommand::SetCylinderPwm { id, pwm });
+        send_device_command(
+            device,
+            PneumaticCommand::SetCylinder {
+                id,
+                extended: bState,
+            },
+        );
+    }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDevicePneumaticCylinderSet(device: V5_DeviceT, id: u32, bState: bool) {
+    let Ok(id) = u8::try_from(id) else {
+        return;
+    };
+
+    unsafe {
+        send_device_command(
+            device,
+            PneumaticCommand::SetCylinder {
+                id,
+                extended: bState,
+            },
+        )
+    }
 }
-pub extern "C" fn vexDevicePneumaticCylinderSet(device: V5_DeviceT, id: u32, bState: bool) {}
-pub extern "C" fn vexDevicePneumaticPwmGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDevicePneumaticPwmGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &PneumaticData| data.compressor_pwm as u32) }
+        .unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDevicePneumaticPwmSet(device: V5_DeviceT, pwm: u8) {
+    unsafe { send_device_command(device, PneumaticCommand::SetCompressorPwm(pwm)) }
 }
-pub extern "C" fn vexDevicePneumaticPwmSet(device: V5_DeviceT, pwm: u8) {}
-pub extern "C" fn vexDevicePneumaticStatusGet(device: V5_DeviceT) -> u32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDevicePneumaticStatusGet(device: V5_DeviceT) -> u32 {
+    unsafe { with_device(device, |data: &PneumaticData| data.status.bits()) }.unwrap_or_default()
 }
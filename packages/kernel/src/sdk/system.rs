@@ -4,7 +4,7 @@ use alloc::format;
 use core::{
     arch::{asm, naked_asm},
     ffi::c_void,
-    sync::atomic::{AtomicBool, Ordering},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
 };
 
 use vex_sdk::*;
@@ -15,7 +15,7 @@ use crate::{
         timers::{global_timer_counter, PrivateTimer, WatchdogTimerMode},
     }, peripherals::{
         timer_interrupt_handler, GIC, PERIPHCLK, PRIVATE_TIMER, SYSTEM_TIME, WATCHDOG_TIMER,
-    }, protocol::exit, sdk::draw_error_box, vectors::undefined_instruction_handler, xil::{
+    }, protocol::exit, sdk::{draw_error_box, exit_tasks}, vectors::undefined_instruction_handler, xil::{
         gic::XSCUGIC_MAX_NUM_INTR_INPUTS,
         timer::XScuTimer,
         XST_FAILURE, XST_SUCCESS,
@@ -52,11 +52,64 @@ pub extern "C" fn vexScratchMemoryUnock() {}
 pub extern "C" fn vexSystemTimeGet() -> u32 {
     SYSTEM_TIME.load(Ordering::Acquire)
 }
+
+/// Milliseconds since the Unix epoch at which [`SYSTEM_TIME`] was 0.
+///
+/// This starts at 16/11/2016 and is updated whenever the host sets the
+/// real-time clock.
+static BOOT_UNIX_TIME: AtomicU64 = AtomicU64::new(1_479_254_400_000);
+
+/// Sets the real-time clock to `unix_time` milliseconds since the Unix epoch.
+///
+/// The clock then advances along with [`SYSTEM_TIME`].
+pub fn set_real_time(unix_time: u64) {
+    let uptime = u64::from(SYSTEM_TIME.load(Ordering::Acquire));
+    BOOT_UNIX_TIME.store(unix_time.saturating_sub(uptime), Ordering::Release);
+}
+
+/// Sets the real-time clock so that it read `unix_time` milliseconds since the
+/// Unix epoch when [`SYSTEM_TIME`] was 0.
+///
+/// Unlike [`set_real_time`], the result doesn't depend on when this is called.
+pub fn set_boot_time(unix_time: u64) {
+    BOOT_UNIX_TIME.store(unix_time, Ordering::Release);
+}
+
+/// Returns the current time in milliseconds since the Unix epoch.
+fn real_time() -> u64 {
+    BOOT_UNIX_TIME
+        .load(Ordering::Acquire)
+        .saturating_add(u64::from(SYSTEM_TIME.load(Ordering::Acquire)))
+}
+
+/// Converts a number of days since the Unix epoch to a `(year, month, day)`
+/// date in the Gregorian calendar.
+///
+/// See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153; // March is 0
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    (year, month, day)
+}
+
 /// # Safety
 ///
-/// `pTime` must be a valid pointer to a `date` structure.
+/// `pTime` must be a valid pointer to a `time` structure.
 pub unsafe extern "C" fn vexGettime(pTime: *mut time) {
-    let time_ms = SYSTEM_TIME.load(Ordering::Acquire);
+    let time_ms = real_time() % (24 * 60 * 60 * 1000);
     let time_secs = time_ms / 1000;
 
     unsafe {
@@ -72,11 +125,13 @@ pub unsafe extern "C" fn vexGettime(pTime: *mut time) {
 ///
 /// `pDate` must be a valid pointer to a `date` structure.
 pub unsafe extern "C" fn vexGetdate(pDate: *mut date) {
+    let (year, month, day) = civil_from_days(real_time() / (24 * 60 * 60 * 1000));
+
     unsafe {
         *pDate = date {
-            da_year: 2016,
-            da_day: 16,
-            da_mon: 11,
+            da_year: year as _,
+            da_day: day as _,
+            da_mon: month as _,
         }
     }
 }
@@ -144,6 +199,7 @@ pub extern "C" fn vexSystemStartupOptions() -> u32 {
     Default::default()
 }
 pub extern "C" fn vexSystemExitRequest() {
+    exit_tasks();
     exit(0);
 }
 pub extern "C" fn vexSystemHighResTimeGet() -> u64 {
@@ -1,81 +1,474 @@
 //! VEXos Task Scheduler Functions
+//!
+//! VEXos runs "simple tasks" cooperatively on the same core as the user
+//! program. Each task has its own stack and runs until it sleeps or calls
+//! [`vexTasksRun`], at which point control returns to whoever called
+//! [`vexTasksRun`] outside of a task.
 
-use core::ffi::{c_char, c_int, c_void};
+use alloc::{boxed::Box, ffi::CString, vec::Vec};
+use core::{
+    arch::naked_asm,
+    ffi::{c_char, c_int, c_void, CStr},
+};
 
 use embedded_io::Write;
 use vex_v5_qemu_protocol::KernelBoundPacket;
 
-use super::{BATTERY, SMARTPORTS};
-use crate::{peripherals::UART1, protocol::recv_packet, sdk::{TOUCH, USB1}};
+use super::{
+    flush_generic_serial, set_boot_time, set_real_time, vexSystemTimeGet, Controller, BATTERY,
+    COMPETITION, DISPLAY, GENERIC_RADIO_BUFFERS, GENERIC_SERIAL_PORTS, ONBOARD_ADI, SMARTPORTS,
+};
+use crate::{
+    peripherals::UART1,
+    protocol::recv_packet,
+    sdk::{dispatch_touch_events, TOUCH, USB1},
+    sync::Mutex,
+};
+
+/// The maximum number of tasks that can be added to the scheduler.
+pub(crate) const MAX_TASKS: usize = 128;
+
+/// How long the program waits for its tasks to finish when it asks to exit,
+/// in milliseconds.
+const EXIT_TIMEOUT: u32 = 1000;
+
+/// Size of each task's stack in bytes.
+const TASK_STACK_SIZE: usize = 0x20000;
+
+/// Callback run by a task.
+type TaskCallback = unsafe extern "C" fn() -> c_int;
+
+/// The registers that a function call must preserve, saved when switching
+/// away from a task so that it can be resumed later.
+#[repr(C)]
+#[derive(Default)]
+struct Context {
+    /// r4-r11
+    registers: [u32; 8],
+    sp: u32,
+    lr: u32,
+    fpscr: u32,
+    /// d8-d15
+    fp_registers: [u32; 16],
+}
+
+/// Saves the current context to `from` and resumes the one in `to`.
+///
+/// Execution continues after the call once another context switches back to
+/// `from`.
+///
+/// # Safety
+///
+/// `to` must have been saved by this function, or set up to start running a
+/// function that never returns on a valid stack.
+#[unsafe(naked)]
+unsafe extern "C" fn switch_context(from: *mut Context, to: *const Context) {
+    naked_asm!(
+        "
+        stmia r0!, {{r4-r11}}  @ Save the current context
+        str sp, [r0], #4
+        str lr, [r0], #4
+        vmrs r2, fpscr
+        str r2, [r0], #4
+        vstmia r0, {{d8-d15}}
+
+        ldmia r1!, {{r4-r11}}  @ Restore the next one
+        ldr sp, [r1], #4
+        ldr lr, [r1], #4
+        ldr r2, [r1], #4
+        vmsr fpscr, r2
+        vldmia r1, {{d8-d15}}
+
+        bx lr
+        ",
+    )
+}
+
+struct Task {
+    callback: TaskCallback,
+    /// The minimum time between the task being resumed, in milliseconds.
+    interval: u32,
+    label: CString,
+    context: Box<Context>,
+    _stack: Box<[u64]>,
+    /// When the task was last resumed.
+    resumed_at: u32,
+    /// The earliest time the task may be resumed.
+    wake_at: u32,
+    /// Whether the task's callback has returned.
+    finished: bool,
+}
+
+struct Scheduler {
+    /// Every task that has been added, indexed by task index.
+    ///
+    /// Tasks that have finished leave an empty slot behind so that the indices
+    /// of other tasks don't change.
+    tasks: Vec<Option<Task>>,
+    /// The index of the task that is currently running, if any.
+    current: Option<usize>,
+    /// The context of whoever called [`vexTasksRun`], which tasks switch back
+    /// to when they yield.
+    context: Context,
+    /// Whether tasks are stopped when the program asks to exit, as set by
+    /// [`ProgramFlags::KILL_TASKS_ON_EXIT`](vex_v5_qemu_protocol::code_signature::ProgramFlags::KILL_TASKS_ON_EXIT).
+    kill_tasks_on_exit: bool,
+}
+
+static SCHEDULER: Mutex<Scheduler> = Mutex::new(Scheduler {
+    tasks: Vec::new(),
+    current: None,
+    context: Context {
+        registers: [0; 8],
+        sp: 0,
+        lr: 0,
+        fpscr: 0,
+        fp_registers: [0; 16],
+    },
+    kill_tasks_on_exit: false,
+});
+
+/// Sets whether tasks are stopped when the program asks to exit, rather than
+/// being left to finish.
+pub fn set_kill_tasks_on_exit(kill_tasks_on_exit: bool) {
+    SCHEDULER.lock().kill_tasks_on_exit = kill_tasks_on_exit;
+}
+
+/// Gets the scheduler ready for the program to exit.
+///
+/// If tasks are killed on exit, every task other than the one calling this is
+/// stopped. Otherwise, the scheduler keeps running until every other task has
+/// finished, giving up after [`EXIT_TIMEOUT`].
+pub fn exit_tasks() {
+    let kill_tasks = {
+        let mut scheduler = SCHEDULER.lock();
+        let scheduler = &mut *scheduler;
+
+        if scheduler.kill_tasks_on_exit {
+            // The current task's stack is still in use, so it is left alone.
+            for (index, task) in scheduler.tasks.iter_mut().enumerate() {
+                if Some(index) != scheduler.current {
+                    *task = None;
+                }
+            }
+        }
+        scheduler.kill_tasks_on_exit
+    };
+    if kill_tasks {
+        return;
+    }
+
+    let deadline = vexSystemTimeGet().saturating_add(EXIT_TIMEOUT);
+    loop {
+        let others_running = {
+            let scheduler = SCHEDULER.lock();
+            scheduler
+                .tasks
+                .iter()
+                .enumerate()
+                .any(|(index, task)| task.is_some() && Some(index) != scheduler.current)
+        };
+        if !others_running {
+            break;
+        }
+        if vexSystemTimeGet() >= deadline {
+            log::warn!("Exiting with tasks that didn't finish within {EXIT_TIMEOUT} ms");
+            break;
+        }
+
+        vexTasksRun();
+    }
+}
+
+/// Entrypoint of every task, run on the task's own stack.
+extern "C" fn task_entry() -> ! {
+    let callback = {
+        let scheduler = SCHEDULER.lock();
+        let index = scheduler.current.unwrap();
+        scheduler.tasks[index].as_ref().unwrap().callback
+    };
+
+    unsafe {
+        callback();
+    }
+
+    // The task can't be removed while we're still running on its stack, so the
+    // scheduler cleans it up once we switch away.
+    let (from, to) = {
+        let mut scheduler = SCHEDULER.lock();
+        let scheduler = &mut *scheduler;
+        let task = scheduler.tasks[scheduler.current.unwrap()]
+            .as_mut()
+            .unwrap();
+        task.finished = true;
+        (&raw mut *task.context, &raw const scheduler.context)
+    };
+
+    unsafe {
+        switch_context(from, to);
+    }
+    unreachable!("Finished tasks should never be resumed");
+}
+
+/// Switches from the current task back to the scheduler, resuming the task no
+/// earlier than `wake_at`.
+///
+/// Does nothing if no task is running.
+fn suspend(wake_at: impl FnOnce(&Task) -> u32) {
+    let (from, to) = {
+        let mut scheduler = SCHEDULER.lock();
+        let scheduler = &mut *scheduler;
+        let Some(index) = scheduler.current else {
+            return;
+        };
+        let task = scheduler.tasks[index].as_mut().unwrap();
+        task.wake_at = wake_at(task);
+        (&raw mut *task.context, &raw const scheduler.context)
+    };
+
+    unsafe {
+        switch_context(from, to);
+    }
+}
+
+/// Switches from the current task back to the scheduler, resuming the task
+/// once its interval has elapsed.
+fn yield_task() {
+    suspend(|task| task.resumed_at.saturating_add(task.interval));
+}
+
+/// Resumes every task that is ready to run, one after another.
+///
+/// Must not be called from within a task.
+fn run_tasks() {
+    let mut index = 0;
+
+    loop {
+        let (from, to) = {
+            let mut scheduler = SCHEDULER.lock();
+            let scheduler = &mut *scheduler;
+            let now = vexSystemTimeGet();
+
+            if index >= scheduler.tasks.len() {
+                break;
+            }
+            let Some(task) = scheduler.tasks[index]
+                .as_mut()
+                .filter(|task| now >= task.wake_at)
+            else {
+                index += 1;
+                continue;
+            };
+
+            task.resumed_at = now;
+            scheduler.current = Some(index);
+            (&raw mut scheduler.context, &raw const *task.context)
+        };
+
+        DISPLAY.lock().set_task_index(Some(index));
+        unsafe {
+            switch_context(from, to);
+        }
+        DISPLAY.lock().set_task_index(None);
+
+        let mut scheduler = SCHEDULER.lock();
+        scheduler.current = None;
+
+        if scheduler.tasks[index]
+            .as_ref()
+            .is_some_and(|task| task.finished)
+        {
+            scheduler.tasks[index] = None;
+        }
+
+        index += 1;
+    }
+}
+
+/// Flushes outgoing serial data, handles every packet the host has sent and
+/// runs the user's touch callback for any new touch events.
+fn process_devices() {
+    USB1.lock().flush().unwrap(); // flush outgoing serial
+    flush_generic_serial();
+
+    while !UART1.lock().is_rx_empty() {
+        if let Some(packet) = recv_packet().unwrap() {
+            handle_packet(packet);
+        }
+    }
+
+    dispatch_touch_events();
+}
 
 /// Adds a new simple task to the task scheduler.
-pub extern "C" fn vexTaskAdd(
-    callback: unsafe extern "C" fn() -> c_int,
-    interval: c_int,
-    label: *const c_char,
-) {
+///
+/// The task runs `callback` on its own stack, and is resumed at most once
+/// every `interval` milliseconds.
+///
+/// # Safety
+///
+/// `label` must be null or satisfy the safety requirements outlined by
+/// [`CStr::from_ptr`].
+pub unsafe extern "C" fn vexTaskAdd(callback: TaskCallback, interval: c_int, label: *const c_char) {
+    let label = if label.is_null() {
+        CString::default()
+    } else {
+        unsafe { CStr::from_ptr(label) }.into()
+    };
+
+    let mut stack = alloc::vec![0u64; TASK_STACK_SIZE / size_of::<u64>()].into_boxed_slice();
+    let context = Box::new(Context {
+        sp: stack.as_mut_ptr_range().end as u32,
+        lr: task_entry as usize as u32,
+        ..Default::default()
+    });
+
+    let task = Task {
+        callback,
+        interval: interval.max(0) as u32,
+        label,
+        context,
+        _stack: stack,
+        resumed_at: 0,
+        wake_at: 0,
+        finished: false,
+    };
+
+    let mut scheduler = SCHEDULER.lock();
+    if let Some(slot) = scheduler.tasks.iter_mut().find(|slot| slot.is_none()) {
+        *slot = Some(task);
+    } else if scheduler.tasks.len() < MAX_TASKS {
+        scheduler.tasks.push(Some(task));
+    } else {
+        log::warn!("Couldn't add task {:?}: too many tasks", task.label);
+    }
 }
 
 /// Gets a tasks's callback function and internal ID.
-pub extern "C" fn vexTaskGetCallbackAndId(index: u32, callback_id: *mut c_int) -> *mut c_void {
-    core::ptr::null_mut()
+///
+/// Returns null if there is no task at `index`.
+///
+/// # Safety
+///
+/// `callback_id` must be null or valid for writes.
+pub unsafe extern "C" fn vexTaskGetCallbackAndId(
+    index: u32,
+    callback_id: *mut c_int,
+) -> *mut c_void {
+    let scheduler = SCHEDULER.lock();
+    let Some(Some(task)) = scheduler.tasks.get(index as usize) else {
+        return core::ptr::null_mut();
+    };
+
+    if !callback_id.is_null() {
+        unsafe {
+            *callback_id = index as c_int;
+        }
+    }
+    task.callback as *mut c_void
 }
 
 /// Yields execution away from the current task for a given number of
 /// milliseconds.
-pub extern "C" fn vexTaskSleep(time: u32) {}
+///
+/// Outside of a task, this runs the scheduler until the time has passed.
+pub extern "C" fn vexTaskSleep(time: u32) {
+    let wake_at = vexSystemTimeGet().saturating_add(time);
+
+    if SCHEDULER.lock().current.is_some() {
+        suspend(|task| wake_at.max(task.resumed_at.saturating_add(task.interval)));
+    } else {
+        while vexSystemTimeGet() < wake_at {
+            vexTasksRun();
+        }
+    }
+}
 
 /// Returns the maximum number of threads that are supported by the VEXos
 /// task scheduler.
-///
-/// Real V5 enviornments set this at 128, but since we don't currently emulate
-/// the VEXcode task scheduler, we just return 0.
 pub extern "C" fn vexTaskHardwareConcurrency() -> i32 {
-    0
+    MAX_TASKS as i32
 }
 
 /// Unknown use; on the partner SDK this is aliased to vexTasksRun,
 /// but this real version on the jumptable isn't understood well.
-pub extern "C" fn vexBackgroundProcessing() {}
+///
+/// We treat it the same as [`vexTasksRun`].
+pub extern "C" fn vexBackgroundProcessing() {
+    vexTasksRun();
+}
 
-/// Ticks's the (non-existent in this environment) internal VEXos task
-/// scheduler
+/// Ticks the VEXos task scheduler.
 ///
-/// This more importantly handles many device reads and flushes
-/// serial.
+/// This handles many device reads and flushes serial, then resumes every task
+/// that is ready to run. When called from a task, the task yields instead.
 pub extern "C" fn vexTasksRun() {
-    USB1.lock().flush().unwrap(); // flush outgoing serial
+    process_devices();
 
-    while !UART1.lock().is_rx_empty() {
-        if let Some(packet) = recv_packet().unwrap() {
-            match packet {
-                KernelBoundPacket::BatteryUpdate { data, timestamp } => {
-                    let mut battery = BATTERY.lock();
-                    battery.data = Some(data);
-                    battery.timestamp = timestamp;
-                }
-                KernelBoundPacket::SmartPortUpdate {
-                    port_index,
-                    data,
-                    timestamp,
-                } => {
-                    if let Some(port) = SMARTPORTS.get(port_index as usize) {
-                        port.lock().update(data, timestamp);
-                    }
-                }
-                KernelBoundPacket::ControllerUpdate {
-                    id,
-                    data,
-                    timestamp,
-                } => {},
-                KernelBoundPacket::Touch(data) => {
-                    TOUCH.lock().data = data;
-                }
-                KernelBoundPacket::UsbSerial(data) => {
-                    USB1.lock().rx.extend(data);
+    if SCHEDULER.lock().current.is_some() {
+        yield_task();
+    } else {
+        run_tasks();
+    }
+}
+
+/// Applies a packet sent by the host to the kernel's view of the brain's
+/// peripherals.
+pub fn handle_packet(packet: KernelBoundPacket) {
+    match packet {
+        KernelBoundPacket::BatteryUpdate { data, timestamp } => {
+            let mut battery = BATTERY.lock();
+            battery.data = Some(data);
+            battery.timestamp = timestamp;
+        }
+        KernelBoundPacket::SmartPortUpdate {
+            port_index,
+            data,
+            timestamp,
+        } => {
+            if let Some(port) = SMARTPORTS.get(port_index as usize) {
+                port.lock().update(data, timestamp);
+            } else if port_index as usize == SMARTPORTS.len() {
+                ONBOARD_ADI.lock().update(data, timestamp);
+            }
+        }
+        KernelBoundPacket::ControllerUpdate {
+            id,
+            data,
+            timestamp,
+        } => {
+            Controller::get(id).lock().update(data, timestamp);
+        }
+        KernelBoundPacket::Touch(data) => {
+            TOUCH.lock().update(data);
+        }
+        KernelBoundPacket::CompetitionUpdate(data) => {
+            *COMPETITION.lock() = data;
+        }
+        KernelBoundPacket::UsbSerial(data) => {
+            USB1.lock().rx.extend(data);
+        }
+        KernelBoundPacket::GenericSerial { port_index, data } => {
+            if let Some(serial) = GENERIC_SERIAL_PORTS.get(port_index as usize) {
+                let mut serial = serial.lock();
+
+                // Bytes sent to a port that isn't in generic serial mode are lost.
+                if serial.enabled {
+                    serial.receive(data);
                 }
-                _ => panic!("Unexpected kernel-bound packet {:?}", packet),
             }
         }
+        KernelBoundPacket::RealTime(unix_time) => {
+            set_real_time(unix_time);
+        }
+        KernelBoundPacket::BootTime(unix_time) => {
+            set_boot_time(unix_time);
+        }
+        KernelBoundPacket::GenericRadio { port_index, data } => {
+            if let Some(buffer) = GENERIC_RADIO_BUFFERS.get(port_index as usize) {
+                buffer.lock().extend(data);
+            }
+        }
+        _ => panic!("Unexpected kernel-bound packet {:?}", packet),
     }
 }
@@ -1,13 +1,32 @@
 //! Brain Screen Touchscreen
 
-use crate::sync::Mutex;
+use alloc::collections::VecDeque;
+
 use vex_sdk::*;
-use vex_v5_qemu_protocol::{geometry::Point2, touch::{TouchData, TouchEvent}};
+use vex_v5_qemu_protocol::{
+    geometry::Point2,
+    touch::{TouchData, TouchEvent},
+};
+
+use crate::sync::Mutex;
 
 pub static TOUCH: Mutex<Touchscreen> = Mutex::new(Touchscreen::new());
 
+/// The most touch events that are kept for the user callback. Older drags are
+/// dropped if the program doesn't process them quickly enough, but presses and
+/// releases are always kept.
+const MAX_PENDING_EVENTS: usize = 64;
+
+/// Callback registered by the user program to be notified of touch events.
+type TouchCallback = unsafe extern "C" fn(V5_TouchEvent, i32, i32);
+
 pub struct Touchscreen {
     pub data: TouchData,
+    press_count: i32,
+    release_count: i32,
+    callback: Option<TouchCallback>,
+    /// Events that haven't been passed to the user callback yet.
+    pending_events: VecDeque<TouchData>,
 }
 
 impl Touchscreen {
@@ -18,27 +37,124 @@ impl Touchscreen {
                 event: TouchEvent::Release,
                 point: Point2 { x: 0, y: 0 },
             },
+            press_count: 0,
+            release_count: 0,
+            callback: None,
+            pending_events: VecDeque::new(),
+        }
+    }
+
+    /// Applies touch data sent by the host, counting presses and releases.
+    pub fn update(&mut self, data: TouchData) {
+        let was_pressed = self.data.event != TouchEvent::Release;
+        let pressed = data.event != TouchEvent::Release;
+
+        if pressed != was_pressed {
+            if pressed {
+                self.press_count += 1;
+            } else {
+                self.release_count += 1;
+            }
+        }
+
+        // Drags are passed to the callback as well as presses and releases. The
+        // host also sends the point once a press turns into a hold, even if it
+        // hasn't moved.
+        let dragged = data.event == TouchEvent::Held && data.point != self.data.point;
+        if (pressed != was_pressed || dragged) && self.callback.is_some() {
+            self.queue_event(data);
+        }
+
+        self.data = data;
+    }
+
+    /// Adds an event to be passed to the user callback.
+    fn queue_event(&mut self, data: TouchData) {
+        if data.event == TouchEvent::Held {
+            // Only the latest point of a drag that hasn't been handled yet matters.
+            if let Some(last) = self
+                .pending_events
+                .back_mut()
+                .filter(|last| last.event == TouchEvent::Held)
+            {
+                *last = data;
+                return;
+            }
+        }
+
+        if self.pending_events.len() >= MAX_PENDING_EVENTS {
+            let oldest_drag = self
+                .pending_events
+                .iter()
+                .position(|event| event.event == TouchEvent::Held);
+
+            match oldest_drag {
+                Some(index) => {
+                    self.pending_events.remove(index);
+                }
+                None if data.event == TouchEvent::Held => return,
+                None => {}
+            }
         }
+
+        self.pending_events.push_back(data);
     }
 }
 
-pub extern "C" fn vexTouchUserCallbackSet(callback: unsafe extern "C" fn(V5_TouchEvent, i32, i32)) {
+fn touch_event(event: TouchEvent) -> V5_TouchEvent {
+    match event {
+        TouchEvent::Press => V5_TouchEvent::kTouchEventPress,
+        TouchEvent::Release => V5_TouchEvent::kTouchEventRelease,
+        TouchEvent::Held => V5_TouchEvent::kTouchEventPressAuto,
+    }
+}
+
+/// Passes any touch events received since the last call to the user
+/// callback.
+///
+/// This is called from the background processing done by
+/// [`vexTasksRun`](super::vexTasksRun), rather than when the events arrive, so
+/// that the callback never runs in the middle of another SDK function.
+pub fn dispatch_touch_events() {
+    loop {
+        // The lock can't be held while the callback runs, since it may read the
+        // touchscreen itself.
+        let Some((callback, data)) = ({
+            let mut touch = TOUCH.lock();
+            touch.callback.zip(touch.pending_events.pop_front())
+        }) else {
+            return;
+        };
+
+        unsafe {
+            callback(
+                touch_event(data.event),
+                data.point.x.into(),
+                data.point.y.into(),
+            );
+        }
+    }
+}
+
+/// Registers a function to be called whenever the screen is pressed,
+/// dragged or released.
+pub extern "C" fn vexTouchUserCallbackSet(callback: TouchCallback) {
+    let mut touch = TOUCH.lock();
+    touch.callback = Some(callback);
+    touch.pending_events.clear();
 }
 /// # Safety
 ///
 /// - `device` must be a valid, non-null pointer to a V5_TouchStatus instance
 pub unsafe extern "C" fn vexTouchDataGet(status: *mut V5_TouchStatus) {
-    let data = TOUCH.lock().data;
+    let touch = TOUCH.lock();
     unsafe {
         *status = V5_TouchStatus {
-            lastEvent: match data.event {
-                TouchEvent::Press => V5_TouchEvent::kTouchEventPress,
-                TouchEvent::Release => V5_TouchEvent::kTouchEventRelease,
-            },
-            lastXpos: data.point.x,
-            lastYpos: data.point.y,
-            pressCount: 0, // TODO
-            releaseCount: 0,
+            lastEvent: touch_event(touch.data.event),
+            lastXpos: touch.data.point.x,
+            lastYpos: touch.data.point.y,
+            pressCount: touch.press_count,
+            releaseCount: touch.release_count,
         }
     }
 }
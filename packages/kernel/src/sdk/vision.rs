@@ -1,58 +1,211 @@
 //! V5 Vision Sensor
 
 use vex_sdk::*;
+use vex_v5_qemu_protocol::vision::{VisionCommand, VisionData};
 
-pub extern "C" fn vexDeviceVisionModeSet(device: V5_DeviceT, mode: V5VisionMode) {}
-pub extern "C" fn vexDeviceVisionModeGet(device: V5_DeviceT) -> V5VisionMode {
-    Default::default()
+use super::{send_device_command, with_device};
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionModeSet(device: V5_DeviceT, mode: V5VisionMode) {
+    unsafe { send_device_command(device, VisionCommand::SetMode(mode.0)) }
 }
-pub extern "C" fn vexDeviceVisionObjectCountGet(device: V5_DeviceT) -> i32 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionModeGet(device: V5_DeviceT) -> V5VisionMode {
+    V5VisionMode(unsafe { with_device(device, |data: &VisionData| data.mode) }.unwrap_or_default())
 }
-pub extern "C" fn vexDeviceVisionObjectGet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionObjectCountGet(device: V5_DeviceT) -> i32 {
+    unsafe { with_device(device, |data: &VisionData| data.objects.len() as i32) }
+        .unwrap_or_default()
+}
+
+/// Writes the object at `index` to `object`, where objects are sorted from
+/// largest to smallest.
+///
+/// Returns 1 if there was an object at `index`, or 0 otherwise.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `object` must be a valid pointer to a [`V5_DeviceVisionObject`]
+pub unsafe extern "C" fn vexDeviceVisionObjectGet(
     device: V5_DeviceT,
     index: u32,
     object: *mut V5_DeviceVisionObject,
 ) -> i32 {
-    Default::default()
+    let Some(found) = unsafe {
+        with_device(device, |data: &VisionData| {
+            data.objects.get(index as usize).copied()
+        })
+    }
+    .flatten() else {
+        return 0;
+    };
+
+    unsafe { object.write_unaligned(found.into()) }
+
+    1
 }
-pub extern "C" fn vexDeviceVisionSignatureSet(
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `signature` must be a valid pointer to a [`V5_DeviceVisionSignature`]
+pub unsafe extern "C" fn vexDeviceVisionSignatureSet(
     device: V5_DeviceT,
     signature: *mut V5_DeviceVisionSignature,
 ) {
+    let signature = unsafe { signature.read_unaligned() };
+
+    unsafe { send_device_command(device, VisionCommand::SetSignature(signature.into())) }
 }
-pub extern "C" fn vexDeviceVisionSignatureGet(
+
+/// Writes the signature with the given `id` to `signature`.
+///
+/// Returns false if the signature hasn't been configured.
+///
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+/// - `signature` must be a valid pointer to a [`V5_DeviceVisionSignature`]
+pub unsafe extern "C" fn vexDeviceVisionSignatureGet(
     device: V5_DeviceT,
     id: u32,
     signature: *mut V5_DeviceVisionSignature,
 ) -> bool {
-    Default::default()
+    let Some(found) = unsafe {
+        with_device(device, |data: &VisionData| {
+            data.signatures
+                .iter()
+                .find(|signature| signature.id as u32 == id)
+                .copied()
+        })
+    }
+    .flatten() else {
+        return false;
+    };
+
+    unsafe { signature.write_unaligned(found.into()) }
+
+    true
 }
-pub extern "C" fn vexDeviceVisionBrightnessSet(device: V5_DeviceT, percent: u8) {}
-pub extern "C" fn vexDeviceVisionBrightnessGet(device: V5_DeviceT) -> u8 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionBrightnessSet(device: V5_DeviceT, percent: u8) {
+    unsafe { send_device_command(device, VisionCommand::SetBrightness(percent)) }
 }
-pub extern "C" fn vexDeviceVisionWhiteBalanceModeSet(device: V5_DeviceT, mode: V5VisionWBMode) {}
-pub extern "C" fn vexDeviceVisionWhiteBalanceModeGet(device: V5_DeviceT) -> V5VisionWBMode {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionBrightnessGet(device: V5_DeviceT) -> u8 {
+    unsafe { with_device(device, |data: &VisionData| data.brightness) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceVisionWhiteBalanceSet(device: V5_DeviceT, color: V5_DeviceVisionRgb) {}
-pub extern "C" fn vexDeviceVisionWhiteBalanceGet(device: V5_DeviceT) -> V5_DeviceVisionRgb {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionWhiteBalanceModeSet(
+    device: V5_DeviceT,
+    mode: V5VisionWBMode,
+) {
+    unsafe { send_device_command(device, VisionCommand::SetWhiteBalanceMode(mode.0)) }
 }
-pub extern "C" fn vexDeviceVisionLedModeSet(device: V5_DeviceT, mode: V5VisionLedMode) {}
-pub extern "C" fn vexDeviceVisionLedModeGet(device: V5_DeviceT) -> V5VisionLedMode {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionWhiteBalanceModeGet(device: V5_DeviceT) -> V5VisionWBMode {
+    V5VisionWBMode(
+        unsafe { with_device(device, |data: &VisionData| data.white_balance_mode) }
+            .unwrap_or_default(),
+    )
 }
-pub extern "C" fn vexDeviceVisionLedBrigntnessSet(device: V5_DeviceT, percent: u8) {}
-pub extern "C" fn vexDeviceVisionLedBrigntnessGet(device: V5_DeviceT) -> u8 {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionWhiteBalanceSet(
+    device: V5_DeviceT,
+    color: V5_DeviceVisionRgb,
+) {
+    unsafe { send_device_command(device, VisionCommand::SetWhiteBalance(color.into())) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionWhiteBalanceGet(device: V5_DeviceT) -> V5_DeviceVisionRgb {
+    unsafe { with_device(device, |data: &VisionData| data.white_balance.into()) }
+        .unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionLedModeSet(device: V5_DeviceT, mode: V5VisionLedMode) {
+    unsafe { send_device_command(device, VisionCommand::SetLedMode(mode.0)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionLedModeGet(device: V5_DeviceT) -> V5VisionLedMode {
+    V5VisionLedMode(
+        unsafe { with_device(device, |data: &VisionData| data.led_mode) }.unwrap_or_default(),
+    )
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionLedBrigntnessSet(device: V5_DeviceT, percent: u8) {
+    unsafe { send_device_command(device, VisionCommand::SetLedBrightness(percent)) }
 }
-pub extern "C" fn vexDeviceVisionLedColorSet(device: V5_DeviceT, color: V5_DeviceVisionRgb) {}
-pub extern "C" fn vexDeviceVisionLedColorGet(device: V5_DeviceT) -> V5_DeviceVisionRgb {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionLedBrigntnessGet(device: V5_DeviceT) -> u8 {
+    unsafe { with_device(device, |data: &VisionData| data.led_brightness) }.unwrap_or_default()
 }
-pub extern "C" fn vexDeviceVisionWifiModeSet(device: V5_DeviceT, mode: V5VisionWifiMode) {}
-pub extern "C" fn vexDeviceVisionWifiModeGet(device: V5_DeviceT) -> V5VisionWifiMode {
-    Default::default()
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionLedColorSet(device: V5_DeviceT, color: V5_DeviceVisionRgb) {
+    unsafe { send_device_command(device, VisionCommand::SetLedColor(color.into())) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionLedColorGet(device: V5_DeviceT) -> V5_DeviceVisionRgb {
+    unsafe { with_device(device, |data: &VisionData| data.led_color.into()) }.unwrap_or_default()
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionWifiModeSet(device: V5_DeviceT, mode: V5VisionWifiMode) {
+    unsafe { send_device_command(device, VisionCommand::SetWifiMode(mode.0)) }
+}
+
+/// # Safety
+///
+/// - `device` must be a valid, non-null pointer to a device handle
+pub unsafe extern "C" fn vexDeviceVisionWifiModeGet(device: V5_DeviceT) -> V5VisionWifiMode {
+    V5VisionWifiMode(
+        unsafe { with_device(device, |data: &VisionData| data.wifi_mode) }.unwrap_or_default(),
+    )
 }
@@ -0,0 +1,152 @@
+use alloc::vec::Vec;
+
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vex_sdk::V5_AdiPortConfiguration;
+
+/// Number of three-wire ports on the brain or on a three-wire expander.
+pub const ADI_PORT_COUNT: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AdiData {
+    pub ports: [AdiPort; ADI_PORT_COUNT],
+}
+
+/// The state of a single three-wire port.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AdiPort {
+    pub config: AdiPortConfig,
+    /// The port's current value, whose meaning depends on its configuration.
+    ///
+    /// For inputs, this is the value read from the connected sensor. For
+    /// outputs, this is the last value written by the brain.
+    pub value: i32,
+}
+
+/// What a three-wire port has been configured to do.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AdiPortConfig {
+    /// 12-bit analog input, from 0 to 4095.
+    #[default]
+    AnalogIn,
+    /// 8-bit analog output, from 0 to 255.
+    AnalogOut,
+    DigitalIn,
+    DigitalOut,
+    SmartButton,
+    SmartPot,
+    LegacyButton,
+    LegacyPotentiometer,
+    LegacyLineSensor,
+    LegacyLightSensor,
+    /// Heading of a legacy yaw-rate gyro, in tenths of a degree.
+    LegacyGyro,
+    LegacyAccelerometer,
+    /// Servo position, from -127 to 127.
+    LegacyServo,
+    /// Motor controller power, from -127 to 127.
+    LegacyPwm,
+    /// Ticks counted by a quadrature encoder. Encoders use two adjacent
+    /// ports, and report their value on the first.
+    QuadEncoder,
+    /// Distance measured by an ultrasonic rangefinder, in millimeters.
+    /// Rangefinders use two adjacent ports, and report their value on the
+    /// first.
+    Sonar,
+    /// Motor controller power with slew rate limiting, from -127 to 127.
+    LegacyPwmSlew,
+    Undefined,
+}
+
+impl AdiPortConfig {
+    /// Returns `true` if ports with this configuration are driven by the brain
+    /// rather than by a sensor.
+    pub const fn is_output(&self) -> bool {
+        matches!(
+            self,
+            Self::AnalogOut
+                | Self::DigitalOut
+                | Self::LegacyServo
+                | Self::LegacyPwm
+                | Self::LegacyPwmSlew
+        )
+    }
+}
+
+impl From<AdiPortConfig> for V5_AdiPortConfiguration {
+    fn from(config: AdiPortConfig) -> Self {
+        match config {
+            AdiPortConfig::AnalogIn => Self::kAdiPortTypeAnalogIn,
+            AdiPortConfig::AnalogOut => Self::kAdiPortTypeAnalogOut,
+            AdiPortConfig::DigitalIn => Self::kAdiPortTypeDigitalIn,
+            AdiPortConfig::DigitalOut => Self::kAdiPortTypeDigitalOut,
+            AdiPortConfig::SmartButton => Self::kAdiPortTypeSmartButton,
+            AdiPortConfig::SmartPot => Self::kAdiPortTypeSmartPot,
+            AdiPortConfig::LegacyButton => Self::kAdiPortTypeLegacyButton,
+            AdiPortConfig::LegacyPotentiometer => Self::kAdiPortTypeLegacyPotentiometer,
+            AdiPortConfig::LegacyLineSensor => Self::kAdiPortTypeLegacyLineSensor,
+            AdiPortConfig::LegacyLightSensor => Self::kAdiPortTypeLegacyLightSensor,
+            AdiPortConfig::LegacyGyro => Self::kAdiPortTypeLegacyGyro,
+            AdiPortConfig::LegacyAccelerometer => Self::kAdiPortTypeLegacyAccelerometer,
+            AdiPortConfig::LegacyServo => Self::kAdiPortTypeLegacyServo,
+            AdiPortConfig::LegacyPwm => Self::kAdiPortTypeLegacyPwm,
+            AdiPortConfig::QuadEncoder => Self::kAdiPortTypeQuadEncoder,
+            AdiPortConfig::Sonar => Self::kAdiPortTypeSonar,
+            AdiPortConfig::LegacyPwmSlew => Self::kAdiPortTypeLegacyPwmSlew,
+            AdiPortConfig::Undefined => Self::kAdiPortTypeUndefined,
+        }
+    }
+}
+
+impl From<V5_AdiPortConfiguration> for AdiPortConfig {
+    fn from(config: V5_AdiPortConfiguration) -> Self {
+        match config {
+            V5_AdiPortConfiguration::kAdiPortTypeAnalogIn => Self::AnalogIn,
+            V5_AdiPortConfiguration::kAdiPortTypeAnalogOut => Self::AnalogOut,
+            V5_AdiPortConfiguration::kAdiPortTypeDigitalIn => Self::DigitalIn,
+            V5_AdiPortConfiguration::kAdiPortTypeDigitalOut => Self::DigitalOut,
+            V5_AdiPortConfiguration::kAdiPortTypeSmartButton => Self::SmartButton,
+            V5_AdiPortConfiguration::kAdiPortTypeSmartPot => Self::SmartPot,
+            V5_AdiPortConfiguration::kAdiPortTypeLegacyButton => Self::LegacyButton,
+            V5_AdiPortConfiguration::kAdiPortTypeLegacyPotentiometer => Self::LegacyPotentiometer,
+            V5_AdiPortConfiguration::kAdiPortTypeLegacyLineSensor => Self::LegacyLineSensor,
+            V5_AdiPortConfiguration::kAdiPortTypeLegacyLightSensor => Self::LegacyLightSensor,
+            V5_AdiPortConfiguration::kAdiPortTypeLegacyGyro => Self::LegacyGyro,
+            V5_AdiPortConfiguration::kAdiPortTypeLegacyAccelerometer => Self::LegacyAccelerometer,
+            V5_AdiPortConfiguration::kAdiPortTypeLegacyServo => Self::LegacyServo,
+            V5_AdiPortConfiguration::kAdiPortTypeLegacyPwm => Self::LegacyPwm,
+            V5_AdiPortConfiguration::kAdiPortTypeQuadEncoder => Self::QuadEncoder,
+            V5_AdiPortConfiguration::kAdiPortTypeSonar => Self::Sonar,
+            V5_AdiPortConfiguration::kAdiPortTypeLegacyPwmSlew => Self::LegacyPwmSlew,
+            _ => Self::Undefined,
+        }
+    }
+}
+
+/// A command sent from the brain to a set of three-wire ports.
+///
+/// Ports are zero-indexed, so port A is 0 and port H is 7.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AdiCommand {
+    /// Change what a port is configured to do.
+    SetConfig { port: u8, config: AdiPortConfig },
+
+    /// Write a value to a port.
+    ///
+    /// Writing to an output drives the port, while writing to a counting input
+    /// such as an encoder resets its count.
+    SetValue { port: u8, value: i32 },
+
+    /// Set the colors of an addressable LED strip connected to a port,
+    /// starting from the LED at `offset`.
+    SetLeds {
+        port: u8,
+        offset: u32,
+        colors: Vec<u32>,
+    },
+}
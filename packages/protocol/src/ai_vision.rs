@@ -0,0 +1,249 @@
+use alloc::{string::String, vec::Vec};
+
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vex_sdk::{
+    V5_DeviceAiVisionCode, V5_DeviceAiVisionColor, V5_DeviceAiVisionColorData,
+    V5_DeviceAiVisionModelData, V5_DeviceAiVisionObject, V5_DeviceAiVisionObjectData,
+    V5_DeviceAiVisionTagData,
+};
+
+use crate::geometry::Point2;
+
+/// Number of color descriptions that can be stored on an AI vision sensor.
+pub const AI_VISION_COLOR_COUNT: usize = 7;
+
+/// Number of color codes that can be stored on an AI vision sensor.
+pub const AI_VISION_CODE_COUNT: usize = 8;
+
+/// Maximum number of colors in a color code.
+pub const AI_VISION_CODE_MAX_LEN: usize = 7;
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AiVisionData {
+    /// Objects currently detected by the sensor.
+    pub objects: Vec<AiVisionObject>,
+    /// Names of the classes detected by the sensor's AI model, indexed by
+    /// class ID.
+    pub class_names: Vec<String>,
+    /// Color descriptions configured by the brain.
+    pub colors: Vec<AiVisionColor>,
+    /// Color codes configured by the brain.
+    pub codes: Vec<AiVisionCode>,
+    pub mode: u32,
+    pub status: u32,
+    /// Temperature of the sensor in degrees celsius.
+    pub temperature: f64,
+    pub brightness: f64,
+    pub contrast: f64,
+}
+
+/// An object detected by the AI vision sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AiVisionObject {
+    /// An object matching one of the configured colors.
+    Color {
+        /// ID of the matched color.
+        id: u8,
+        bounds: AiVisionBounds,
+        /// Angle of the object in tenths of a degree.
+        angle: u16,
+    },
+    /// An object matching one of the configured color codes.
+    Code {
+        /// ID of the matched code.
+        id: u8,
+        bounds: AiVisionBounds,
+        /// Angle of the object in tenths of a degree.
+        angle: u16,
+    },
+    /// An AprilTag.
+    Tag {
+        /// ID encoded in the tag.
+        id: u8,
+        /// Positions of the tag's four corners in pixels, starting from the
+        /// top left and going clockwise.
+        corners: [Point2<i16>; 4],
+    },
+    /// An object recognized by the sensor's AI model.
+    Model {
+        /// ID of the object's class.
+        id: u8,
+        bounds: AiVisionBounds,
+        /// Confidence of the detection in percent.
+        score: u16,
+    },
+}
+
+impl AiVisionObject {
+    pub const fn id(&self) -> u8 {
+        match self {
+            Self::Color { id, .. }
+            | Self::Code { id, .. }
+            | Self::Tag { id, .. }
+            | Self::Model { id, .. } => *id,
+        }
+    }
+}
+
+/// The bounding box of a detected object in pixels.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AiVisionBounds {
+    pub left: u16,
+    pub top: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl From<AiVisionObject> for V5_DeviceAiVisionObject {
+    fn from(object: AiVisionObject) -> Self {
+        let color = |bounds: AiVisionBounds, angle| V5_DeviceAiVisionObjectData {
+            color: V5_DeviceAiVisionColorData {
+                xoffset: bounds.left,
+                yoffset: bounds.top,
+                width: bounds.width,
+                height: bounds.height,
+                angle,
+            },
+        };
+
+        let (r#type, data) = match object {
+            AiVisionObject::Color { bounds, angle, .. } => (1 << 0, color(bounds, angle)),
+            AiVisionObject::Code { bounds, angle, .. } => (1 << 1, color(bounds, angle)),
+            AiVisionObject::Model { bounds, score, .. } => (
+                1 << 2,
+                V5_DeviceAiVisionObjectData {
+                    model: V5_DeviceAiVisionModelData {
+                        xoffset: bounds.left,
+                        yoffset: bounds.top,
+                        width: bounds.width,
+                        height: bounds.height,
+                        score,
+                    },
+                },
+            ),
+            AiVisionObject::Tag { corners, .. } => (
+                1 << 3,
+                V5_DeviceAiVisionObjectData {
+                    tag: V5_DeviceAiVisionTagData {
+                        x0: corners[0].x,
+                        y0: corners[0].y,
+                        x1: corners[1].x,
+                        y1: corners[1].y,
+                        x2: corners[2].x,
+                        y2: corners[2].y,
+                        x3: corners[3].x,
+                        y3: corners[3].y,
+                    },
+                },
+            ),
+        };
+
+        Self {
+            id: object.id(),
+            r#type,
+            object: data,
+        }
+    }
+}
+
+/// A color that the AI vision sensor matches objects against.
+#[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AiVisionColor {
+    /// ID of the color, from 1 to 7.
+    pub id: u8,
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    /// How far the hue of an object can be from this color's hue and still
+    /// match, in degrees.
+    pub hue_range: f32,
+    /// How far the saturation of an object can be from this color's
+    /// saturation and still match.
+    pub saturation_range: f32,
+}
+
+impl From<V5_DeviceAiVisionColor> for AiVisionColor {
+    fn from(color: V5_DeviceAiVisionColor) -> Self {
+        Self {
+            id: color.id,
+            red: color.red,
+            green: color.grn,
+            blue: color.blu,
+            hue_range: color.hangle,
+            saturation_range: color.hdsat,
+        }
+    }
+}
+
+impl From<AiVisionColor> for V5_DeviceAiVisionColor {
+    fn from(color: AiVisionColor) -> Self {
+        Self {
+            id: color.id,
+            red: color.red,
+            grn: color.green,
+            blu: color.blue,
+            hangle: color.hue_range,
+            hdsat: color.saturation_range,
+            reserved: 0,
+        }
+    }
+}
+
+/// A sequence of colors that the AI vision sensor detects as a single object
+/// when they appear next to each other.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AiVisionCode {
+    /// ID of the code, from 1 to 8.
+    pub id: u8,
+    /// IDs of the colors making up the code, from left to right.
+    pub colors: Vec<i16>,
+}
+
+impl From<V5_DeviceAiVisionCode> for AiVisionCode {
+    fn from(code: V5_DeviceAiVisionCode) -> Self {
+        let colors = [
+            code.c1, code.c2, code.c3, code.c4, code.c5, code.c6, code.c7,
+        ];
+
+        Self {
+            id: code.id,
+            colors: colors[..(code.len as usize).min(AI_VISION_CODE_MAX_LEN)].to_vec(),
+        }
+    }
+}
+
+impl From<AiVisionCode> for V5_DeviceAiVisionCode {
+    fn from(code: AiVisionCode) -> Self {
+        let mut colors = [0; AI_VISION_CODE_MAX_LEN];
+        let len = code.colors.len().min(AI_VISION_CODE_MAX_LEN);
+        colors[..len].copy_from_slice(&code.colors[..len]);
+
+        Self {
+            id: code.id,
+            len: len as u8,
+            c1: colors[0],
+            c2: colors[1],
+            c3: colors[2],
+            c4: colors[3],
+            c5: colors[4],
+            c6: colors[5],
+            c7: colors[6],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AiVisionCommand {
+    SetMode(u32),
+    SetColor(AiVisionColor),
+    SetCode(AiVisionCode),
+    SetSensor { brightness: f64, contrast: f64 },
+}
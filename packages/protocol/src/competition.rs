@@ -0,0 +1,38 @@
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CompetitionData {
+    pub mode: CompetitionMode,
+    /// How the brain is connected to a competition controller, or `None` if
+    /// it isn't connected to one.
+    pub connection: Option<CompetitionConnection>,
+}
+
+/// The phase of a match that the robot is in.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CompetitionMode {
+    /// The robot is disabled and may not move.
+    Disabled,
+
+    /// The robot is running its autonomous routine.
+    Autonomous,
+
+    /// The robot is being controlled by its drivers.
+    #[default]
+    Driver,
+}
+
+/// The kind of competition controller that a brain is connected to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CompetitionConnection {
+    /// A VEXnet field controller, as used in official matches.
+    FieldControl,
+
+    /// A competition switch plugged into the controller.
+    CompetitionSwitch,
+}
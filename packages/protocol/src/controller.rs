@@ -9,15 +9,20 @@ pub enum ControllerId {
     Partner,
 }
 
+/// The state of a controller's joysticks and buttons.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ControllerData {
-    pub axis_1: u8,
-    pub axis_2: u8,
-    pub axis_3: u8,
-    pub axis_4: u8,
-    pub axis_spare_1: u8,
-    pub axis_spare_2: u8,
+    /// Right joystick X axis, from -127 to 127.
+    pub axis_1: i8,
+    /// Right joystick Y axis, from -127 to 127.
+    pub axis_2: i8,
+    /// Left joystick Y axis, from -127 to 127.
+    pub axis_3: i8,
+    /// Left joystick X axis, from -127 to 127.
+    pub axis_4: i8,
+    pub axis_spare_1: i8,
+    pub axis_spare_2: i8,
     pub button_a: bool,
     pub button_b: bool,
     pub button_x: bool,
@@ -30,9 +35,12 @@ pub struct ControllerData {
     pub button_l2: bool,
     pub button_r1: bool,
     pub button_r2: bool,
+    /// Whether any of the other buttons are pressed.
     pub button_all: bool,
-    pub button_level: bool,
     pub button_sel: bool,
+    /// Battery level of the controller in percent.
+    pub battery_level: u8,
+    /// Battery capacity of the controller in percent.
     pub battery_capacity: u8,
     pub flags: u32,
 }
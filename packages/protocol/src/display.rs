@@ -33,6 +33,25 @@ pub enum DrawCommand {
     },
 }
 
+/// The file format of an image passed to one of the `vexImage` functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Encode, Decode, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImageFormat {
+    Png,
+    Bmp,
+}
+
+/// An image that has been decoded into pixels that can be copied to the
+/// display.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Encode, Decode, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DecodedImage {
+    pub width: u16,
+    pub height: u16,
+    /// The image's pixels in `0xAARRGGBB` format, row by row.
+    pub pixels: Vec<u32>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Encode, Decode, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ScrollLocation {
@@ -55,6 +74,14 @@ pub struct TextSize {
     pub denom: u32,
 }
 
+/// The space that a string takes up on the display, in pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Encode, Decode, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TextMetrics {
+    pub width: u32,
+    pub height: u32,
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Encode, Decode, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DisplayRenderMode {
@@ -79,4 +106,8 @@ pub enum Shape {
         start: Point2<i32>,
         end: Point2<i32>,
     },
+    /// A single point, which is drawn as a square the size of the pen.
+    Pixel {
+        position: Point2<i32>,
+    },
 }
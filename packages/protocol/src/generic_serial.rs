@@ -0,0 +1,18 @@
+use alloc::vec::Vec;
+
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A command sent to a smartport that has been configured for generic serial
+/// communication.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GenericSerialCommand {
+    /// Switches the port into generic serial mode, clearing its buffers.
+    Enable { options: i32 },
+    /// Sets the baud rate of the port in bits per second.
+    SetBaudrate(i32),
+    /// Bytes transmitted by the brain.
+    Transmit(Vec<u8>),
+}
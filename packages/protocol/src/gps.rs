@@ -0,0 +1,92 @@
+use bincode::{Decode, Encode};
+use bitflags::bitflags;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    geometry::{Point2, Vector3},
+    impl_bincode_bitflags,
+    imu::{ImuAttitude, ImuQuaternion},
+};
+
+/// Readings from a GPS sensor.
+///
+/// Positions are measured in meters from the center of the field, and
+/// headings are measured in degrees clockwise from the positive Y axis.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GpsData {
+    /// Position of the robot, found by applying the origin offset to the
+    /// sensor's own position.
+    pub position: Point2<f64>,
+    /// Position of the sensor itself.
+    pub raw_position: Point2<f64>,
+    /// Heading of the robot, found by applying the rotation offset to the
+    /// sensor's own heading. Bounded from 0 to 360.
+    pub heading: f64,
+    /// Heading of the sensor itself, bounded from 0 to 360.
+    pub raw_heading: f64,
+    /// Unbounded heading of the robot.
+    pub rotation: f64,
+    /// Orientation of the robot, following the same conventions as an
+    /// inertial sensor.
+    pub quaternion: ImuQuaternion,
+    pub attitude: ImuAttitude,
+    /// Position of the sensor relative to the robot's center of rotation.
+    pub origin: Point2<f64>,
+    /// Heading of the sensor relative to the front of the robot.
+    pub rotation_offset: f64,
+    /// Estimated RMS error of the reported position in meters.
+    pub error: f64,
+    /// Angular velocity measured by the sensor's gyroscope in degrees per
+    /// second.
+    pub gyro: Vector3<f64>,
+    /// Acceleration measured by the sensor's accelerometer in G.
+    pub accel: Vector3<f64>,
+    pub status: GpsStatus,
+    /// Temperature of the sensor in degrees celsius.
+    pub temperature: f64,
+    pub mode: u32,
+    /// Interval at which the sensor produces new readings in milliseconds.
+    pub data_rate: u32,
+}
+
+/// A command sent from the brain to a GPS sensor.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GpsCommand {
+    /// Reset the unbounded rotation of the sensor to its current heading.
+    Reset,
+
+    /// Set the position of the sensor relative to the robot's center of
+    /// rotation (in meters).
+    SetOrigin(Point2<f64>),
+
+    /// Set the heading of the sensor relative to the front of the robot (in
+    /// degrees).
+    SetRotation(f64),
+
+    /// Set the position and heading of the robot to use until the sensor can
+    /// see the field strip.
+    SetInitialPosition { position: Point2<f64>, heading: f64 },
+
+    /// Set the sensor's operating mode.
+    SetMode(u32),
+
+    /// Set the interval at which the sensor produces new readings (in
+    /// milliseconds).
+    SetDataRate(u32),
+}
+
+bitflags! {
+    /// The status bits returned by a [`GpsData`].
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct GpsStatus: u32 {
+        /// The sensor can see the field strip, so its reported position is
+        /// valid.
+        const POSITION_VALID = 0x01;
+    }
+}
+
+impl_bincode_bitflags!(GpsStatus);
@@ -0,0 +1,74 @@
+use bincode::{Decode, Encode};
+use bitflags::bitflags;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{geometry::Vector3, impl_bincode_bitflags};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImuData {
+    /// Unbounded rotation around the yaw axis in degrees, increasing
+    /// clockwise.
+    pub rotation: f64,
+    /// Rotation around the yaw axis in degrees, bounded from 0 to 360.
+    pub heading: f64,
+    pub quaternion: ImuQuaternion,
+    pub attitude: ImuAttitude,
+    /// Angular velocity measured by the gyroscope in degrees per second.
+    pub gyro: Vector3<f64>,
+    /// Acceleration measured by the accelerometer in G.
+    pub accel: Vector3<f64>,
+    /// Temperature of the sensor in degrees celsius.
+    pub temperature: f64,
+    pub status: ImuStatus,
+    /// Orientation mode of the sensor, as set by `vexDeviceImuModeSet`.
+    pub mode: u32,
+    /// Interval at which the sensor produces new readings in milliseconds.
+    pub data_rate: u32,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImuQuaternion {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub w: f64,
+}
+
+/// Euler angles of the sensor in degrees, each bounded from -180 to 180.
+#[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImuAttitude {
+    pub pitch: f64,
+    pub roll: f64,
+    pub yaw: f64,
+}
+
+/// A command sent from the brain to an inertial sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImuCommand {
+    /// Recalibrate the sensor and zero its readings.
+    Reset,
+
+    /// Set the sensor's orientation mode.
+    SetMode(u32),
+
+    /// Set the interval at which the sensor produces new readings (in
+    /// milliseconds).
+    SetDataRate(u32),
+}
+
+bitflags! {
+    /// The status bits returned by an [`ImuData`].
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct ImuStatus: u32 {
+        /// The sensor is calibrating.
+        const CALIBRATING = 0x01;
+    }
+}
+
+impl_bincode_bitflags!(ImuStatus);
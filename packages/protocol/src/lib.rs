@@ -2,29 +2,56 @@
 
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use core::{num::NonZeroU32, option::Option};
 
+use adi::{AdiCommand, AdiData};
+use ai_vision::{AiVisionCommand, AiVisionData};
 use battery::BatteryData;
 use bincode::{Decode, Encode};
 use code_signature::CodeSignature;
+use competition::CompetitionData;
 use controller::{ControllerData, ControllerId};
-use display::{Color, DrawCommand, ScrollLocation};
+use display::{
+    Color, DecodedImage, DrawCommand, ImageFormat, ScrollLocation, TextFont, TextMetrics, TextSize,
+};
 use distance_sensor::DistanceSensorData;
+use generic_serial::GenericSerialCommand;
 use geometry::Rect;
+use gps::{GpsCommand, GpsData};
+use imu::{ImuCommand, ImuData};
+use motor::{MotorCommand, MotorData};
+use optical_sensor::{OpticalSensorCommand, OpticalSensorData};
+use pneumatic::{PneumaticCommand, PneumaticData};
+use radio::{RadioCommand, RadioData};
+use rotation_sensor::{RotationSensorCommand, RotationSensorData};
+use sd_card::{FileError, FileRequest, FileResponse};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use vision::{VisionCommand, VisionData};
 
 use crate::touch::TouchData;
 
+pub mod adi;
+pub mod ai_vision;
 pub mod battery;
 pub mod code_signature;
+pub mod competition;
 pub mod controller;
 pub mod display;
 pub mod distance_sensor;
+pub mod generic_serial;
 pub mod geometry;
+pub mod gps;
+pub mod imu;
 pub mod motor;
+pub mod optical_sensor;
+pub mod pneumatic;
+pub mod radio;
+pub mod rotation_sensor;
+pub mod sd_card;
 pub mod touch;
+pub mod vision;
 
 /// A message sent from the guest to the host.
 #[derive(Debug, Clone, PartialEq, PartialOrd, Encode, Decode)]
@@ -34,8 +61,16 @@ pub enum HostBoundPacket {
     KernelSerial(Vec<u8>),
     CodeSignature(CodeSignature),
     ExitRequest(i32),
-    DisplayCommand { command: DisplayCommand },
-    SmartPortCommand { port: u8, command: SmartPortCommand },
+    DisplayCommand {
+        command: DisplayCommand,
+    },
+    SmartPortCommand {
+        port: u8,
+        command: SmartPortCommand,
+    },
+    /// A filesystem operation on the SD card. The host must reply with a
+    /// [`KernelBoundPacket::SdCardResponse`].
+    SdCardRequest(FileRequest),
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Encode, Decode)]
@@ -44,6 +79,8 @@ pub enum DisplayCommand {
     Draw {
         command: DrawCommand,
         color: Color,
+        /// The width of strokes and lines, and the size of pixels, in pixels.
+        pen_size: u32,
         clip_region: Rect,
     },
     Scroll {
@@ -58,6 +95,22 @@ pub enum DisplayCommand {
     },
     DisableDoubleBuffering,
     Render,
+    /// Decodes an image so that it can be drawn by the user program. The host
+    /// must reply with a [`KernelBoundPacket::DecodedImage`].
+    DecodeImage {
+        format: ImageFormat,
+        data: Vec<u8>,
+        /// The largest image that fits in the user program's buffer.
+        max_width: u32,
+        max_height: u32,
+    },
+    /// Measures how much space a string takes up when drawn. The host must
+    /// reply with a [`KernelBoundPacket::TextMetrics`].
+    MeasureText {
+        data: String,
+        font: TextFont,
+        size: TextSize,
+    },
 }
 
 /// A message sent from the host to the guest.
@@ -71,9 +124,11 @@ pub enum KernelBoundPacket {
         data: SmartPortData,
         timestamp: u32,
     },
+    /// Updates the state of a controller, or disconnects it if `data` is
+    /// `None`.
     ControllerUpdate {
         id: ControllerId,
-        data: ControllerData,
+        data: Option<ControllerData>,
         timestamp: u32,
     },
     BatteryUpdate {
@@ -81,17 +136,89 @@ pub enum KernelBoundPacket {
         timestamp: u32,
     },
     Touch(TouchData),
+    CompetitionUpdate(CompetitionData),
+    /// Bytes received by a smartport in generic serial mode.
+    GenericSerial {
+        port_index: u8,
+        data: Vec<u8>,
+    },
+    /// Bytes received over VEXlink by a smartport's radio.
+    GenericRadio {
+        port_index: u8,
+        data: Vec<u8>,
+    },
+    /// The result of the last [`HostBoundPacket::SdCardRequest`].
+    SdCardResponse(Result<FileResponse, FileError>),
+    /// The result of the last [`DisplayCommand::DecodeImage`], or `None` if the
+    /// image was invalid or too large.
+    DecodedImage(Option<DecodedImage>),
+    /// The result of the last [`DisplayCommand::MeasureText`].
+    TextMetrics(TextMetrics),
+    /// Sets the brain's real-time clock to a number of milliseconds since the
+    /// Unix epoch.
+    RealTime(u64),
+    /// Sets the brain's real-time clock so that it read a number of
+    /// milliseconds since the Unix epoch when the brain booted.
+    BootTime(u64),
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Encode, Decode)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum SmartPortData {
     DistanceSensor(DistanceSensorData),
+    Motor(MotorData),
+    Imu(ImuData),
+    RotationSensor(RotationSensorData),
+    OpticalSensor(OpticalSensorData),
+    Gps(GpsData),
+    Adi(AdiData),
+    Pneumatic(PneumaticData),
+    Vision(VisionData),
+    AiVision(AiVisionData),
+    Radio(RadioData),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Encode, Decode)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub enum SmartPortCommand {}
+pub enum SmartPortCommand {
+    Motor(MotorCommand),
+    Imu(ImuCommand),
+    RotationSensor(RotationSensorCommand),
+    OpticalSensor(OpticalSensorCommand),
+    Gps(GpsCommand),
+    Adi(AdiCommand),
+    Pneumatic(PneumaticCommand),
+    Vision(VisionCommand),
+    AiVision(AiVisionCommand),
+    GenericSerial(GenericSerialCommand),
+    Radio(RadioCommand),
+}
+
+macro_rules! impl_from_device_command {
+    ($($variant:ident($command:ty)),* $(,)?) => {
+        $(
+            impl From<$command> for SmartPortCommand {
+                fn from(command: $command) -> Self {
+                    Self::$variant(command)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_device_command!(
+    Motor(MotorCommand),
+    Imu(ImuCommand),
+    RotationSensor(RotationSensorCommand),
+    OpticalSensor(OpticalSensorCommand),
+    Gps(GpsCommand),
+    Adi(AdiCommand),
+    Pneumatic(PneumaticCommand),
+    Vision(VisionCommand),
+    AiVision(AiVisionCommand),
+    GenericSerial(GenericSerialCommand),
+    Radio(RadioCommand),
+);
 
 #[macro_export]
 macro_rules! impl_bincode_bitflags {
@@ -1,23 +1,277 @@
+use bincode::{Decode, Encode};
 use bitflags::bitflags;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use vex_sdk::{V5MotorBrakeMode, V5MotorControlMode, V5MotorEncoderUnits, V5MotorGearset};
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+use crate::impl_bincode_bitflags;
+
+/// Number of encoder ticks counted per revolution of the motor's armature
+/// (before the gear cartridge).
+pub const TICKS_PER_ARMATURE_REVOLUTION: u32 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MotorData {
+    /// Velocity of the output shaft in RPM.
     pub velocity: f64,
+    /// Position of the output shaft in raw encoder ticks.
     pub position: i32,
+    /// Current drawn by the motor in mA.
+    pub current: i32,
+    /// Voltage applied to the motor in mV.
+    pub voltage: i32,
+    /// Mechanical power output in watts.
     pub power: f64,
+    /// Torque at the output shaft in Nm.
     pub torque: f64,
+    /// Efficiency of the motor in percent.
     pub efficiency: f64,
+    /// Temperature of the motor in degrees celsius.
     pub temperature: f64,
     pub flags: MotorFlags,
     pub faults: MotorFaults,
+
+    pub gearset: MotorGearset,
+    pub brake_mode: MotorBrakeMode,
+    pub encoder_units: MotorEncoderUnits,
+    pub reversed: bool,
+    /// Current limit in mA.
+    pub current_limit: i32,
+    /// Voltage limit in mV.
+    pub voltage_limit: i32,
+    pub control: MotorControl,
+}
+
+/// What the motor's internal controller is currently trying to do.
+#[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MotorControl {
+    /// Stop the motor using its configured [`MotorBrakeMode`].
+    #[default]
+    Brake,
+
+    /// Apply a fixed voltage (in mV) to the motor.
+    Voltage(i32),
+
+    /// Spin the output shaft at a target velocity (in RPM).
+    Velocity(i32),
+
+    /// Move the output shaft to a target position (in raw encoder ticks)
+    /// without exceeding a maximum velocity (in RPM).
+    Position { target: f64, velocity: i32 },
+}
+
+impl From<MotorControl> for V5MotorControlMode {
+    fn from(control: MotorControl) -> Self {
+        match control {
+            MotorControl::Brake => Self::kMotorControlModeBRAKE,
+            MotorControl::Voltage(_) => Self::kMotorControlModeUNDEFINED,
+            MotorControl::Velocity(_) => Self::kMotorControlModeVELOCITY,
+            MotorControl::Position { .. } => Self::kMotorControlModePROFILE,
+        }
+    }
+}
+
+/// A command sent from the brain to a motor.
+///
+/// Positions are given in the motor's currently configured
+/// [`MotorEncoderUnits`], and velocities are in RPM.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MotorCommand {
+    /// Stop the motor using its configured [`MotorBrakeMode`].
+    Stop,
+
+    /// Apply a fixed voltage (in mV) to the motor.
+    SetVoltage(i32),
+
+    /// Spin the motor at a target velocity.
+    SetVelocity(i32),
+
+    /// Change the target velocity of the motor without changing its control
+    /// mode.
+    UpdateVelocity(i32),
+
+    /// Move the motor to an absolute position.
+    SetAbsoluteTarget {
+        position: f64,
+        velocity: i32,
+    },
+
+    /// Move the motor by an offset from its current position.
+    SetRelativeTarget {
+        position: f64,
+        velocity: i32,
+    },
+
+    /// Move the motor to an absolute position using its current target
+    /// velocity.
+    SetServoTarget(f64),
+
+    /// Overwrite the motor's current position.
+    SetPosition(f64),
+
+    SetReversed(bool),
+    SetGearset(MotorGearset),
+    SetBrakeMode(MotorBrakeMode),
+    SetEncoderUnits(MotorEncoderUnits),
+
+    /// Set the motor's current limit (in mA).
+    SetCurrentLimit(i32),
+
+    /// Set the motor's voltage limit (in mV).
+    SetVoltageLimit(i32),
+}
+
+/// The gear cartridge installed in a motor.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MotorGearset {
+    /// 36:1 gear ratio (100 RPM, red cartridge).
+    Ratio36,
+
+    /// 18:1 gear ratio (200 RPM, green cartridge).
+    #[default]
+    Ratio18,
+
+    /// 6:1 gear ratio (600 RPM, blue cartridge).
+    Ratio6,
+}
+
+impl MotorGearset {
+    /// Returns the reduction between the armature and the output shaft.
+    pub const fn ratio(&self) -> f64 {
+        match self {
+            Self::Ratio36 => 36.0,
+            Self::Ratio18 => 18.0,
+            Self::Ratio6 => 6.0,
+        }
+    }
+
+    /// Returns the nominal free speed of the output shaft in RPM.
+    pub const fn max_velocity(&self) -> i32 {
+        match self {
+            Self::Ratio36 => 100,
+            Self::Ratio18 => 200,
+            Self::Ratio6 => 600,
+        }
+    }
+
+    /// Returns the number of encoder ticks per revolution of the output shaft.
+    pub const fn ticks_per_revolution(&self) -> f64 {
+        TICKS_PER_ARMATURE_REVOLUTION as f64 * self.ratio()
+    }
+}
+
+impl From<V5MotorGearset> for MotorGearset {
+    fn from(gearset: V5MotorGearset) -> Self {
+        match gearset {
+            V5MotorGearset::kMotorGearSet_36 => Self::Ratio36,
+            V5MotorGearset::kMotorGearSet_06 => Self::Ratio6,
+            _ => Self::Ratio18,
+        }
+    }
+}
+
+impl From<MotorGearset> for V5MotorGearset {
+    fn from(gearset: MotorGearset) -> Self {
+        match gearset {
+            MotorGearset::Ratio36 => Self::kMotorGearSet_36,
+            MotorGearset::Ratio18 => Self::kMotorGearSet_18,
+            MotorGearset::Ratio6 => Self::kMotorGearSet_06,
+        }
+    }
+}
+
+/// How a motor behaves when it is told to stop.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MotorBrakeMode {
+    /// The motor spins freely.
+    #[default]
+    Coast,
+
+    /// The motor's windings are shorted, resisting motion with back-EMF.
+    Brake,
+
+    /// The motor actively holds the position it stopped at.
+    Hold,
+}
+
+impl From<V5MotorBrakeMode> for MotorBrakeMode {
+    fn from(mode: V5MotorBrakeMode) -> Self {
+        match mode {
+            V5MotorBrakeMode::kV5MotorBrakeModeBrake => Self::Brake,
+            V5MotorBrakeMode::kV5MotorBrakeModeHold => Self::Hold,
+            _ => Self::Coast,
+        }
+    }
+}
+
+impl From<MotorBrakeMode> for V5MotorBrakeMode {
+    fn from(mode: MotorBrakeMode) -> Self {
+        match mode {
+            MotorBrakeMode::Coast => Self::kV5MotorBrakeModeCoast,
+            MotorBrakeMode::Brake => Self::kV5MotorBrakeModeBrake,
+            MotorBrakeMode::Hold => Self::kV5MotorBrakeModeHold,
+        }
+    }
+}
+
+/// The units that a motor's position is reported in.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MotorEncoderUnits {
+    #[default]
+    Degrees,
+    Rotations,
+    Counts,
+}
+
+impl MotorEncoderUnits {
+    /// Converts a raw encoder tick count into these units.
+    pub const fn from_ticks(&self, ticks: f64, gearset: MotorGearset) -> f64 {
+        match self {
+            Self::Degrees => ticks * 360.0 / gearset.ticks_per_revolution(),
+            Self::Rotations => ticks / gearset.ticks_per_revolution(),
+            Self::Counts => ticks,
+        }
+    }
+
+    /// Converts a position in these units into raw encoder ticks.
+    pub const fn to_ticks(&self, position: f64, gearset: MotorGearset) -> f64 {
+        match self {
+            Self::Degrees => position * gearset.ticks_per_revolution() / 360.0,
+            Self::Rotations => position * gearset.ticks_per_revolution(),
+            Self::Counts => position,
+        }
+    }
+}
+
+impl From<V5MotorEncoderUnits> for MotorEncoderUnits {
+    fn from(units: V5MotorEncoderUnits) -> Self {
+        match units {
+            V5MotorEncoderUnits::kMotorEncoderRotations => Self::Rotations,
+            V5MotorEncoderUnits::kMotorEncoderCounts => Self::Counts,
+            _ => Self::Degrees,
+        }
+    }
+}
+
+impl From<MotorEncoderUnits> for V5MotorEncoderUnits {
+    fn from(units: MotorEncoderUnits) -> Self {
+        match units {
+            MotorEncoderUnits::Degrees => Self::kMotorEncoderDegrees,
+            MotorEncoderUnits::Rotations => Self::kMotorEncoderRotations,
+            MotorEncoderUnits::Counts => Self::kMotorEncoderCounts,
+        }
+    }
 }
 
 bitflags! {
     /// The fault flags returned by a [`Motor`].
-    #[derive(Debug, Clone, Eq, PartialEq, PartialOrd)]
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd)]
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct MotorFaults: u32 {
         /// The motor's temperature is above its limit.
@@ -34,18 +288,22 @@ bitflags! {
     }
 }
 
+impl_bincode_bitflags!(MotorFaults);
+
 bitflags! {
     /// The status bits returned by a [`Motor`].
-    #[derive(Debug, Clone, Eq, PartialEq, PartialOrd)]
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd)]
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     pub struct MotorFlags: u32 {
         /// Failed communicate with the motor
         const BUSY = 0x01;
 
-        /// UNUSED: The motor is currently near zero velocity.
+        /// The motor is currently near zero velocity.
         const ZERO_VELOCITY = 0x02;
 
-        /// UNUSED: The motor is at its zero position.
+        /// The motor is at its zero position.
         const ZERO_POSITION = 0x04;
     }
 }
+
+impl_bincode_bitflags!(MotorFlags);
@@ -0,0 +1,91 @@
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OpticalSensorData {
+    /// Hue of the detected color in degrees, bounded from 0 to 360.
+    pub hue: f64,
+    /// Saturation of the detected color, bounded from 0 to 1.
+    pub saturation: f64,
+    /// Brightness of the detected color, bounded from 0 to 1.
+    pub brightness: f64,
+    /// Proximity of the nearest object, from 0 (nothing detected) to 255
+    /// (touching the sensor).
+    pub proximity: i32,
+    pub rgb: OpticalRgb,
+    pub raw: OpticalRaw,
+    /// Brightness of the sensor's LED in percent.
+    pub led_pwm: i32,
+    pub status: u32,
+    pub mode: u32,
+    /// Proximity at which the sensor starts detecting gestures.
+    pub proximity_threshold: i32,
+    /// Time that the sensor spends collecting light for each reading in
+    /// milliseconds.
+    pub integration_time: f64,
+    pub gestures_enabled: bool,
+    /// The most recent gesture detected by the sensor.
+    pub gesture: Option<OpticalGesture>,
+}
+
+/// Detected color converted to RGB, with each channel bounded from 0 to 255.
+#[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OpticalRgb {
+    pub red: f64,
+    pub green: f64,
+    pub blue: f64,
+    pub brightness: f64,
+}
+
+/// Raw light counts from each of the sensor's photodiodes.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OpticalRaw {
+    pub clear: u16,
+    pub red: u16,
+    pub green: u16,
+    pub blue: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OpticalGesture {
+    pub direction: GestureDirection,
+    /// Number of gestures detected since the sensor was powered on.
+    pub count: u16,
+    /// Timestamp at which the gesture was detected in milliseconds.
+    pub time: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GestureDirection {
+    Up = 1,
+    Down = 2,
+    Left = 3,
+    Right = 4,
+}
+
+/// A command sent from the brain to an optical sensor.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OpticalSensorCommand {
+    /// Set the brightness of the sensor's LED (in percent).
+    SetLedPwm(i32),
+
+    /// Set the sensor's operating mode.
+    SetMode(u32),
+
+    /// Enable or disable gesture detection.
+    SetGesturesEnabled(bool),
+
+    /// Set the proximity at which the sensor starts detecting gestures.
+    SetProximityThreshold(i32),
+
+    /// Set the time that the sensor spends collecting light for each reading
+    /// (in milliseconds).
+    SetIntegrationTime(f64),
+}
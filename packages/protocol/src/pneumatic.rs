@@ -0,0 +1,73 @@
+use bincode::{Decode, Encode};
+use bitflags::bitflags;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::impl_bincode_bitflags;
+
+/// Number of cylinders that can be connected to a pneumatic controller.
+pub const PNEUMATIC_CYLINDER_COUNT: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PneumaticData {
+    /// Gauge pressure of the air tank in PSI.
+    pub pressure: f64,
+    /// Whether the brain has enabled the compressor.
+    pub compressor_enabled: bool,
+    /// PWM duty cycle of the compressor in percent.
+    pub compressor_pwm: u8,
+    pub cylinders: [PneumaticCylinder; PNEUMATIC_CYLINDER_COUNT],
+    pub status: PneumaticStatus,
+}
+
+/// The state of a single cylinder's solenoid valve.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PneumaticCylinder {
+    pub extended: bool,
+    /// PWM duty cycle used to drive the solenoid in percent.
+    pub pwm: u8,
+    /// Number of times the cylinder has been actuated.
+    pub actuations: u16,
+}
+
+/// A command sent from the brain to a pneumatic controller.
+///
+/// Cylinders are zero-indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PneumaticCommand {
+    /// Enable or disable the compressor.
+    SetCompressor(bool),
+
+    /// Set the PWM duty cycle of the compressor (in percent).
+    SetCompressorPwm(u8),
+
+    /// Extend or retract a cylinder.
+    SetCylinder { id: u8, extended: bool },
+
+    /// Set the PWM duty cycle used to drive a cylinder's solenoid (in
+    /// percent).
+    SetCylinderPwm { id: u8, pwm: u8 },
+}
+
+bitflags! {
+    /// The status bits returned by a [`PneumaticData`].
+    #[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd)]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    pub struct PneumaticStatus: u32 {
+        /// The first cylinder is extended.
+        const CYLINDER_1 = 1 << 0;
+        /// The second cylinder is extended.
+        const CYLINDER_2 = 1 << 1;
+        /// The third cylinder is extended.
+        const CYLINDER_3 = 1 << 2;
+        /// The fourth cylinder is extended.
+        const CYLINDER_4 = 1 << 3;
+        /// The compressor is running.
+        const COMPRESSOR_RUNNING = 1 << 4;
+    }
+}
+
+impl_bincode_bitflags!(PneumaticStatus);
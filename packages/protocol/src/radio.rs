@@ -0,0 +1,39 @@
+use alloc::{string::String, vec::Vec};
+
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Number of bytes that a radio can hold before they are sent over the link.
+pub const RADIO_TX_BUFFER_SIZE: usize = 512;
+
+/// Which end of a VEXlink connection a radio is.
+///
+/// A link can only be established between one manager and one worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RadioRole {
+    Manager,
+    Worker,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RadioData {
+    /// Whether a link has been established with another radio.
+    pub linked: bool,
+    /// Number of bytes that can be transmitted before the radio's buffer is
+    /// full.
+    pub write_free: u16,
+}
+
+/// A command sent from the brain to a radio.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RadioCommand {
+    /// Switches the radio into VEXlink mode and starts looking for a radio
+    /// with the same link ID and the opposite role.
+    Connect { link_id: String, role: RadioRole },
+    /// Bytes to send to the linked radio.
+    Transmit(Vec<u8>),
+}
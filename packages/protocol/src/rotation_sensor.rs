@@ -0,0 +1,39 @@
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RotationSensorData {
+    /// Continuous position of the sensor in centidegrees.
+    pub position: i32,
+    /// Absolute angle of the sensor in centidegrees, bounded from 0 to 36000.
+    pub angle: i32,
+    /// Angular velocity of the sensor in centidegrees per second.
+    pub velocity: i32,
+    /// Whether the sensor's readings are reversed.
+    pub reversed: bool,
+    pub status: u32,
+    /// Temperature of the sensor in degrees celsius.
+    pub temperature: f64,
+    /// Interval at which the sensor produces new readings in milliseconds.
+    pub data_rate: u32,
+}
+
+/// A command sent from the brain to a rotation sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RotationSensorCommand {
+    /// Reset the sensor's position to its current absolute angle.
+    Reset,
+
+    /// Set the sensor's position (in centidegrees).
+    SetPosition(i32),
+
+    /// Set whether the sensor's readings are reversed.
+    SetReversed(bool),
+
+    /// Set the interval at which the sensor produces new readings (in
+    /// milliseconds).
+    SetDataRate(u32),
+}
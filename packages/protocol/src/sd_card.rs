@@ -0,0 +1,132 @@
+use alloc::{string::String, vec::Vec};
+
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vex_sdk::FRESULT;
+
+/// How a file on the SD card is opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OpenMode {
+    /// Open an existing file for reading.
+    Read,
+    /// Open a file for writing at its end, creating it if it doesn't exist.
+    Append,
+    /// Create a file for writing, replacing it if it already exists.
+    Create,
+}
+
+/// A position in a file to seek to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SeekFrom {
+    Start(u32),
+    Current(i32),
+    End(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FileKind {
+    File,
+    Directory,
+}
+
+/// A filesystem operation requested by the brain.
+///
+/// Files are referred to by the descriptor returned when they were opened.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FileRequest {
+    /// Returns whether an SD card is inserted.
+    DriveStatus,
+    Open {
+        path: String,
+        mode: OpenMode,
+    },
+    Close {
+        fd: u32,
+    },
+    /// Reads up to `len` bytes from the file's current position.
+    Read {
+        fd: u32,
+        len: u32,
+    },
+    /// Writes bytes at the file's current position.
+    Write {
+        fd: u32,
+        data: Vec<u8>,
+    },
+    Seek {
+        fd: u32,
+        position: SeekFrom,
+    },
+    Tell {
+        fd: u32,
+    },
+    Size {
+        fd: u32,
+    },
+    /// Flushes any buffered writes to the card.
+    Sync {
+        fd: u32,
+    },
+    /// Lists the names of the entries in a directory.
+    ReadDirectory {
+        path: String,
+    },
+    /// Returns what kind of entry is at a path, if any.
+    Status {
+        path: String,
+    },
+}
+
+/// The result of a successful [`FileRequest`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FileResponse {
+    DriveStatus { inserted: bool },
+    Opened { fd: u32 },
+    Data(Vec<u8>),
+    Written(u32),
+    Position(u32),
+    Size(u32),
+    Directory(Vec<String>),
+    Status(Option<FileKind>),
+    Done,
+}
+
+/// The reason a [`FileRequest`] failed.
+///
+/// These match the FatFs result codes returned by VEXos.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u8)]
+pub enum FileError {
+    DiskError = 1,
+    InternalError = 2,
+    NotReady = 3,
+    NoFile = 4,
+    NoPath = 5,
+    InvalidName = 6,
+    Denied = 7,
+    Exists = 8,
+    InvalidObject = 9,
+    WriteProtected = 10,
+    InvalidDrive = 11,
+    NotEnabled = 12,
+    NoFilesystem = 13,
+    MkfsAborted = 14,
+    Timeout = 15,
+    Locked = 16,
+    NotEnoughCore = 17,
+    TooManyOpenFiles = 18,
+    InvalidParameter = 19,
+}
+
+impl From<FileError> for FRESULT {
+    fn from(error: FileError) -> Self {
+        Self(error as u8)
+    }
+}
@@ -25,4 +25,7 @@ impl Default for TouchData {
 pub enum TouchEvent {
     Press,
     Release,
+    /// The screen is still being pressed, possibly at a different point than
+    /// it was first pressed at.
+    Held,
 }
@@ -0,0 +1,177 @@
+use alloc::vec::Vec;
+
+use bincode::{Decode, Encode};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use vex_sdk::{
+    V5VisionBlockType, V5_DeviceVisionObject, V5_DeviceVisionRgb, V5_DeviceVisionSignature,
+};
+
+/// Number of color signatures that can be stored on a vision sensor.
+pub const VISION_SIGNATURE_COUNT: usize = 7;
+
+#[derive(Debug, Clone, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VisionData {
+    /// Objects currently detected by the sensor, largest first.
+    pub objects: Vec<VisionObject>,
+    /// Color signatures configured by the brain.
+    pub signatures: Vec<VisionSignature>,
+    pub mode: u8,
+    /// Exposure of the camera in percent.
+    pub brightness: u8,
+    pub white_balance_mode: u8,
+    pub white_balance: VisionRgb,
+    pub led_mode: u8,
+    /// Brightness of the LED in percent.
+    pub led_brightness: u8,
+    pub led_color: VisionRgb,
+    pub wifi_mode: u8,
+}
+
+/// An object detected by the vision sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VisionObject {
+    /// ID of the signature matched by the object, or the color code of the
+    /// object if `kind` is [`VisionObjectKind::ColorCode`].
+    pub signature: u16,
+    pub kind: VisionObjectKind,
+    /// Position of the left side of the object in pixels.
+    pub left: u16,
+    /// Position of the top of the object in pixels.
+    pub top: u16,
+    pub width: u16,
+    pub height: u16,
+    /// Angle of a color code in tenths of a degree.
+    pub angle: u16,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum VisionObjectKind {
+    #[default]
+    Normal,
+    ColorCode,
+    LineDetect,
+}
+
+impl From<VisionObject> for V5_DeviceVisionObject {
+    fn from(object: VisionObject) -> Self {
+        Self {
+            signature: object.signature,
+            r#type: match object.kind {
+                VisionObjectKind::Normal => V5VisionBlockType::kVisionTypeNormal,
+                VisionObjectKind::ColorCode => V5VisionBlockType::kVisionTypeColorCode,
+                VisionObjectKind::LineDetect => V5VisionBlockType::kVisionTypeLineDetect,
+            },
+            xoffset: object.left,
+            yoffset: object.top,
+            width: object.width,
+            height: object.height,
+            angle: object.angle,
+        }
+    }
+}
+
+/// A color signature that the vision sensor matches objects against.
+///
+/// Colors are described by ranges in the sensor's YUV color space.
+#[derive(Default, Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VisionSignature {
+    /// ID of the signature, from 1 to 7.
+    pub id: u8,
+    pub flags: u8,
+    pub range: f32,
+    pub u_min: i32,
+    pub u_max: i32,
+    pub u_mean: i32,
+    pub v_min: i32,
+    pub v_max: i32,
+    pub v_mean: i32,
+    pub rgb: u32,
+    pub kind: u32,
+}
+
+impl From<V5_DeviceVisionSignature> for VisionSignature {
+    fn from(signature: V5_DeviceVisionSignature) -> Self {
+        Self {
+            id: signature.id,
+            flags: signature.flags,
+            range: signature.range,
+            u_min: signature.uMin,
+            u_max: signature.uMax,
+            u_mean: signature.uMean,
+            v_min: signature.vMin,
+            v_max: signature.vMax,
+            v_mean: signature.vMean,
+            rgb: signature.mRgb,
+            kind: signature.mType,
+        }
+    }
+}
+
+impl From<VisionSignature> for V5_DeviceVisionSignature {
+    fn from(signature: VisionSignature) -> Self {
+        Self {
+            id: signature.id,
+            flags: signature.flags,
+            pad: [0; 2],
+            range: signature.range,
+            uMin: signature.u_min,
+            uMax: signature.u_max,
+            uMean: signature.u_mean,
+            vMin: signature.v_min,
+            vMax: signature.v_max,
+            vMean: signature.v_mean,
+            mRgb: signature.rgb,
+            mType: signature.kind,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VisionRgb {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub brightness: u8,
+}
+
+impl From<V5_DeviceVisionRgb> for VisionRgb {
+    fn from(rgb: V5_DeviceVisionRgb) -> Self {
+        Self {
+            red: rgb.red,
+            green: rgb.green,
+            blue: rgb.blue,
+            brightness: rgb.brightness,
+        }
+    }
+}
+
+impl From<VisionRgb> for V5_DeviceVisionRgb {
+    fn from(rgb: VisionRgb) -> Self {
+        Self {
+            red: rgb.red,
+            green: rgb.green,
+            blue: rgb.blue,
+            brightness: rgb.brightness,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Encode, Decode)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum VisionCommand {
+    SetMode(u8),
+    SetSignature(VisionSignature),
+    SetBrightness(u8),
+    SetWhiteBalanceMode(u8),
+    SetWhiteBalance(VisionRgb),
+    SetLedMode(u8),
+    SetLedBrightness(u8),
+    SetLedColor(VisionRgb),
+    SetWifiMode(u8),
+}